@@ -0,0 +1,66 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use bench::{
+    harness::Mode, CipherSuite, CryptoConfig, ECGroup, RustlsHarness, S2NHarness, TlsBenchHarness,
+};
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+
+/// Measures handshakes as the client offers more ALPN protocols, where the
+/// only protocol the server supports is the last one offered
+///
+/// The number of bytes sent by the client is printed for each list length,
+/// since it grows with the ClientHello
+pub fn bench_alpn(c: &mut Criterion) {
+    let crypto_config = CryptoConfig {
+        cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+        ec_group: ECGroup::SECP256R1,
+    };
+
+    for protocol_count in [1, 10, 50] {
+        let protocols: Vec<Vec<u8>> = (0..protocol_count)
+            .map(|i| format!("protocol-{i}").into_bytes())
+            .collect();
+        let client_protocols: Vec<&[u8]> = protocols.iter().map(Vec::as_slice).collect();
+        let server_protocols = [protocols.last().unwrap().as_slice()];
+
+        let mut group = c.benchmark_group(format!("alpn-{protocol_count}-protocols"));
+
+        macro_rules! bench_alpn_for_libraries {
+            ($(($lib_name:expr, $lib_type:ty),)*) => {
+            $(
+                let new_harness = || {
+                    <$lib_type>::new_with_alpn(&crypto_config, &client_protocols, &server_protocols)
+                        .unwrap()
+                };
+                let mut harness = new_harness();
+                harness.handshake().unwrap();
+                assert!(harness.connection_info().alpn.is_some());
+                println!(
+                    "{} client bytes with {protocol_count} protocols: {}",
+                    $lib_name,
+                    harness.bytes_sent(Mode::Client)
+                );
+
+                group.bench_function(BenchmarkId::from_parameter($lib_name), |b| {
+                    b.iter_batched_ref(
+                        new_harness,
+                        |harness| harness.handshake().unwrap(),
+                        BatchSize::SmallInput,
+                    )
+                });
+            )*
+            }
+        }
+
+        bench_alpn_for_libraries! {
+            ("s2n-tls", S2NHarness),
+            ("rustls", RustlsHarness),
+        }
+
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_alpn);
+criterion_main!(benches);
@@ -0,0 +1,75 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use bench::{
+    CertConfig, CipherSuite, CryptoConfig, ECGroup, IntermediateOrder, S2NHarness, TlsBenchHarness,
+};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, SamplingMode};
+use s2n_tls::enums::Blinding;
+use std::time::Duration;
+
+const BLINDING_MODES: [(&str, Blinding); 2] = [
+    ("self-service", Blinding::SelfService),
+    ("built-in", Blinding::BuiltIn),
+];
+
+/// Measures successful handshakes under each blinding mode, which should
+/// match since blinding only applies to errors
+pub fn bench_blinding(c: &mut Criterion) {
+    let crypto_config = CryptoConfig {
+        cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+        ec_group: ECGroup::SECP256R1,
+    };
+    let mut group = c.benchmark_group("blinding-success-s2n-tls");
+    for (name, blinding) in BLINDING_MODES {
+        group.bench_function(name, |b| {
+            b.iter_batched_ref(
+                || {
+                    S2NHarness::new_with_blinding(&crypto_config, &CertConfig::default(), blinding)
+                        .unwrap()
+                },
+                |harness| harness.handshake().unwrap(),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+/// Set to also measure failed handshakes with built-in blinding
+const BUILTIN_FAILURE_VAR: &str = "BENCH_BUILTIN_BLINDING_FAILURE";
+
+/// Measures handshakes that fail cert validation under each blinding mode
+///
+/// Built-in blinding sleeps for 10 to 30 seconds on each failure, so it's
+/// only measured if [`BUILTIN_FAILURE_VAR`] is set. Flat sampling runs one
+/// handshake per sample, so that takes 11 handshakes, or 2 to 6 minutes.
+pub fn bench_blinding_failure(c: &mut Criterion) {
+    let crypto_config = CryptoConfig {
+        cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+        ec_group: ECGroup::SECP256R1,
+    };
+    let cert_config = CertConfig::with_intermediates(IntermediateOrder::Missing);
+    let mut group = c.benchmark_group("blinding-failure-s2n-tls");
+    group
+        .sampling_mode(SamplingMode::Flat)
+        .sample_size(10)
+        .warm_up_time(Duration::from_millis(1))
+        .measurement_time(Duration::from_secs(1));
+    for (name, blinding) in BLINDING_MODES {
+        if blinding == Blinding::BuiltIn && std::env::var_os(BUILTIN_FAILURE_VAR).is_none() {
+            continue;
+        }
+        group.bench_function(name, |b| {
+            b.iter_batched_ref(
+                || S2NHarness::new_with_blinding(&crypto_config, &cert_config, blinding).unwrap(),
+                |harness| assert!(harness.handshake().is_err()),
+                BatchSize::PerIteration,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_blinding, bench_blinding_failure);
+criterion_main!(benches);
@@ -0,0 +1,155 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use bench::{
+    harness::Mode, CertConfig, CipherSuite, CryptoConfig, ECGroup, HandshakeType,
+    IntermediateOrder, RustlsHarness, S2NHarness, SigType, TlsBenchHarness,
+};
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+
+pub fn bench_cert_chain(c: &mut Criterion) {
+    let crypto_config = CryptoConfig {
+        cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+        ec_group: ECGroup::SECP256R1,
+    };
+
+    // a missing intermediate fails verification, so only valid chains are benched
+    for order in [IntermediateOrder::Ordered, IntermediateOrder::Reversed] {
+        let cert_config = CertConfig::with_intermediates(order);
+        let mut group = c.benchmark_group(format!("cert-chain-{order:?}"));
+
+        macro_rules! bench_cert_chain_for_libraries {
+            ($(($lib_name:expr, $lib_type:ty),)*) => {
+            $(
+                group.bench_function($lib_name, |b| {
+                    b.iter_batched_ref(
+                        || {
+                            <$lib_type>::new_with_certs(
+                                &crypto_config,
+                                HandshakeType::ServerAuth,
+                                &cert_config,
+                            )
+                            .unwrap()
+                        },
+                        |harness| {
+                            harness.handshake().unwrap();
+                        },
+                        BatchSize::SmallInput,
+                    )
+                });
+            )*
+            }
+        }
+
+        bench_cert_chain_for_libraries! {
+            ("s2n-tls", S2NHarness),
+            ("rustls", RustlsHarness),
+        }
+
+        group.finish();
+    }
+}
+
+/// Measures how handshakes scale with the number of certs in the server's
+/// Certificate message, from 2 to 6
+///
+/// The bytes sent by the server for each length are printed before it's
+/// measured, since the wire size grows along with the verification cost.
+pub fn bench_cert_chain_len(c: &mut Criterion) {
+    let crypto_config = CryptoConfig {
+        cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+        ec_group: ECGroup::SECP256R1,
+    };
+
+    macro_rules! bench_cert_chain_len_for_libraries {
+        ($(($lib_name:expr, $lib_type:ty),)*) => {
+        $(
+            let mut group = c.benchmark_group(format!("cert-chain-len-{}", $lib_name));
+            for len in 2..=6 {
+                let cert_config = CertConfig::with_chain_len(len, IntermediateOrder::Ordered);
+                let new_harness = || {
+                    <$lib_type>::new_with_certs(
+                        &crypto_config,
+                        HandshakeType::ServerAuth,
+                        &cert_config,
+                    )
+                    .unwrap()
+                };
+
+                let mut harness = new_harness();
+                harness.handshake().unwrap();
+                println!(
+                    "{} {len} certs: {} server bytes",
+                    $lib_name,
+                    harness.bytes_sent(Mode::Server)
+                );
+
+                group.bench_function(BenchmarkId::from_parameter(len), |b| {
+                    b.iter_batched_ref(
+                        new_harness,
+                        |harness| harness.handshake().unwrap(),
+                        BatchSize::SmallInput,
+                    )
+                });
+            }
+            group.finish();
+        )*
+        }
+    }
+
+    bench_cert_chain_len_for_libraries! {
+        ("s2n-tls", S2NHarness),
+        ("rustls", RustlsHarness),
+    }
+}
+
+/// Compares chains where the leaf has a different key type than its issuer,
+/// so verification switches signature algorithms up the chain, against
+/// chains with a single key type
+pub fn bench_mixed_cert_chain(c: &mut Criterion) {
+    let crypto_config = CryptoConfig {
+        cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+        ec_group: ECGroup::SECP256R1,
+    };
+
+    macro_rules! bench_mixed_cert_chain_for_libraries {
+        ($(($lib_name:expr, $lib_type:ty),)*) => {
+        $(
+            let mut group = c.benchmark_group(format!("mixed-cert-chain-{}", $lib_name));
+            for ca in [SigType::Ecdsa, SigType::Rsa] {
+                for leaf in [SigType::Ecdsa, SigType::Rsa] {
+                    let cert_config = CertConfig::with_chain_sig_types(ca, leaf);
+                    group.bench_function(format!("{ca:?}-ca-{leaf:?}-leaf"), |b| {
+                        b.iter_batched_ref(
+                            || {
+                                <$lib_type>::new_with_certs(
+                                    &crypto_config,
+                                    HandshakeType::ServerAuth,
+                                    &cert_config,
+                                )
+                                .unwrap()
+                            },
+                            |harness| harness.handshake().unwrap(),
+                            BatchSize::SmallInput,
+                        )
+                    });
+                }
+            }
+            group.finish();
+        )*
+        }
+    }
+
+    bench_mixed_cert_chain_for_libraries! {
+        ("s2n-tls", S2NHarness),
+        ("rustls", RustlsHarness),
+    }
+}
+
+criterion_group!(
+    benches,
+    bench_cert_chain,
+    bench_cert_chain_len,
+    bench_mixed_cert_chain
+);
+criterion_main!(benches);
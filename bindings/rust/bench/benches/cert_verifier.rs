@@ -0,0 +1,48 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use bench::{
+    AcceptAnyServerCert, CipherSuite, CryptoConfig, ECGroup, HandshakeType, RustlsHarness,
+    TlsBenchHarness,
+};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use std::sync::Arc;
+
+/// Compares handshakes using the built-in server certificate verifier with
+/// handshakes using a custom verifier that accepts any certificate chain
+///
+/// The difference is the cost of chain validation minus the cost of calling
+/// the custom verifier.
+pub fn bench_cert_verifier(c: &mut Criterion) {
+    let crypto_config = CryptoConfig {
+        cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+        ec_group: ECGroup::SECP256R1,
+    };
+    let mut group = c.benchmark_group("cert-verifier-rustls");
+
+    group.bench_function("built-in", |b| {
+        b.iter_batched_ref(
+            || RustlsHarness::new(&crypto_config, HandshakeType::ServerAuth).unwrap(),
+            |harness| harness.handshake().unwrap(),
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("custom-no-op", |b| {
+        b.iter_batched_ref(
+            || {
+                RustlsHarness::new_with_server_cert_verifier(
+                    &crypto_config,
+                    Arc::new(AcceptAnyServerCert),
+                )
+                .unwrap()
+            },
+            |harness| harness.handshake().unwrap(),
+            BatchSize::SmallInput,
+        )
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_cert_verifier);
+criterion_main!(benches);
@@ -0,0 +1,48 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use bench::{
+    harness::Mode, CipherSuite, CryptoConfig, ECGroup, HandshakeType, S2NHarness, TlsBenchHarness,
+};
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+
+/// Measures s2n-tls handshakes where the client sends only the extensions
+/// s2n-tls always sends, against ones where it also sends the extensions a
+/// browser would, which the server has to parse
+///
+/// The size of the ClientHello is printed for each extension set
+pub fn bench_client_hello_extensions(c: &mut Criterion) {
+    let crypto_config = CryptoConfig {
+        cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+        ec_group: ECGroup::SECP256R1,
+    };
+    let mut group = c.benchmark_group("client-hello-extensions");
+
+    let new_minimal = || S2NHarness::new(&crypto_config, HandshakeType::ServerAuth).unwrap();
+    let new_browser = || S2NHarness::new_with_browser_extensions(&crypto_config).unwrap();
+    let extension_sets: [(&str, &dyn Fn() -> S2NHarness); 2] =
+        [("minimal", &new_minimal), ("browser", &new_browser)];
+
+    for (name, new_harness) in extension_sets {
+        let mut harness = new_harness();
+        // the first step only sends the ClientHello
+        harness.handshake_step(Mode::Client).unwrap();
+        println!(
+            "{name} ClientHello bytes: {}",
+            harness.bytes_sent(Mode::Client)
+        );
+
+        group.bench_function(BenchmarkId::from_parameter(name), |b| {
+            b.iter_batched_ref(
+                new_harness,
+                |harness| harness.handshake().unwrap(),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_client_hello_extensions);
+criterion_main!(benches);
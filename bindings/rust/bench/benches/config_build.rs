@@ -0,0 +1,44 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use bench::{CipherSuite, CryptoConfig, ECGroup, HandshakeType, RustlsHarness, S2NHarness};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+/// Measures building client and server configs with and without client auth,
+/// which loads the client's cert and key and the CA the server verifies it
+/// with
+///
+/// Cert files are only read from disk the first time, so only parsing and
+/// building is measured.
+pub fn bench_config_build(c: &mut Criterion) {
+    let crypto_config = CryptoConfig {
+        cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+        ec_group: ECGroup::SECP256R1,
+    };
+
+    macro_rules! bench_config_build_for_libraries {
+        ($(($lib_name:expr, $lib_type:ty),)*) => {
+        $(
+            let mut group = c.benchmark_group(format!("config-build-{}", $lib_name));
+            for handshake_type in [HandshakeType::ServerAuth, HandshakeType::MutualAuth] {
+                group.bench_with_input(
+                    BenchmarkId::from_parameter(format!("{handshake_type:?}")),
+                    &handshake_type,
+                    |b, &handshake_type| {
+                        b.iter(|| <$lib_type>::build_configs(&crypto_config, handshake_type).unwrap())
+                    },
+                );
+            }
+            group.finish();
+        )*
+        }
+    }
+
+    bench_config_build_for_libraries! {
+        ("s2n-tls", S2NHarness),
+        ("rustls", RustlsHarness),
+    }
+}
+
+criterion_group!(benches, bench_config_build);
+criterion_main!(benches);
@@ -0,0 +1,37 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use bench::{RustlsHarness, S2NHarness, TlsBenchHarness};
+use criterion::{criterion_group, criterion_main, Criterion};
+
+/// Measures the fixed cost of setting up connections from configs shared
+/// with existing connections, without handshaking
+///
+/// Both libraries share configs by reference counting, so this is mostly the
+/// cost of initializing the connections. rustls clients also build their
+/// ClientHello, including key shares, when they're initialized.
+pub fn bench_config_clone(c: &mut Criterion) {
+    let mut group = c.benchmark_group("config-clone");
+
+    macro_rules! bench_config_clone_for_libraries {
+        ($(($lib_name:expr, $lib_type:ty),)*) => {
+        $(
+            let harness = <$lib_type>::default().unwrap();
+            group.bench_function($lib_name, |b| {
+                // dropping the connections isn't measured
+                b.iter_with_large_drop(|| harness.new_with_same_configs().unwrap())
+            });
+        )*
+        }
+    }
+
+    bench_config_clone_for_libraries! {
+        ("s2n-tls", S2NHarness),
+        ("rustls", RustlsHarness),
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_config_clone);
+criterion_main!(benches);
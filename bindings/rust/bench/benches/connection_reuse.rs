@@ -0,0 +1,36 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use bench::{CipherSuite, CryptoConfig, ECGroup, HandshakeType, S2NHarness, TlsBenchHarness};
+use criterion::{criterion_group, criterion_main, Criterion};
+
+/// Compares handshakes on s2n-tls connections reused with `wipe`, as in a
+/// connection pool, with handshakes on newly allocated connections
+///
+/// Both reuse the same configs, and resetting is included in the timing.
+pub fn bench_connection_reuse(c: &mut Criterion) {
+    let crypto_config = CryptoConfig {
+        cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+        ec_group: ECGroup::SECP256R1,
+    };
+    let mut harness = S2NHarness::new(&crypto_config, HandshakeType::ServerAuth).unwrap();
+    let mut group = c.benchmark_group("connection-reuse-s2n-tls");
+
+    group.bench_function("wipe", |b| {
+        b.iter(|| {
+            harness.reset().unwrap();
+            harness.handshake().unwrap();
+        })
+    });
+    group.bench_function("new", |b| {
+        b.iter(|| {
+            harness.reset_with_new_connections().unwrap();
+            harness.handshake().unwrap();
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_connection_reuse);
+criterion_main!(benches);
@@ -0,0 +1,61 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use bench::{
+    harness::Mode, AlertDescription, CipherSuite, CryptoConfig, ECGroup, HandshakeType,
+    RustlsHarness, S2NHarness, TlsBenchHarness,
+};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+
+/// Amount of application data in the corrupted record
+const DATA_LEN: usize = 1024;
+const RECORD_HEADER_LEN: usize = 5;
+
+/// Measures receiving a record whose ciphertext has one bit flipped, which
+/// fails the AEAD tag check
+///
+/// s2n-tls connections use self-service blinding in the harness, so the
+/// blinding delay isn't included.
+pub fn bench_corrupt_record(c: &mut Criterion) {
+    let crypto_config = CryptoConfig {
+        cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+        ec_group: ECGroup::SECP256R1,
+    };
+    let data = [1; DATA_LEN];
+    let mut buf = [0; DATA_LEN];
+    let mut group = c.benchmark_group("corrupt-record");
+
+    macro_rules! bench_corrupt_record_for_libraries {
+        ($(($lib_name:expr, $lib_type:ty),)*) => {
+        $(
+            group.bench_function($lib_name, |b| {
+                b.iter_batched_ref(
+                    || {
+                        let mut harness =
+                            <$lib_type>::new(&crypto_config, HandshakeType::ServerAuth).unwrap();
+                        harness.handshake().unwrap();
+                        harness.send(Mode::Client, &data).unwrap();
+                        harness.corrupt_pending_data(Mode::Server, RECORD_HEADER_LEN);
+                        harness
+                    },
+                    |harness| {
+                        assert!(harness.recv(Mode::Server, &mut buf).is_err());
+                        assert_eq!(harness.last_alert(), Some(AlertDescription::BadRecordMac));
+                    },
+                    BatchSize::SmallInput,
+                )
+            });
+        )*
+        }
+    }
+
+    bench_corrupt_record_for_libraries! {
+        ("s2n-tls", S2NHarness),
+        ("rustls", RustlsHarness),
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_corrupt_record);
+criterion_main!(benches);
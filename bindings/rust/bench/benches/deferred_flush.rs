@@ -0,0 +1,62 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use bench::{
+    harness::Mode, CipherSuite, CryptoConfig, ECGroup, HandshakeType, S2NHarness, TlsBenchHarness,
+};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+/// Length of each application write
+const WRITE_LEN: usize = 100;
+/// Number of application writes sent per iteration
+const WRITES: usize = 100;
+
+/// Measures sending small writes when the transport is flushed after every
+/// write versus after every `writes_per_flush` writes
+///
+/// s2n-tls writes each record to the transport as it's sent, so only the
+/// transport's flushes are deferred. The number of transport writes and
+/// flushes per iteration is printed for each flush interval.
+pub fn bench_deferred_flush(c: &mut Criterion) {
+    let crypto_config = CryptoConfig {
+        cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+        ec_group: ECGroup::SECP256R1,
+    };
+    let data = vec![1; WRITE_LEN];
+    let mut received = vec![0; WRITE_LEN * WRITES];
+
+    let mut group = c.benchmark_group("deferred-flush-s2n-tls");
+    group.throughput(Throughput::Bytes(received.len() as u64));
+    for writes_per_flush in [1, 10, 100] {
+        let mut harness = S2NHarness::new(&crypto_config, HandshakeType::ServerAuth).unwrap();
+        harness.handshake().unwrap();
+        harness.set_deferred_flush(Mode::Client, true);
+
+        let mut send = |harness: &mut S2NHarness| {
+            for i in 1..=WRITES {
+                harness.send(Mode::Client, &data).unwrap();
+                if i % writes_per_flush == 0 {
+                    harness.flush(Mode::Client).unwrap();
+                }
+            }
+            harness.recv(Mode::Server, &mut received).unwrap();
+        };
+
+        let (flushes, writes) = harness.flush_and_write_calls(Mode::Client);
+        send(&mut harness);
+        let (flushes_after, writes_after) = harness.flush_and_write_calls(Mode::Client);
+        println!(
+            "{writes_per_flush} writes per flush: {} transport writes, {} flushes",
+            writes_after - writes,
+            flushes_after - flushes
+        );
+
+        group.bench_function(BenchmarkId::from_parameter(writes_per_flush), |b| {
+            b.iter(|| send(&mut harness))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_deferred_flush);
+criterion_main!(benches);
@@ -0,0 +1,54 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use bench::{
+    harness::Mode, CipherSuite, CryptoConfig, ECGroup, HandshakeType, RustlsHarness, S2NHarness,
+    TlsBenchHarness,
+};
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+
+/// Amount of application data encrypted per iteration
+const DATA_LEN: usize = 100_000;
+
+/// Measures encryption alone: the client sends data that the server never
+/// reads, and the encrypted records are discarded after each iteration
+pub fn bench_encrypt(c: &mut Criterion) {
+    let data = vec![0; DATA_LEN];
+
+    for cipher_suite in [
+        CipherSuite::AES_128_GCM_SHA256,
+        CipherSuite::AES_256_GCM_SHA384,
+    ] {
+        let mut group = c.benchmark_group(format!("encrypt-{cipher_suite:?}"));
+        group.throughput(Throughput::Bytes(DATA_LEN as u64));
+        let crypto_config = CryptoConfig {
+            cipher_suite,
+            ec_group: ECGroup::SECP256R1,
+        };
+
+        macro_rules! bench_encrypt_for_libraries {
+            ($(($lib_name:expr, $lib_type:ty),)*) => {
+            $(
+                let mut harness = <$lib_type>::new(&crypto_config, HandshakeType::ServerAuth).unwrap();
+                harness.handshake().unwrap();
+                group.bench_function($lib_name, |b| {
+                    b.iter(|| {
+                        harness.send(Mode::Client, &data).unwrap();
+                        harness.discard_pending_data(Mode::Server);
+                    })
+                });
+            )*
+            }
+        }
+
+        bench_encrypt_for_libraries! {
+            ("s2n-tls", S2NHarness),
+            ("rustls", RustlsHarness),
+        }
+
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_encrypt);
+criterion_main!(benches);
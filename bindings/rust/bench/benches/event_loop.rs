@@ -0,0 +1,31 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use bench::{S2NHarness, TlsBenchHarness};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+
+const MAX_POLLS: usize = 100;
+
+/// Compares driving s2n-tls handshakes by alternating between connections
+/// with driving them from a readiness-based event loop
+pub fn bench_event_loop(c: &mut Criterion) {
+    let mut group = c.benchmark_group("event-loop-s2n-tls");
+    group.bench_function("alternating", |b| {
+        b.iter_batched_ref(
+            || S2NHarness::default().unwrap(),
+            |harness| harness.handshake().unwrap(),
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("event-loop", |b| {
+        b.iter_batched_ref(
+            || S2NHarness::default().unwrap(),
+            |harness| harness.handshake_with_event_loop(MAX_POLLS).unwrap(),
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_event_loop);
+criterion_main!(benches);
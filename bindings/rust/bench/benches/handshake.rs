@@ -1,37 +1,102 @@
 // Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
 // SPDX-License-Identifier: Apache-2.0
 
-use bench::{RustlsHarness, S2NHarness, TlsBenchHarness};
+use bench::{
+    CertConfig, CipherSuite, CryptoConfig, ECGroup, HandshakeType, RustlsHarness, S2NHarness,
+    SigType, TlsBenchHarness,
+};
 use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
 
 pub fn bench_handshake(c: &mut Criterion) {
-    let mut group = c.benchmark_group("handshake");
-
-    macro_rules! bench_handshake_for_libraries {
-        ($(($lib_name:expr, $lib_type:ty),)*) => {
-        $(
-            // generate all inputs (TlsBenchHarness structs) before benchmarking handshakes
-            // timing only includes negotiation, not config/connection initialization
-            group.bench_function($lib_name, |b| {
-                b.iter_batched_ref(
-                    || <$lib_type>::default().unwrap(),
-                    |harness| {
-                        harness.handshake().unwrap();
-                    },
-                    BatchSize::SmallInput,
-                )
-            });
-        )*
+    let crypto_config = CryptoConfig {
+        cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+        ec_group: ECGroup::SECP256R1,
+    };
+
+    for handshake_type in [
+        HandshakeType::ServerAuth,
+        HandshakeType::MutualAuth,
+        HandshakeType::OptionalClientAuth,
+    ] {
+        let mut group = c.benchmark_group(format!("handshake-{handshake_type:?}"));
+
+        macro_rules! bench_handshake_for_libraries {
+            ($(($lib_name:expr, $lib_type:ty),)*) => {
+            $(
+                // generate all inputs (TlsBenchHarness structs) before benchmarking handshakes
+                // timing only includes negotiation, not config/connection initialization
+                group.bench_function($lib_name, |b| {
+                    b.iter_batched_ref(
+                        || <$lib_type>::new(&crypto_config, handshake_type).unwrap(),
+                        |harness| {
+                            harness.handshake().unwrap();
+                        },
+                        BatchSize::SmallInput,
+                    )
+                });
+            )*
+            }
         }
-    }
 
-    bench_handshake_for_libraries! {
-        ("s2n-tls", S2NHarness),
-        ("rustls", RustlsHarness),
+        bench_handshake_for_libraries! {
+            ("s2n-tls", S2NHarness),
+            ("rustls", RustlsHarness),
+        }
+
+        group.finish();
     }
+}
+
+/// Measures mutual auth handshakes where the server and client certs have
+/// different key types
+pub fn bench_mixed_sig_types(c: &mut Criterion) {
+    let crypto_config = CryptoConfig {
+        cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+        ec_group: ECGroup::SECP256R1,
+    };
 
-    group.finish();
+    for (server, client) in [
+        (SigType::Ecdsa, SigType::Ecdsa),
+        (SigType::Ecdsa, SigType::Rsa),
+        (SigType::Rsa, SigType::Ecdsa),
+        (SigType::Rsa, SigType::Rsa),
+    ] {
+        let cert_config = CertConfig::with_sig_types(server, client);
+        let mut group = c.benchmark_group(format!(
+            "handshake-MutualAuth-{server:?}-server-{client:?}-client"
+        ));
+
+        macro_rules! bench_mixed_sig_types_for_libraries {
+            ($(($lib_name:expr, $lib_type:ty),)*) => {
+            $(
+                group.bench_function($lib_name, |b| {
+                    b.iter_batched_ref(
+                        || {
+                            <$lib_type>::new_with_certs(
+                                &crypto_config,
+                                HandshakeType::MutualAuth,
+                                &cert_config,
+                            )
+                            .unwrap()
+                        },
+                        |harness| {
+                            harness.handshake().unwrap();
+                        },
+                        BatchSize::SmallInput,
+                    )
+                });
+            )*
+            }
+        }
+
+        bench_mixed_sig_types_for_libraries! {
+            ("s2n-tls", S2NHarness),
+            ("rustls", RustlsHarness),
+        }
+
+        group.finish();
+    }
 }
 
-criterion_group!(benches, bench_handshake);
+criterion_group!(benches, bench_handshake, bench_mixed_sig_types);
 criterion_main!(benches);
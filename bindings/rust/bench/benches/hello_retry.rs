@@ -0,0 +1,42 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use bench::{S2NHarness, TlsBenchHarness};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+
+/// Calls to `handshake` needed by the retry, which adds a round trip
+const MAX_HANDSHAKES: usize = 2;
+
+/// Compares handshakes where the client's first key share is accepted with
+/// handshakes where the server sends a HelloRetryRequest for a different group
+///
+/// Both negotiate secp256r1. The client with default_tls13 first sends an
+/// x25519 key share, which the server with 20230317 doesn't support. Each
+/// bench's name includes the number of round trips, since the retry's main
+/// cost is latency rather than CPU.
+pub fn bench_hello_retry(c: &mut Criterion) {
+    let mut group = c.benchmark_group("hello-retry-s2n-tls");
+    for (name, client_policy) in [("no-retry", "20230317"), ("retry", "default_tls13")] {
+        let new_harness =
+            || S2NHarness::new_with_security_policies(client_policy, "20230317").unwrap();
+
+        let mut harness = new_harness();
+        harness.handshake_with_timeout(MAX_HANDSHAKES).unwrap();
+        assert_eq!(harness.hello_retry_requested(), name == "retry");
+
+        group.bench_function(
+            format!("{name}-{}-round-trips", harness.round_trips()),
+            |b| {
+                b.iter_batched_ref(
+                    new_harness,
+                    |harness| harness.handshake_with_timeout(MAX_HANDSHAKES).unwrap(),
+                    BatchSize::SmallInput,
+                )
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_hello_retry);
+criterion_main!(benches);
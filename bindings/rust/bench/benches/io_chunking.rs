@@ -0,0 +1,68 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use bench::{
+    harness::ConnectedBuffer, CipherSuite, CryptoConfig, ECGroup, HandshakeType, S2NHarness,
+    TlsBenchHarness,
+};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+
+/// Bound on handshake steps, so that a regression that stalls the handshake
+/// fails instead of hanging
+const MAX_HANDSHAKE_STEPS: usize = 10_000;
+
+/// Drive the handshake until it completes, since each poll may only make
+/// partial progress when reads block
+fn handshake_until_completed(harness: &mut S2NHarness) {
+    harness.handshake_with_timeout(MAX_HANDSHAKE_STEPS).unwrap();
+}
+
+pub fn bench_io_chunking(c: &mut Criterion) {
+    let crypto_config = CryptoConfig {
+        cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+        ec_group: ECGroup::SECP256R1,
+    };
+    let mut group = c.benchmark_group("io-chunking-s2n-tls");
+
+    // (name, function to create the IO buffer)
+    let buffers: [(&str, fn() -> ConnectedBuffer); 4] = [
+        ("all-at-once", ConnectedBuffer::new),
+        ("byte-by-byte", || ConnectedBuffer::with_max_read_len(1)),
+        // poll_negotiate returns Pending after every chunk, like an event
+        // loop where data arrives in small reads
+        ("pending-every-256-bytes", || {
+            ConnectedBuffer::with_chunked_arrival(256)
+        }),
+        ("pending-every-16-bytes", || {
+            ConnectedBuffer::with_chunked_arrival(16)
+        }),
+    ];
+    for (name, new_buffer) in buffers {
+        // report how many calls each handshake makes alongside the timings
+        let mut harness =
+            S2NHarness::new_with_buffer(&crypto_config, HandshakeType::ServerAuth, new_buffer())
+                .unwrap();
+        handshake_until_completed(&mut harness);
+        println!("{name}: {:?}", harness.call_counts());
+
+        group.bench_function(name, |b| {
+            b.iter_batched_ref(
+                || {
+                    S2NHarness::new_with_buffer(
+                        &crypto_config,
+                        HandshakeType::ServerAuth,
+                        new_buffer(),
+                    )
+                    .unwrap()
+                },
+                handshake_until_completed,
+                BatchSize::SmallInput,
+            )
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_io_chunking);
+criterion_main!(benches);
@@ -0,0 +1,70 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use bench::{CipherSuite, CryptoConfig, ECGroup, S2NHarness, TlsBenchHarness};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use ring::{
+    agreement::{self, Algorithm, EphemeralPrivateKey, UnparsedPublicKey},
+    rand::SystemRandom,
+};
+
+/// Measures the key agreement each side of a TLS1.3 handshake performs for
+/// each group: generating an ephemeral key pair and deriving the shared
+/// secret from the peer's public key
+///
+/// Neither s2n-tls nor rustls supports the psk_ke mode, so every handshake
+/// performs key agreement and there's no handshake without it to compare
+/// against. Instead, key agreement is measured alone with ring, the crypto
+/// backend of rustls, and alongside PSK handshakes, which perform key
+/// agreement without certificates or signatures. SECP384R1 is only measured
+/// alone, since the harnesses don't support it.
+pub fn bench_key_agreement(c: &mut Criterion) {
+    let rng = SystemRandom::new();
+    let algorithms: [(&str, &Algorithm); 3] = [
+        ("X25519", &agreement::X25519),
+        ("SECP256R1", &agreement::ECDH_P256),
+        ("SECP384R1", &agreement::ECDH_P384),
+    ];
+
+    let mut group = c.benchmark_group("key-agreement-ring");
+    for (name, algorithm) in algorithms {
+        let peer_public_key = EphemeralPrivateKey::generate(algorithm, &rng)
+            .unwrap()
+            .compute_public_key()
+            .unwrap();
+        let peer_public_key = UnparsedPublicKey::new(algorithm, peer_public_key.as_ref().to_vec());
+
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                let private_key = EphemeralPrivateKey::generate(algorithm, &rng).unwrap();
+                let public_key = private_key.compute_public_key().unwrap();
+                let shared_secret_len =
+                    agreement::agree_ephemeral(private_key, &peer_public_key, |secret| {
+                        secret.len()
+                    })
+                    .unwrap();
+                (public_key, shared_secret_len)
+            })
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("key-agreement-psk-s2n-tls");
+    for ec_group in [ECGroup::X25519, ECGroup::SECP256R1] {
+        let crypto_config = CryptoConfig {
+            cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+            ec_group,
+        };
+        group.bench_function(format!("{:?}", crypto_config.ec_group), |b| {
+            b.iter_batched_ref(
+                || S2NHarness::new_with_psk(&crypto_config, b"bench identity", &[1; 32]).unwrap(),
+                |harness| harness.handshake().unwrap(),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_key_agreement);
+criterion_main!(benches);
@@ -0,0 +1,49 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use bench::{harness::Mode, ClientHelloMalformation, RustlsHarness, S2NHarness, TlsBenchHarness};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+
+/// Measures a server rejecting a malformed ClientHello, which is the cost a
+/// server pays per garbage connection from scanners, fuzzers, or attackers
+///
+/// Only the server's processing of the ClientHello is timed. s2n-tls
+/// connections use self-service blinding in the harness, so the blinding
+/// delay isn't included.
+pub fn bench_malformed_client_hello(c: &mut Criterion) {
+    macro_rules! bench_malformed_client_hello_for_libraries {
+        ($(($lib_name:expr, $lib_type:ty),)*) => {
+        $(
+            let mut group = c.benchmark_group(format!("malformed-client-hello-{}", $lib_name));
+            for malformation in ClientHelloMalformation::ALL {
+                group.bench_function(format!("{malformation:?}"), |b| {
+                    b.iter_batched_ref(
+                        || {
+                            let mut harness = <$lib_type>::default().unwrap();
+                            harness.handshake_step(Mode::Client).unwrap();
+                            let client_hello = harness.pending_data(Mode::Server);
+                            harness.discard_pending_data(Mode::Server);
+                            harness.inject_pending_data(
+                                Mode::Server,
+                                &malformation.apply(&client_hello),
+                            );
+                            harness
+                        },
+                        |harness| assert!(harness.handshake_step(Mode::Server).is_err()),
+                        BatchSize::SmallInput,
+                    )
+                });
+            }
+            group.finish();
+        )*
+        }
+    }
+
+    bench_malformed_client_hello_for_libraries! {
+        ("s2n-tls", S2NHarness),
+        ("rustls", RustlsHarness),
+    }
+}
+
+criterion_group!(benches, bench_malformed_client_hello);
+criterion_main!(benches);
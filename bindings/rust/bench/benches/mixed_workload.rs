@@ -0,0 +1,90 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use bench::{
+    harness::Mode, CipherSuite, CryptoConfig, ECGroup, HandshakeType, RustlsHarness, S2NHarness,
+    TlsBenchHarness,
+};
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+
+const CRYPTO_CONFIG: CryptoConfig = CryptoConfig {
+    cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+    ec_group: ECGroup::SECP256R1,
+};
+/// Application data sent on each established connection per handshake step,
+/// which fills one record
+const CHUNK_LEN: usize = 16_384;
+/// Bound on handshake steps, so a stalled handshake fails instead of hanging
+const MAX_STEPS: usize = 16;
+
+fn new_harness<T: TlsBenchHarness>() -> T {
+    T::new(&CRYPTO_CONFIG, HandshakeType::ServerAuth).unwrap()
+}
+
+/// Complete the handshake of `harness` one step at a time, sending a chunk
+/// from client to server on every connection in `pool` before each step
+///
+/// Returns the number of application data bytes transferred.
+fn mixed_round<T: TlsBenchHarness>(pool: &mut [T], harness: &mut T, chunk: &[u8]) -> usize {
+    let mut received = vec![0; chunk.len()];
+    let mut transferred = 0;
+    for mode in [Mode::Client, Mode::Server]
+        .into_iter()
+        .cycle()
+        .take(MAX_STEPS)
+    {
+        if harness.handshake_completed() {
+            return transferred;
+        }
+        for established in pool.iter_mut() {
+            established.send(Mode::Client, chunk).unwrap();
+            established.recv(Mode::Server, &mut received).unwrap();
+            transferred += chunk.len();
+        }
+        harness.handshake_step(mode).unwrap();
+    }
+    assert!(harness.handshake_completed(), "handshake stalled");
+    transferred
+}
+
+/// Measures new handshakes completing while a pool of established
+/// connections transfers data, interleaved on one thread like an event loop
+/// serving both
+///
+/// Each iteration completes one handshake, so handshakes per second is the
+/// inverse of the time per iteration. Throughput is the data transferred by
+/// the pool meanwhile.
+fn bench_mixed_workload_for_library<T: TlsBenchHarness>(c: &mut Criterion, lib_name: &str) {
+    let chunk = vec![1; CHUNK_LEN];
+    let mut group = c.benchmark_group(format!("mixed-workload-{lib_name}"));
+
+    for pool_size in [1, 8, 32] {
+        let mut pool: Vec<T> = (0..pool_size)
+            .map(|_| {
+                let mut harness = new_harness::<T>();
+                harness.handshake().unwrap();
+                harness
+            })
+            .collect();
+        // the handshake takes the same number of steps every time
+        let transferred = mixed_round(&mut pool, &mut new_harness::<T>(), &chunk);
+
+        group.throughput(Throughput::Bytes(transferred as u64));
+        group.bench_function(BenchmarkId::from_parameter(pool_size), |b| {
+            b.iter_batched_ref(
+                new_harness::<T>,
+                |harness| mixed_round(&mut pool, harness, &chunk),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+pub fn bench_mixed_workload(c: &mut Criterion) {
+    bench_mixed_workload_for_library::<S2NHarness>(c, "s2n-tls");
+    bench_mixed_workload_for_library::<RustlsHarness>(c, "rustls");
+}
+
+criterion_group!(benches, bench_mixed_workload);
+criterion_main!(benches);
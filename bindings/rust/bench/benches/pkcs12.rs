@@ -0,0 +1,27 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use bench::{harness::read_to_bytes, CertConfig, ServerIdentity};
+use criterion::{criterion_group, criterion_main, Criterion};
+
+/// Measures loading the server identity from a password-protected PKCS#12
+/// bundle, which requires key derivation and decryption, compared to PEM
+pub fn bench_pkcs12(c: &mut Criterion) {
+    let pem_config = CertConfig::default();
+    let (pkcs12_path, password) = CertConfig::with_pkcs12().server_pkcs12.unwrap();
+    let cert_chain = read_to_bytes(&pem_config.server_cert_chain);
+    let key = read_to_bytes(&pem_config.server_key);
+    let bundle = read_to_bytes(&pkcs12_path);
+
+    let mut group = c.benchmark_group("load-server-identity");
+    group.bench_function("pem", |b| {
+        b.iter(|| ServerIdentity::from_pem(&cert_chain, &key).unwrap())
+    });
+    group.bench_function("pkcs12", |b| {
+        b.iter(|| ServerIdentity::from_pkcs12(&bundle, &password).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_pkcs12);
+criterion_main!(benches);
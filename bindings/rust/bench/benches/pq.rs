@@ -0,0 +1,47 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Compares s2n-tls handshakes using hybrid post-quantum key exchange with
+//! handshakes where a PQ-capable client falls back to a classical server
+//!
+//! If the libcrypto doesn't support the hybrid groups, both cases use
+//! classical key exchange and the "hybrid" case is skipped.
+
+use bench::{
+    s2n_tls::{CLASSICAL_SECURITY_POLICY, PQ_SECURITY_POLICY},
+    S2NHarness, TlsBenchHarness,
+};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+
+pub fn bench_pq(c: &mut Criterion) {
+    let mut group = c.benchmark_group("pq-s2n-tls");
+
+    for (name, server_policy) in [
+        ("hybrid", PQ_SECURITY_POLICY),
+        ("fallback", CLASSICAL_SECURITY_POLICY),
+    ] {
+        let mut harness =
+            S2NHarness::new_with_security_policies(PQ_SECURITY_POLICY, server_policy).unwrap();
+        harness.handshake().unwrap();
+        if name == "hybrid" && harness.kem_group().is_none() {
+            println!("hybrid groups not supported by libcrypto, skipping");
+            continue;
+        }
+
+        group.bench_function(name, |b| {
+            b.iter_batched_ref(
+                || {
+                    S2NHarness::new_with_security_policies(PQ_SECURITY_POLICY, server_policy)
+                        .unwrap()
+                },
+                |harness| harness.handshake().unwrap(),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_pq);
+criterion_main!(benches);
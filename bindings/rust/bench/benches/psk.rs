@@ -0,0 +1,39 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use bench::{CipherSuite, CryptoConfig, ECGroup, HandshakeType, S2NHarness, TlsBenchHarness};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+
+/// Compares handshakes authenticated with certificates with handshakes
+/// authenticated with an external PSK
+///
+/// Both handshakes perform key agreement and the key schedule, so the PSK
+/// handshake approximates their cost alone, and the difference approximates
+/// the cost of sending, validating, and signing with certificates.
+pub fn bench_psk(c: &mut Criterion) {
+    let mut group = c.benchmark_group("handshake-auth-s2n-tls");
+    for ec_group in [ECGroup::SECP256R1, ECGroup::X25519] {
+        let crypto_config = CryptoConfig {
+            cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+            ec_group,
+        };
+        group.bench_function(format!("certificate-{:?}", crypto_config.ec_group), |b| {
+            b.iter_batched_ref(
+                || S2NHarness::new(&crypto_config, HandshakeType::ServerAuth).unwrap(),
+                |harness| harness.handshake().unwrap(),
+                BatchSize::SmallInput,
+            )
+        });
+        group.bench_function(format!("psk-{:?}", crypto_config.ec_group), |b| {
+            b.iter_batched_ref(
+                || S2NHarness::new_with_psk(&crypto_config, b"bench identity", &[1; 32]).unwrap(),
+                |harness| harness.handshake().unwrap(),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_psk);
+criterion_main!(benches);
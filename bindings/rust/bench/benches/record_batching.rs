@@ -0,0 +1,71 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use bench::{
+    harness::Mode, CipherSuite, CryptoConfig, ECGroup, HandshakeType, S2NHarness, TlsBenchHarness,
+};
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+
+/// Length of each application write
+const WRITE_LEN: usize = 1000;
+/// Number of application writes sent per iteration
+const WRITES: usize = 100;
+/// Large enough to buffer every record of an iteration
+const SEND_BUFFER_SIZE: u32 = 128 * 1024;
+
+/// Compares sending each application write as soon as it's made with
+/// batching the writes into one call to s2n-tls
+///
+/// Sending each write separately sends at least one record per write, and
+/// writes each record to the IO buffer separately. Batched writes are
+/// coalesced into full records, and with a send buffer, all the records are
+/// written to the IO buffer at once. The number of IO writes per iteration of
+/// each variant is printed before it's measured.
+pub fn bench_record_batching(c: &mut Criterion) {
+    let crypto_config = CryptoConfig {
+        cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+        ec_group: ECGroup::SECP256R1,
+    };
+    let data = vec![1; WRITE_LEN * WRITES];
+    let writes: Vec<&[u8]> = data.chunks(WRITE_LEN).collect();
+    let mut received = vec![0; data.len()];
+
+    let per_write = |harness: &mut S2NHarness| {
+        for write in &writes {
+            harness.send(Mode::Client, write).unwrap();
+        }
+    };
+    let batched = |harness: &mut S2NHarness| harness.send_vectored(Mode::Client, &writes).unwrap();
+    let variants: [(&str, Option<u32>, &dyn Fn(&mut S2NHarness)); 3] = [
+        ("per-write", None, &per_write),
+        ("batched", None, &batched),
+        ("batched-send-buffer", Some(SEND_BUFFER_SIZE), &batched),
+    ];
+
+    let mut group = c.benchmark_group("record-batching-s2n-tls");
+    group.throughput(Throughput::Bytes(data.len() as u64));
+    for (name, send_buffer_size, send) in variants {
+        let mut harness = match send_buffer_size {
+            Some(size) => S2NHarness::new_with_send_buffer_size(&crypto_config, size),
+            None => S2NHarness::new(&crypto_config, HandshakeType::ServerAuth),
+        }
+        .unwrap();
+        harness.handshake().unwrap();
+
+        let before = harness.call_counts().send_cb;
+        send(&mut harness);
+        println!("{name}: {} writes", harness.call_counts().send_cb - before);
+        harness.recv(Mode::Server, &mut received).unwrap();
+
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                send(&mut harness);
+                harness.recv(Mode::Server, &mut received).unwrap();
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_record_batching);
+criterion_main!(benches);
@@ -0,0 +1,74 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use bench::{
+    harness::Mode, CipherSuite, CryptoConfig, ECGroup, HandshakeType, RustlsHarness, S2NHarness,
+    TlsBenchHarness,
+};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+
+const CRYPTO_CONFIG: CryptoConfig = CryptoConfig {
+    cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+    ec_group: ECGroup::SECP256R1,
+};
+
+/// Initialize connections whose server issues session tickets, so that full
+/// and resumed handshakes are measured with the same configs
+fn new_harness<T: TlsBenchHarness>() -> T {
+    T::new_with_session_tickets(&CRYPTO_CONFIG, HandshakeType::ServerAuth).unwrap()
+}
+
+/// Complete a full handshake and receive its session ticket, then initialize
+/// connections resuming that session
+fn new_resumed_harness<T: TlsBenchHarness>() -> T {
+    let mut harness = new_harness::<T>();
+    harness.handshake().unwrap();
+    // session tickets are sent after the handshake in TLS1.3
+    harness.send(Mode::Server, &[0]).unwrap();
+    harness.recv(Mode::Client, &mut [0]).unwrap();
+    harness.new_resumed().unwrap()
+}
+
+fn total_bytes_sent<T: TlsBenchHarness>(harness: &T) -> usize {
+    harness.bytes_sent(Mode::Client) + harness.bytes_sent(Mode::Server)
+}
+
+fn bench_resumption_for_library<T: TlsBenchHarness>(c: &mut Criterion, lib_name: &str) {
+    // guard against measuring a silent fallback to a full handshake
+    let mut full = new_harness::<T>();
+    full.handshake().unwrap();
+    let mut resumed = new_resumed_harness::<T>();
+    resumed.handshake().unwrap();
+    assert!(!full.was_resumed());
+    assert!(resumed.was_resumed(), "{lib_name} did not resume");
+    println!(
+        "{lib_name}: full handshake sent {} bytes, resumed handshake sent {} bytes",
+        total_bytes_sent(&full),
+        total_bytes_sent(&resumed)
+    );
+
+    let mut group = c.benchmark_group(format!("resumption-{lib_name}"));
+    group.bench_function("full", |b| {
+        b.iter_batched_ref(
+            new_harness::<T>,
+            |harness| harness.handshake().unwrap(),
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("resumed", |b| {
+        b.iter_batched_ref(
+            new_resumed_harness::<T>,
+            |harness| harness.handshake().unwrap(),
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+pub fn bench_resumption(c: &mut Criterion) {
+    bench_resumption_for_library::<S2NHarness>(c, "s2n-tls");
+    bench_resumption_for_library::<RustlsHarness>(c, "rustls");
+}
+
+criterion_group!(benches, bench_resumption);
+criterion_main!(benches);
@@ -0,0 +1,50 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use bench::{
+    harness::Mode, CipherSuite, CryptoConfig, ECGroup, HandshakeType, S2NHarness, TlsBenchHarness,
+};
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+
+/// Measures resumptions per second for a pool of connections that all resume
+/// the session ticket of one full handshake
+///
+/// Each connection pair has its own IO buffers, so the resumptions run one
+/// after another, but read from the same session ticket storage.
+pub fn bench_resumption_pool(c: &mut Criterion) {
+    // seed the shared store with a single full handshake
+    let crypto_config = CryptoConfig {
+        cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+        ec_group: ECGroup::SECP256R1,
+    };
+    let mut seed =
+        S2NHarness::new_with_session_tickets(&crypto_config, HandshakeType::ServerAuth).unwrap();
+    seed.handshake().unwrap();
+    // TLS1.3 tickets are sent after the handshake
+    seed.send(Mode::Server, &[0]).unwrap();
+    seed.recv(Mode::Client, &mut [0]).unwrap();
+
+    let mut group = c.benchmark_group("resumption-pool-s2n-tls");
+    for pool_size in [1, 4, 16, 64] {
+        group.throughput(Throughput::Elements(pool_size as u64));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(pool_size),
+            &pool_size,
+            |b, &pool_size| {
+                b.iter_batched_ref(
+                    || seed.new_resumed_pool(pool_size).unwrap(),
+                    |pool| {
+                        for harness in pool.iter_mut() {
+                            harness.handshake().unwrap();
+                        }
+                    },
+                    BatchSize::SmallInput,
+                )
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_resumption_pool);
+criterion_main!(benches);
@@ -0,0 +1,90 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use bench::{
+    harness::Mode, CertConfig, CipherSuite, CryptoConfig, ECGroup, HandshakeType, RustlsHarness,
+    S2NHarness, TlsBenchHarness,
+};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use std::time::{Duration, Instant};
+
+/// Time spent in the server's and the client's handshake steps
+#[derive(Default)]
+struct RoleTimes {
+    server: Duration,
+    client: Duration,
+}
+
+impl RoleTimes {
+    /// Handshake each harness, attributing the time of each step to the
+    /// role that took it
+    fn handshake<T: TlsBenchHarness>(mut harnesses: Vec<T>) -> Self {
+        let mut times = Self::default();
+        for harness in &mut harnesses {
+            while !harness.handshake_completed() {
+                for mode in [Mode::Client, Mode::Server] {
+                    let start = Instant::now();
+                    harness.handshake_step(mode).unwrap();
+                    match mode {
+                        Mode::Client => times.client += start.elapsed(),
+                        Mode::Server => times.server += start.elapsed(),
+                    }
+                }
+            }
+        }
+        times
+    }
+}
+
+/// Measures the time each role spends in handshakes with RSA server certs of
+/// different key sizes
+///
+/// The server signs with its RSA key, which gets much more expensive as the
+/// key grows, while the client only verifies signatures, which stays cheap.
+/// Each role's time is measured separately from the same handshakes.
+pub fn bench_rsa_roles(c: &mut Criterion) {
+    let crypto_config = CryptoConfig {
+        cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+        ec_group: ECGroup::SECP256R1,
+    };
+
+    for bits in [2048, 3072, 4096] {
+        let cert_config = CertConfig::with_rsa_key_size(bits);
+        let mut group = c.benchmark_group(format!("rsa-{bits}-roles"));
+
+        macro_rules! bench_rsa_roles_for_libraries {
+            ($(($lib_name:expr, $lib_type:ty),)*) => {
+            $(
+                let new_harnesses = |iters| {
+                    (0..iters)
+                        .map(|_| {
+                            <$lib_type>::new_with_certs(
+                                &crypto_config,
+                                HandshakeType::ServerAuth,
+                                &cert_config,
+                            )
+                            .unwrap()
+                        })
+                        .collect::<Vec<_>>()
+                };
+                group.bench_function(BenchmarkId::new("server", $lib_name), |b| {
+                    b.iter_custom(|iters| RoleTimes::handshake(new_harnesses(iters)).server)
+                });
+                group.bench_function(BenchmarkId::new("client", $lib_name), |b| {
+                    b.iter_custom(|iters| RoleTimes::handshake(new_harnesses(iters)).client)
+                });
+            )*
+            }
+        }
+
+        bench_rsa_roles_for_libraries! {
+            ("s2n-tls", S2NHarness),
+            ("rustls", RustlsHarness),
+        }
+
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_rsa_roles);
+criterion_main!(benches);
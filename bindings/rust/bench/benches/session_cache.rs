@@ -0,0 +1,37 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use bench::{S2NHarness, TlsBenchHarness};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+
+/// Calls to `handshake` needed by a full TLS1.2 handshake, where the client
+/// completes in a fifth step by reading the server's Finished
+const MAX_HANDSHAKES: usize = 2;
+
+pub fn bench_session_cache(c: &mut Criterion) {
+    let mut group = c.benchmark_group("session-cache-s2n-tls");
+
+    group.bench_function("full", |b| {
+        b.iter_batched_ref(
+            || S2NHarness::new_with_session_cache().unwrap(),
+            |harness| harness.handshake_with_timeout(MAX_HANDSHAKES).unwrap(),
+            BatchSize::SmallInput,
+        )
+    });
+
+    // a single full handshake populates the server cache for all resumptions
+    let mut harness = S2NHarness::new_with_session_cache().unwrap();
+    harness.handshake_with_timeout(MAX_HANDSHAKES).unwrap();
+    group.bench_function("resumed", |b| {
+        b.iter_batched_ref(
+            || harness.new_resumed_by_session_id().unwrap(),
+            |resumed| resumed.handshake().unwrap(),
+            BatchSize::SmallInput,
+        )
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_session_cache);
+criterion_main!(benches);
@@ -0,0 +1,50 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use bench::{harness::Mode, CipherSuite, CryptoConfig, ECGroup, S2NHarness, TlsBenchHarness};
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+
+/// Handshake, then send one byte so the client reads the session tickets
+/// sent after the handshake
+fn handshake_and_receive_tickets(harness: &mut S2NHarness) {
+    harness.handshake().unwrap();
+    harness.send(Mode::Server, &[0]).unwrap();
+    harness.recv(Mode::Client, &mut [0]).unwrap();
+}
+
+pub fn bench_session_tickets(c: &mut Criterion) {
+    let crypto_config = CryptoConfig {
+        cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+        ec_group: ECGroup::SECP256R1,
+    };
+    let mut group = c.benchmark_group("session-tickets-s2n-tls");
+
+    for ticket_count in [1, 2, 4, 8, 16] {
+        // report client-side storage and wire cost alongside the timings
+        let mut harness = S2NHarness::new_with_ticket_count(&crypto_config, ticket_count).unwrap();
+        handshake_and_receive_tickets(&mut harness);
+        let (stored_count, stored_bytes) = harness.stored_tickets();
+        println!(
+            "{ticket_count} tickets: {stored_count} stored using {stored_bytes} bytes, \
+            {} bytes sent by server",
+            harness.bytes_sent(Mode::Server)
+        );
+
+        group.bench_with_input(
+            BenchmarkId::from_parameter(ticket_count),
+            &ticket_count,
+            |b, &ticket_count| {
+                b.iter_batched_ref(
+                    || S2NHarness::new_with_ticket_count(&crypto_config, ticket_count).unwrap(),
+                    handshake_and_receive_tickets,
+                    BatchSize::SmallInput,
+                )
+            },
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_session_tickets);
+criterion_main!(benches);
@@ -0,0 +1,54 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use bench::{
+    harness::Mode, CipherSuite, CryptoConfig, ECGroup, HandshakeType, RustlsHarness, S2NHarness,
+    TlsBenchHarness,
+};
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+
+/// Size of each request and response, typical of small RPC messages
+const MESSAGE_LEN: usize = 64;
+
+/// Measures the fixed per-message cost of many small round trips on an
+/// established connection, such as RPCs over a pooled connection
+pub fn bench_small_messages(c: &mut Criterion) {
+    let crypto_config = CryptoConfig {
+        cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+        ec_group: ECGroup::SECP256R1,
+    };
+    let request = [1; MESSAGE_LEN];
+    let response = [2; MESSAGE_LEN];
+    let mut buf = [0; MESSAGE_LEN];
+
+    let mut group = c.benchmark_group(format!("small-messages-{MESSAGE_LEN}-bytes"));
+    // report round trips per second
+    group.throughput(Throughput::Elements(1));
+
+    macro_rules! bench_small_messages_for_libraries {
+        ($(($lib_name:expr, $lib_type:ty),)*) => {
+        $(
+            let mut harness = <$lib_type>::new(&crypto_config, HandshakeType::ServerAuth).unwrap();
+            harness.handshake().unwrap();
+            group.bench_function($lib_name, |b| {
+                b.iter(|| {
+                    harness.send(Mode::Client, &request).unwrap();
+                    harness.recv(Mode::Server, &mut buf).unwrap();
+                    harness.send(Mode::Server, &response).unwrap();
+                    harness.recv(Mode::Client, &mut buf).unwrap();
+                })
+            });
+        )*
+        }
+    }
+
+    bench_small_messages_for_libraries! {
+        ("s2n-tls", S2NHarness),
+        ("rustls", RustlsHarness),
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_small_messages);
+criterion_main!(benches);
@@ -0,0 +1,59 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use bench::{
+    harness::Mode, CipherSuite, CryptoConfig, ECGroup, HandshakeType, RustlsHarness, S2NHarness,
+    TlsBenchHarness,
+};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+/// Amount of application data transferred per iteration
+const DATA_LEN: usize = 1_000_000;
+/// Largest plaintext that fits in one TLS record
+const MAX_RECORD_LEN: usize = 16_384;
+
+/// Measures receiving a large transfer into application buffers smaller than
+/// one record, which forces the library to buffer the rest of each decrypted
+/// record between reads, compared with reading a whole record at a time
+pub fn bench_small_reads(c: &mut Criterion) {
+    let crypto_config = CryptoConfig {
+        cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+        ec_group: ECGroup::SECP256R1,
+    };
+    let data = vec![0; DATA_LEN];
+    let mut buf = vec![0; MAX_RECORD_LEN];
+
+    let mut group = c.benchmark_group("small-reads");
+    group.throughput(Throughput::Bytes(DATA_LEN as u64));
+
+    macro_rules! bench_small_reads_for_libraries {
+        ($(($lib_name:expr, $lib_type:ty),)*) => {
+        $(
+            let mut harness = <$lib_type>::new(&crypto_config, HandshakeType::ServerAuth).unwrap();
+            harness.handshake().unwrap();
+            for read_len in [128, 1024, MAX_RECORD_LEN] {
+                group.bench_function(BenchmarkId::new($lib_name, read_len), |b| {
+                    b.iter(|| {
+                        harness.send(Mode::Client, &data).unwrap();
+                        for _ in 0..DATA_LEN / read_len {
+                            harness.recv(Mode::Server, &mut buf[..read_len]).unwrap();
+                        }
+                        // receive the remainder when read_len doesn't divide DATA_LEN
+                        harness.recv(Mode::Server, &mut buf[..DATA_LEN % read_len]).unwrap();
+                    })
+                });
+            }
+        )*
+        }
+    }
+
+    bench_small_reads_for_libraries! {
+        ("s2n-tls", S2NHarness),
+        ("rustls", RustlsHarness),
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_small_reads);
+criterion_main!(benches);
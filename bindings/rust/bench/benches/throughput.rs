@@ -0,0 +1,139 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use bench::{
+    harness::{ConnectedBuffer, Mode},
+    CipherSuite, CryptoConfig, ECGroup, HandshakeType, RustlsHarness, S2NHarness, TlsBenchHarness,
+};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+
+/// Amount of application data sent in each direction per iteration
+const DATA_LEN: usize = 100_000;
+/// Amount of application data sent by the client and by the server per
+/// iteration of the asymmetric bench
+const REQUEST_LEN: usize = 100;
+const RESPONSE_LEN: usize = 1_000_000;
+/// Preallocated capacity of the buffer to the client in the asymmetric
+/// bench, with room for record overhead and the handshake
+const RESPONSE_BUFFER_CAPACITY: usize = RESPONSE_LEN + 64 * 1024;
+/// Preallocated capacity of the buffer to the server in the asymmetric
+/// bench, which only needs to hold the client's handshake and request
+const REQUEST_BUFFER_CAPACITY: usize = 4096;
+
+/// Send `DATA_LEN` bytes from the client to the server and back
+fn round_trip_transfer<T: TlsBenchHarness>(harness: &mut T, shared_buf: &mut [u8]) {
+    harness.send(Mode::Client, shared_buf).unwrap();
+    harness.recv(Mode::Server, shared_buf).unwrap();
+    harness.send(Mode::Server, shared_buf).unwrap();
+    harness.recv(Mode::Client, shared_buf).unwrap();
+}
+
+/// Measures round trip transfers of bulk data
+///
+/// The "reused" variant loops on one established connection, which measures
+/// the steady-state data plane alone. The "fresh" variant transfers on a new
+/// connection each iteration. Connection setup isn't timed in either, but
+/// the fresh variant includes costs paid by the first transfer on a
+/// connection, such as growing IO buffers, and cold caches.
+pub fn bench_throughput(c: &mut Criterion) {
+    let crypto_config = CryptoConfig {
+        cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+        ec_group: ECGroup::SECP256R1,
+    };
+    let mut shared_buf = vec![0; DATA_LEN];
+
+    macro_rules! bench_throughput_for_libraries {
+        ($(($lib_name:expr, $lib_type:ty),)*) => {
+        $(
+            let mut group = c.benchmark_group(format!("throughput-{}", $lib_name));
+            group.throughput(Throughput::Bytes(2 * DATA_LEN as u64));
+
+            let mut harness = <$lib_type>::new(&crypto_config, HandshakeType::ServerAuth).unwrap();
+            harness.handshake().unwrap();
+            group.bench_function("reused", |b| {
+                b.iter(|| round_trip_transfer(&mut harness, &mut shared_buf))
+            });
+
+            group.bench_function("fresh", |b| {
+                b.iter_batched_ref(
+                    || {
+                        let mut harness =
+                            <$lib_type>::new(&crypto_config, HandshakeType::ServerAuth).unwrap();
+                        harness.handshake().unwrap();
+                        harness
+                    },
+                    |harness| round_trip_transfer(harness, &mut shared_buf),
+                    BatchSize::SmallInput,
+                )
+            });
+
+            group.finish();
+        )*
+        }
+    }
+
+    bench_throughput_for_libraries! {
+        ("s2n-tls", S2NHarness),
+        ("rustls", RustlsHarness),
+    }
+}
+
+/// Send a small request from the client and a large response from the
+/// server
+fn request_response<T: TlsBenchHarness>(harness: &mut T, request: &mut [u8], response: &mut [u8]) {
+    harness.send(Mode::Client, request).unwrap();
+    harness.recv(Mode::Server, request).unwrap();
+    harness.send(Mode::Server, response).unwrap();
+    harness.recv(Mode::Client, response).unwrap();
+}
+
+/// Measures a request/response workload where the server sends much more
+/// data than the client, over buffers preallocated for each direction
+///
+/// Run with the `alloc-counter` feature to also report the memory of each
+/// direction's buffer after a transfer.
+pub fn bench_asymmetric_throughput(c: &mut Criterion) {
+    let crypto_config = CryptoConfig {
+        cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+        ec_group: ECGroup::SECP256R1,
+    };
+    let mut request = vec![0; REQUEST_LEN];
+    let mut response = vec![0; RESPONSE_LEN];
+
+    macro_rules! bench_asymmetric_for_libraries {
+        ($(($lib_name:expr, $lib_type:ty),)*) => {
+        $(
+            let mut group = c.benchmark_group(format!("throughput-asymmetric-{}", $lib_name));
+            group.throughput(Throughput::Bytes((REQUEST_LEN + RESPONSE_LEN) as u64));
+
+            let buf =
+                ConnectedBuffer::with_capacities(REQUEST_BUFFER_CAPACITY, RESPONSE_BUFFER_CAPACITY);
+            let mut harness =
+                <$lib_type>::new_with_buffer(&crypto_config, HandshakeType::ServerAuth, buf)
+                    .unwrap();
+            harness.handshake().unwrap();
+
+            #[cfg(feature = "alloc-counter")]
+            {
+                let ((), stats) = bench::alloc_counter::measure(|| {
+                    request_response(&mut harness, &mut request, &mut response)
+                });
+                println!("{} request/response: {stats:?}", $lib_name);
+            }
+
+            group.bench_function("request-response", |b| {
+                b.iter(|| request_response(&mut harness, &mut request, &mut response))
+            });
+            group.finish();
+        )*
+        }
+    }
+
+    bench_asymmetric_for_libraries! {
+        ("s2n-tls", S2NHarness),
+        ("rustls", RustlsHarness),
+    }
+}
+
+criterion_group!(benches, bench_throughput, bench_asymmetric_throughput);
+criterion_main!(benches);
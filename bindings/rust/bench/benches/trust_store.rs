@@ -0,0 +1,99 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use bench::harness::{
+    parse_der_certs, parse_pem_certs, read_to_bytes, LARGE_DER_TRUST_STORE_PATH,
+    LARGE_TRUST_STORE_PATH,
+};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use rustls::{Certificate, RootCertStore};
+use s2n_tls::config::Builder;
+use std::{error::Error, sync::Arc};
+
+/// Build a client config that trusts only the CAs in `trusted_cas`
+fn build_config(trusted_cas: &[u8]) {
+    let mut builder = Builder::new();
+    // system certs would otherwise be loaded on build
+    builder
+        .with_system_certs(false)
+        .unwrap()
+        .wipe_trust_store()
+        .unwrap()
+        .trust_pem(trusted_cas)
+        .unwrap();
+    builder.build().unwrap();
+}
+
+/// Measures how config construction scales with the number of trusted CAs
+pub fn bench_trust_store(c: &mut Criterion) {
+    const END_CERT: &str = "-----END CERTIFICATE-----\n";
+    let bundle = read_to_bytes(LARGE_TRUST_STORE_PATH);
+    let bundle = std::str::from_utf8(&bundle).unwrap();
+
+    let mut group = c.benchmark_group("trust-store-s2n-tls");
+    for ca_count in [1, 10, 100, 1000] {
+        // the first `ca_count` certs of the bundle
+        let trusted_cas: String = bundle.split_inclusive(END_CERT).take(ca_count).collect();
+        assert_eq!(trusted_cas.matches(END_CERT).count(), ca_count);
+
+        #[cfg(feature = "alloc-counter")]
+        {
+            let ((), stats) =
+                bench::alloc_counter::measure(|| build_config(trusted_cas.as_bytes()));
+            println!("{ca_count} CAs: {stats:?}");
+        }
+
+        group.bench_with_input(
+            BenchmarkId::from_parameter(ca_count),
+            trusted_cas.as_bytes(),
+            |b, trusted_cas| b.iter(|| build_config(trusted_cas)),
+        );
+    }
+    group.finish();
+}
+
+/// Function splitting a trust store into DER certs
+type ParseCerts = fn(&[u8]) -> Result<Vec<Vec<u8>>, Box<dyn Error>>;
+
+/// Parse a trust store with `parse` and add every cert to a rustls root store
+fn build_root_store(bundle: &[u8], parse: ParseCerts) -> RootCertStore {
+    let mut root_store = RootCertStore::empty();
+    for cert in parse(bundle).unwrap() {
+        root_store.add(&Certificate(cert)).unwrap();
+    }
+    root_store
+}
+
+/// Measures loading the same 1000 CA trust store from PEM and from DER
+///
+/// rustls is compared because it adds DER certs directly, so PEM only adds
+/// the base64 decoding and header parsing. s2n-tls only loads PEM trust
+/// stores.
+pub fn bench_trust_store_format(c: &mut Criterion) {
+    let mut group = c.benchmark_group("trust-store-format-rustls");
+    let formats: [(&str, Arc<[u8]>, ParseCerts); 2] = [
+        (
+            "pem",
+            read_to_bytes(LARGE_TRUST_STORE_PATH),
+            parse_pem_certs,
+        ),
+        (
+            "der",
+            read_to_bytes(LARGE_DER_TRUST_STORE_PATH),
+            parse_der_certs,
+        ),
+    ];
+    for (name, bundle, parse) in formats {
+        #[cfg(feature = "alloc-counter")]
+        {
+            let (_, stats) = bench::alloc_counter::measure(|| build_root_store(&bundle, parse));
+            println!("{name}: {stats:?}");
+        }
+
+        group.bench_function(name, |b| b.iter(|| build_root_store(&bundle, parse)));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_trust_store, bench_trust_store_format);
+criterion_main!(benches);
@@ -0,0 +1,36 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use bench::{CipherSuite, CryptoConfig, ECGroup, S2NHarness, TlsBenchHarness, TrustStoreSource};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+
+/// Compares handshakes where the client trusts only the CA with handshakes
+/// where it also trusts the system trust store
+///
+/// Only the handshake is measured. The cost of loading each trust store when
+/// the config is built is measured by the `trust_store` bench.
+pub fn bench_trust_store_source(c: &mut Criterion) {
+    let crypto_config = CryptoConfig {
+        cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+        ec_group: ECGroup::SECP256R1,
+    };
+
+    let mut group = c.benchmark_group("trust-store-source-s2n-tls");
+    for source in [
+        TrustStoreSource::Pem,
+        TrustStoreSource::Location,
+        TrustStoreSource::System,
+    ] {
+        group.bench_function(format!("{source:?}"), |b| {
+            b.iter_batched_ref(
+                || S2NHarness::new_with_trust_store_source(&crypto_config, source).unwrap(),
+                |harness| harness.handshake().unwrap(),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_trust_store_source);
+criterion_main!(benches);
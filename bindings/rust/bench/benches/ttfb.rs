@@ -0,0 +1,119 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use bench::{
+    harness::Mode, CipherSuite, CryptoConfig, ECGroup, HandshakeType, RustlsHarness, S2NHarness,
+    TlsBenchHarness,
+};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use std::time::{Duration, Instant};
+
+/// Simulated network round trip time added to measured CPU time for wall time results
+const ROUND_TRIP_LATENCY: Duration = Duration::from_millis(50);
+
+const CRYPTO_CONFIG: CryptoConfig = CryptoConfig {
+    cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+    ec_group: ECGroup::SECP256R1,
+};
+
+const REQUEST: &[u8] = b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n";
+const RESPONSE: &[u8] = b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n";
+
+/// Initialize connections whose server issues session tickets, so that full
+/// and resumed handshakes are measured with the same configs
+fn new_harness<T: TlsBenchHarness>() -> T {
+    T::new_with_session_tickets(&CRYPTO_CONFIG, HandshakeType::ServerAuth).unwrap()
+}
+
+/// Handshake, send a request, and receive the first byte of the response
+fn time_to_first_byte<T: TlsBenchHarness>(harness: &mut T) {
+    harness.handshake().unwrap();
+    harness.send(Mode::Client, REQUEST).unwrap();
+    harness.recv(Mode::Server, &mut [0; REQUEST.len()]).unwrap();
+    harness.send(Mode::Server, RESPONSE).unwrap();
+    harness.recv(Mode::Client, &mut [0]).unwrap();
+}
+
+/// Complete one request/response exchange so the client holds a session
+/// ticket, then return a harness that resumes that session
+fn resumed_harness<T: TlsBenchHarness>() -> T {
+    let mut harness = new_harness::<T>();
+    time_to_first_byte(&mut harness);
+    harness
+        .recv(Mode::Client, &mut [0; RESPONSE.len() - 1])
+        .unwrap();
+    harness.new_resumed().unwrap()
+}
+
+pub fn bench_ttfb(c: &mut Criterion) {
+    let mut cpu_group = c.benchmark_group("ttfb-cpu");
+
+    macro_rules! bench_ttfb_cpu_for_libraries {
+        ($(($lib_name:expr, $lib_type:ty),)*) => {
+        $(
+            // timing only includes negotiation and data transfer, not
+            // config/connection initialization or priming session tickets
+            cpu_group.bench_function(format!("{}-full", $lib_name), |b| {
+                b.iter_batched_ref(
+                    new_harness::<$lib_type>,
+                    |harness| time_to_first_byte(harness),
+                    BatchSize::SmallInput,
+                )
+            });
+            cpu_group.bench_function(format!("{}-resumption", $lib_name), |b| {
+                b.iter_batched_ref(
+                    resumed_harness::<$lib_type>,
+                    |harness| time_to_first_byte(harness),
+                    BatchSize::SmallInput,
+                )
+            });
+        )*
+        }
+    }
+
+    bench_ttfb_cpu_for_libraries! {
+        ("s2n-tls", S2NHarness),
+        ("rustls", RustlsHarness),
+    }
+
+    cpu_group.finish();
+
+    // reported times are CPU time plus ROUND_TRIP_LATENCY for every round trip
+    // the client waits on, approximating time to first byte over a network
+    let mut wall_group = c.benchmark_group("ttfb-simulated-wall");
+
+    macro_rules! bench_ttfb_wall_for_libraries {
+        ($(($lib_name:expr, $lib_type:ty),)*) => {
+        $(
+            for (scenario, setup) in [
+                ("full", new_harness::<$lib_type> as fn() -> $lib_type),
+                ("resumption", resumed_harness::<$lib_type>),
+            ] {
+                wall_group.bench_function(format!("{}-{}", $lib_name, scenario), |b| {
+                    b.iter_custom(|iters| {
+                        let mut total = Duration::ZERO;
+                        for _ in 0..iters {
+                            let mut harness = setup();
+                            let start = Instant::now();
+                            time_to_first_byte(&mut harness);
+                            total += start.elapsed()
+                                + ROUND_TRIP_LATENCY * harness.round_trips() as u32;
+                        }
+                        total
+                    })
+                });
+            }
+        )*
+        }
+    }
+
+    bench_ttfb_wall_for_libraries! {
+        ("s2n-tls", S2NHarness),
+        ("rustls", RustlsHarness),
+    }
+
+    wall_group.finish();
+}
+
+criterion_group!(benches, bench_ttfb);
+criterion_main!(benches);
@@ -0,0 +1,41 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use bench::{CipherSuite, CryptoConfig, ECGroup, HandshakeType, S2NHarness, TlsBenchHarness};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use std::time::{Duration, SystemTime};
+
+/// Compares handshakes validating the server's certificates as of the real
+/// time with handshakes validating them as of an injected time
+///
+/// s2n-tls checks certificate validity against the wall clock either way, so
+/// the difference is the cost of the injected clock callback, which should be
+/// within noise.
+pub fn bench_validation_time(c: &mut Criterion) {
+    let crypto_config = CryptoConfig {
+        cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+        ec_group: ECGroup::SECP256R1,
+    };
+    // well within the validity period of the default certs
+    let validation_time = SystemTime::now() + Duration::from_secs(365 * 24 * 60 * 60);
+
+    let mut group = c.benchmark_group("validation-time-s2n-tls");
+    group.bench_function("system-clock", |b| {
+        b.iter_batched_ref(
+            || S2NHarness::new(&crypto_config, HandshakeType::ServerAuth).unwrap(),
+            |harness| harness.handshake().unwrap(),
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("fixed-clock", |b| {
+        b.iter_batched_ref(
+            || S2NHarness::new_with_validation_time(&crypto_config, validation_time).unwrap(),
+            |harness| harness.handshake().unwrap(),
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_validation_time);
+criterion_main!(benches);
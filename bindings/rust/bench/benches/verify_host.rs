@@ -0,0 +1,88 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use bench::{
+    CertConfig, CipherSuite, CryptoConfig, ECGroup, S2NHarness, TlsBenchHarness,
+    WildcardHostNameHandler,
+};
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+
+pub fn bench_verify_host(c: &mut Criterion) {
+    let crypto_config = CryptoConfig {
+        cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+        ec_group: ECGroup::SECP256R1,
+    };
+    let mut group = c.benchmark_group("verify-host-s2n-tls");
+
+    // the hostname callback is called once per subject alternative name
+    for san_count in [1, 10, 100, 1000] {
+        let cert_config = CertConfig::with_san_count(san_count);
+        group.bench_with_input(
+            BenchmarkId::from_parameter(san_count),
+            &cert_config,
+            |b, cert_config| {
+                b.iter_batched_ref(
+                    || {
+                        S2NHarness::new_with_host_name_handler(
+                            &crypto_config,
+                            cert_config,
+                            WildcardHostNameHandler::new("localhost"),
+                        )
+                        .unwrap()
+                    },
+                    |harness| {
+                        harness.handshake().unwrap();
+                    },
+                    BatchSize::SmallInput,
+                )
+            },
+        );
+    }
+
+    group.finish();
+}
+
+pub fn bench_verify_host_callback(c: &mut Criterion) {
+    let crypto_config = CryptoConfig {
+        cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+        ec_group: ECGroup::SECP256R1,
+    };
+    // with a single name, the callback is called exactly once, so the
+    // difference is the cost of calling into Rust for it
+    let cert_config = CertConfig::with_san_count(1);
+    let mut group = c.benchmark_group("verify-host-callback-s2n-tls");
+
+    group.bench_function("callback", |b| {
+        b.iter_batched_ref(
+            || {
+                S2NHarness::new_with_host_name_handler(
+                    &crypto_config,
+                    &cert_config,
+                    WildcardHostNameHandler::new("localhost"),
+                )
+                .unwrap()
+            },
+            |harness| {
+                harness.handshake().unwrap();
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("builtin", |b| {
+        b.iter_batched_ref(
+            || {
+                S2NHarness::new_with_builtin_host_verification(&crypto_config, &cert_config)
+                    .unwrap()
+            },
+            |harness| {
+                harness.handshake().unwrap();
+            },
+            BatchSize::SmallInput,
+        )
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_verify_host, bench_verify_host_callback);
+criterion_main!(benches);
@@ -27,6 +27,11 @@ openssl verify -CAfile ca-cert.pem client-cert.pem
 
 cat server-cert.pem ca-cert.pem > fullchain.pem
 
+# PKCS#12 PBE algorithms supported by pure Rust parsers, rather than the
+# OpenSSL 3 default of PBES2
+echo "bundling server certificate chain and private key as PKCS#12"
+openssl pkcs12 -export -in fullchain.pem -inkey server-key.pem -out server.p12 -passout pass:bench-password -keypbe PBE-SHA1-3DES -certpbe PBE-SHA1-3DES -macalg sha1
+
 echo "cleaning up temporary files"
 rm server.csr
 rm client.csr
@@ -0,0 +1,40 @@
+# immediately bail if any command fails
+set -e
+
+# generates a server certificate signed through two intermediate CAs, with
+# the chain sent by the server in different orders:
+# ordered: leaf, intermediate 2, intermediate 1
+# reversed: leaf, intermediate 1, intermediate 2
+# missing: leaf, intermediate 1 (intermediate 2 is omitted)
+
+pushd "$(dirname "$0")"
+mkdir -p chain
+
+echo "generating root CA private key and certificate"
+openssl req -nodes -new -x509 -keyout chain/ca-key.pem -out chain/ca-cert.pem -days 65536 -config config/ca.cnf -addext basicConstraints=critical,CA:TRUE
+
+echo "basicConstraints = critical, CA:TRUE" > chain/ca.ext
+
+issuer=chain/ca
+for i in 1 2; do
+    echo "generating intermediate $i private key and certificate"
+    openssl req -new -nodes -newkey ec -pkeyopt ec_paramgen_curve:secp384r1 -keyout chain/intermediate-$i-key.pem -out chain/intermediate.csr -subj "/O=intermediate/CN=intermediate$i.localca"
+    openssl x509 -days 65536 -req -in chain/intermediate.csr -CA $issuer-cert.pem -CAkey $issuer-key.pem -CAcreateserial -out chain/intermediate-$i-cert.pem -extfile chain/ca.ext
+    issuer=chain/intermediate-$i
+done
+
+echo "generating server private key and certificate"
+openssl req -new -nodes -newkey ec -pkeyopt ec_paramgen_curve:secp384r1 -keyout chain/server-key.pem -out chain/server.csr -config config/server.cnf
+openssl x509 -days 65536 -req -in chain/server.csr -CA $issuer-cert.pem -CAkey $issuer-key.pem -CAcreateserial -out chain/server-cert.pem -extensions req_ext -extfile config/server.cnf
+
+echo "verifying generated certificates"
+openssl verify -CAfile chain/ca-cert.pem -untrusted chain/intermediate-1-cert.pem -untrusted chain/intermediate-2-cert.pem chain/server-cert.pem
+
+cat chain/server-cert.pem chain/intermediate-2-cert.pem chain/intermediate-1-cert.pem > chain/ordered-fullchain.pem
+cat chain/server-cert.pem chain/intermediate-1-cert.pem chain/intermediate-2-cert.pem > chain/reversed-fullchain.pem
+cat chain/server-cert.pem chain/intermediate-1-cert.pem > chain/missing-fullchain.pem
+
+echo "cleaning up temporary files"
+rm chain/*.csr chain/*.srl chain/ca.ext chain/ca-key.pem chain/intermediate-*.pem chain/server-cert.pem
+
+popd
@@ -0,0 +1,52 @@
+# immediately bail if any command fails
+set -e
+
+# generates server certificate chains of 2 to 6 certificates, where the server
+# certificate is issued through 1 to 5 intermediate CAs, with each chain sent
+# by the server in different orders:
+# ordered: leaf, then intermediates from the leaf's issuer up to the root
+# reversed: leaf, then intermediates from the root down to the leaf's issuer
+# missing: ordered, with the leaf's issuer omitted
+
+pushd "$(dirname "$0")"
+mkdir -p long-chain
+
+echo "generating root CA private key and certificate"
+openssl req -nodes -new -x509 -keyout long-chain/ca-key.pem -out long-chain/ca-cert.pem -days 65536 -config config/ca.cnf -addext basicConstraints=critical,CA:TRUE
+
+echo "basicConstraints = critical, CA:TRUE" > long-chain/ca.ext
+
+echo "generating server private key"
+openssl req -new -nodes -newkey ec -pkeyopt ec_paramgen_curve:secp384r1 -keyout long-chain/server-key.pem -out long-chain/server.csr -config config/server.cnf
+
+for len in 2 3 4 5 6; do
+    issuer=long-chain/ca
+    ordered=""
+    reversed=""
+    for i in $(seq 1 $((len - 1))); do
+        echo "generating intermediate $i of the $len certificate chain"
+        openssl req -new -nodes -newkey ec -pkeyopt ec_paramgen_curve:secp384r1 -keyout long-chain/intermediate-$i-key.pem -out long-chain/intermediate.csr -subj "/O=intermediate/CN=intermediate$i.localca"
+        openssl x509 -days 65536 -req -in long-chain/intermediate.csr -CA $issuer-cert.pem -CAkey $issuer-key.pem -CAcreateserial -out long-chain/intermediate-$i-cert.pem -extfile long-chain/ca.ext
+        issuer=long-chain/intermediate-$i
+        ordered="$issuer-cert.pem $ordered"
+        reversed="$reversed $issuer-cert.pem"
+    done
+
+    echo "generating server certificate of the $len certificate chain"
+    openssl x509 -days 65536 -req -in long-chain/server.csr -CA $issuer-cert.pem -CAkey $issuer-key.pem -CAcreateserial -out long-chain/server-cert.pem -extensions req_ext -extfile config/server.cnf
+
+    echo "verifying the $len certificate chain"
+    cat $ordered > long-chain/untrusted.pem
+    openssl verify -CAfile long-chain/ca-cert.pem -untrusted long-chain/untrusted.pem long-chain/server-cert.pem
+
+    cat long-chain/server-cert.pem $ordered > long-chain/server-$len-ordered-fullchain.pem
+    cat long-chain/server-cert.pem $reversed > long-chain/server-$len-reversed-fullchain.pem
+    cat long-chain/server-cert.pem ${ordered#* } > long-chain/server-$len-missing-fullchain.pem
+
+    rm long-chain/intermediate-*.pem long-chain/server-cert.pem long-chain/untrusted.pem
+done
+
+echo "cleaning up temporary files"
+rm long-chain/*.csr long-chain/*.srl long-chain/ca.ext long-chain/ca-key.pem
+
+popd
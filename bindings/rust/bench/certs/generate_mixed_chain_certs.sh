@@ -0,0 +1,53 @@
+# immediately bail if any command fails
+set -e
+
+# generates server certificates issued through one intermediate CA, for every
+# combination of CA and leaf key type. The intermediate uses the same key type
+# as the root, so chains with a different leaf key type switch signature
+# algorithms between the leaf and its issuer, like ECDSA leaves issued under a
+# legacy RSA PKI.
+
+pushd "$(dirname "$0")"
+mkdir -p mixed-chain
+
+echo "basicConstraints = critical, CA:TRUE" > mixed-chain/ca.ext
+
+for ca in ecdsa rsa; do
+    if [ $ca = ecdsa ]; then
+        ca_key="-newkey ec -pkeyopt ec_paramgen_curve:secp384r1"
+        # match the digest to the curve
+        digest=-sha384
+    else
+        ca_key="-newkey rsa:2048"
+        digest=-sha256
+    fi
+
+    echo "generating $ca root CA private key and certificate"
+    openssl req -nodes -new -x509 $ca_key $digest -keyout mixed-chain/$ca-ca-key.pem -out mixed-chain/$ca-ca-cert.pem -days 65536 -config config/ca.cnf -addext basicConstraints=critical,CA:TRUE
+
+    echo "generating $ca intermediate private key and certificate"
+    openssl req -new -nodes $ca_key -keyout mixed-chain/$ca-intermediate-key.pem -out mixed-chain/intermediate.csr -subj "/O=intermediate/CN=intermediate.localca"
+    openssl x509 -days 65536 -req $digest -in mixed-chain/intermediate.csr -CA mixed-chain/$ca-ca-cert.pem -CAkey mixed-chain/$ca-ca-key.pem -CAcreateserial -out mixed-chain/$ca-intermediate-cert.pem -extfile mixed-chain/ca.ext
+
+    for leaf in ecdsa rsa; do
+        if [ $leaf = ecdsa ]; then
+            leaf_key="-newkey ec -pkeyopt ec_paramgen_curve:secp384r1"
+        else
+            leaf_key="-newkey rsa:2048"
+        fi
+
+        echo "generating $leaf server private key and certificate issued by the $ca intermediate"
+        openssl req -new -nodes $leaf_key -keyout mixed-chain/$ca-ca-$leaf-key.pem -out mixed-chain/server.csr -config config/server.cnf
+        openssl x509 -days 65536 -req $digest -in mixed-chain/server.csr -CA mixed-chain/$ca-intermediate-cert.pem -CAkey mixed-chain/$ca-intermediate-key.pem -CAcreateserial -out mixed-chain/$ca-ca-$leaf-cert.pem -extensions req_ext -extfile config/server.cnf
+
+        echo "verifying generated certificate"
+        openssl verify -CAfile mixed-chain/$ca-ca-cert.pem -untrusted mixed-chain/$ca-intermediate-cert.pem mixed-chain/$ca-ca-$leaf-cert.pem
+
+        cat mixed-chain/$ca-ca-$leaf-cert.pem mixed-chain/$ca-intermediate-cert.pem > mixed-chain/$ca-ca-$leaf-fullchain.pem
+    done
+done
+
+echo "cleaning up temporary files"
+rm mixed-chain/*.csr mixed-chain/*.srl mixed-chain/ca.ext mixed-chain/*-ca-key.pem mixed-chain/*-intermediate-*.pem mixed-chain/*-ca-*-cert.pem
+
+popd
@@ -0,0 +1,27 @@
+# immediately bail if any command fails
+set -e
+
+# generates RSA server certificates with 2048, 3072, and 4096 bit keys, all
+# issued by the same CA, to compare the cost of RSA signatures by key size
+
+pushd "$(dirname "$0")"
+mkdir -p rsa-size
+
+echo "generating CA private key and certificate"
+openssl req -nodes -new -x509 -keyout rsa-size/ca-key.pem -out rsa-size/ca-cert.pem -days 65536 -config config/ca.cnf -addext basicConstraints=critical,CA:TRUE
+
+for bits in 2048 3072 4096; do
+    echo "generating server $bits bit RSA private key and certificate"
+    openssl req -new -nodes -newkey rsa:$bits -keyout rsa-size/server-$bits-key.pem -out rsa-size/server.csr -config config/server.cnf
+    openssl x509 -days 65536 -req -in rsa-size/server.csr -CA rsa-size/ca-cert.pem -CAkey rsa-size/ca-key.pem -CAcreateserial -out rsa-size/server-$bits-cert.pem -extensions req_ext -extfile config/server.cnf
+
+    echo "verifying generated $bits bit certificate"
+    openssl verify -CAfile rsa-size/ca-cert.pem rsa-size/server-$bits-cert.pem
+
+    cat rsa-size/server-$bits-cert.pem rsa-size/ca-cert.pem > rsa-size/server-$bits-fullchain.pem
+done
+
+echo "cleaning up temporary files"
+rm rsa-size/*.csr rsa-size/*.srl rsa-size/ca-key.pem rsa-size/server-*-cert.pem
+
+popd
@@ -0,0 +1,33 @@
+# immediately bail if any command fails
+set -e
+
+# generates server certificates with many subject alternative names, where only
+# the last name matches "localhost"
+
+pushd "$(dirname "$0")"
+mkdir -p sans
+
+echo "generating CA private key and certificate"
+openssl req -nodes -new -x509 -keyout sans/ca-key.pem -out sans/ca-cert.pem -days 65536 -config config/ca.cnf -addext basicConstraints=critical,CA:TRUE
+
+for count in 1 10 100 1000; do
+    echo "generating server certificate with $count names"
+    config=sans/server-$count.cnf
+    sed '/^\[alt_names\]/q' config/server.cnf > $config
+    for i in $(seq 1 $((count - 1))); do
+        echo "DNS.$i = *.domain$i.example.com" >> $config
+    done
+    echo "DNS.$count = localhost" >> $config
+
+    openssl req -new -nodes -newkey ec -pkeyopt ec_paramgen_curve:secp384r1 -keyout sans/server-$count-key.pem -out sans/server.csr -config $config
+    openssl x509 -days 65536 -req -in sans/server.csr -CA sans/ca-cert.pem -CAkey sans/ca-key.pem -CAcreateserial -out sans/server-cert.pem -extensions req_ext -extfile $config
+    openssl verify -CAfile sans/ca-cert.pem sans/server-cert.pem
+    cat sans/server-cert.pem sans/ca-cert.pem > sans/server-$count-fullchain.pem
+
+    rm $config sans/server.csr sans/server-cert.pem
+done
+
+echo "cleaning up temporary files"
+rm sans/ca-key.pem sans/ca-cert.srl
+
+popd
@@ -0,0 +1,34 @@
+# immediately bail if any command fails
+set -e
+
+# generates ECDSA and RSA certificates for both the server and the client,
+# all issued by the same CA, so that each side's signature type can be chosen
+# independently
+
+pushd "$(dirname "$0")"
+mkdir -p sig
+
+echo "generating CA private key and certificate"
+openssl req -nodes -new -x509 -keyout sig/ca-key.pem -out sig/ca-cert.pem -days 65536 -config config/ca.cnf -addext basicConstraints=critical,CA:TRUE
+
+for side in server client; do
+    echo "generating $side ECDSA private key and certificate"
+    openssl req -new -nodes -newkey ec -pkeyopt ec_paramgen_curve:secp384r1 -keyout sig/$side-ecdsa-key.pem -out sig/$side.csr -config config/$side.cnf
+    openssl x509 -days 65536 -req -in sig/$side.csr -CA sig/ca-cert.pem -CAkey sig/ca-key.pem -CAcreateserial -out sig/$side-ecdsa-cert.pem -extensions req_ext -extfile config/$side.cnf
+
+    echo "generating $side RSA private key and certificate"
+    openssl req -new -nodes -newkey rsa:2048 -keyout sig/$side-rsa-key.pem -out sig/$side.csr -config config/$side.cnf
+    openssl x509 -days 65536 -req -in sig/$side.csr -CA sig/ca-cert.pem -CAkey sig/ca-key.pem -CAcreateserial -out sig/$side-rsa-cert.pem -extensions req_ext -extfile config/$side.cnf
+done
+
+echo "verifying generated certificates"
+openssl verify -CAfile sig/ca-cert.pem sig/*-ecdsa-cert.pem sig/*-rsa-cert.pem
+
+for sig in ecdsa rsa; do
+    cat sig/server-$sig-cert.pem sig/ca-cert.pem > sig/server-$sig-fullchain.pem
+done
+
+echo "cleaning up temporary files"
+rm sig/*.csr sig/*.srl sig/ca-key.pem sig/server-*-cert.pem
+
+popd
@@ -0,0 +1,27 @@
+# immediately bail if any command fails
+set -e
+
+# generates a bundle of 1000 CA certificates for trust store benchmarks: 999
+# unrelated self-signed CAs, followed by the CA that issued the server cert
+# in this directory. The bundle is written both as PEM and as concatenated DER
+
+CA_COUNT=999
+
+pushd "$(dirname "$0")"
+mkdir -p trust
+
+echo "generating $CA_COUNT unrelated CA certificates"
+rm -f trust/ca-bundle.pem trust/ca-bundle.der
+for i in $(seq 1 $CA_COUNT); do
+    openssl req -x509 -nodes -newkey ec -pkeyopt ec_paramgen_curve:prime256v1 -keyout trust/ca-key.pem -out trust/ca-cert.pem -days 65536 -subj "/O=bench/CN=bench-ca-$i" -addext basicConstraints=critical,CA:TRUE 2> /dev/null
+    cat trust/ca-cert.pem >> trust/ca-bundle.pem
+    openssl x509 -in trust/ca-cert.pem -outform der >> trust/ca-bundle.der
+done
+
+cat ca-cert.pem >> trust/ca-bundle.pem
+openssl x509 -in ca-cert.pem -outform der >> trust/ca-bundle.der
+
+echo "cleaning up temporary files"
+rm trust/ca-key.pem trust/ca-cert.pem
+
+popd
@@ -0,0 +1,124 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Counting global allocator for fast, in-process allocation measurements
+//!
+//! s2n-tls routes its allocations through the Rust global allocator, so they
+//! are counted along with Rust allocations. Allocations made directly by
+//! libcrypto are not counted.
+//!
+//! With the `slow-alloc` feature, allocations are made through
+//! [`crate::slow_alloc::SlowAllocator`] instead of the system allocator.
+
+#[cfg(feature = "slow-alloc")]
+use crate::slow_alloc::SlowAllocator as Inner;
+use crate::TlsBenchHarness;
+#[cfg(not(feature = "slow-alloc"))]
+use std::alloc::System as Inner;
+use std::{
+    alloc::{GlobalAlloc, Layout},
+    cell::Cell,
+    error::Error,
+};
+
+// counters are thread local so that concurrently running tests or benches
+// don't affect each other's measurements
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+    static ALLOCATED_BYTES: Cell<usize> = const { Cell::new(0) };
+}
+
+pub struct CountingAllocator;
+
+impl CountingAllocator {
+    fn record(bytes: usize) {
+        // counters may be unavailable while the thread is being torn down
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        let _ = ALLOCATED_BYTES.try_with(|total| total.set(total.get() + bytes));
+    }
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        Self::record(layout.size());
+        Inner.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        Inner.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        Self::record(new_size);
+        Inner.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AllocationStats {
+    /// Number of allocations and reallocations
+    pub allocations: usize,
+    /// Total bytes requested by all allocations and reallocations
+    pub bytes: usize,
+}
+
+fn reset() {
+    ALLOCATIONS.with(|count| count.set(0));
+    ALLOCATED_BYTES.with(|total| total.set(0));
+}
+
+fn stats() -> AllocationStats {
+    AllocationStats {
+        allocations: ALLOCATIONS.with(Cell::get),
+        bytes: ALLOCATED_BYTES.with(Cell::get),
+    }
+}
+
+/// Run `f` and get the allocations it made on the current thread
+pub fn measure<R>(f: impl FnOnce() -> R) -> (R, AllocationStats) {
+    reset();
+    let result = f();
+    (result, stats())
+}
+
+/// Get allocations made by one handshake on the current thread, excluding
+/// config and connection initialization
+pub fn handshake_allocations<T: TlsBenchHarness>() -> Result<AllocationStats, Box<dyn Error>> {
+    let mut harness = T::default()?;
+    let (result, stats) = measure(|| harness.handshake());
+    result?;
+    assert!(harness.handshake_completed());
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{RustlsHarness, S2NHarness};
+
+    /// Regression threshold for allocations made during one s2n-tls
+    /// handshake, including s2n-tls's own allocations but not libcrypto's
+    const S2N_TLS_MAX_HANDSHAKE_ALLOCATIONS: usize = 1000;
+
+    #[test]
+    fn test_s2n_tls_handshake_allocations() {
+        let stats = handshake_allocations::<S2NHarness>().unwrap();
+        assert!(stats.allocations > 0);
+        assert!(
+            stats.allocations < S2N_TLS_MAX_HANDSHAKE_ALLOCATIONS,
+            "{stats:?}"
+        );
+    }
+
+    #[test]
+    fn test_counts_are_per_handshake() {
+        // counters are reset for each measurement
+        let first = handshake_allocations::<RustlsHarness>().unwrap();
+        let second = handshake_allocations::<RustlsHarness>().unwrap();
+        assert!(first.allocations > 0);
+        assert!(second.allocations < 2 * first.allocations);
+    }
+}
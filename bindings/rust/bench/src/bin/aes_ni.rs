@@ -0,0 +1,97 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Compares s2n-tls AES-GCM encryption throughput with hardware AES
+//! instructions (AES-NI) enabled and disabled
+//!
+//! libcrypto reads the CPU capabilities it may use once, when it initializes,
+//! so each case runs in a fresh child process. AES-NI is disabled by masking
+//! its capability bit with the `OPENSSL_ia32cap` environment variable, which
+//! is supported by OpenSSL and AWS-LC on x86_64. Other libcryptos ignore the
+//! variable, in which case both cases use the same implementation. rustls
+//! uses ring, which doesn't support disabling AES-NI, so it isn't compared.
+//!
+//! Usage: `cargo run --release --bin aes_ni [iterations]`
+
+use bench::{
+    harness::Mode, CipherSuite, CryptoConfig, ECGroup, HandshakeType, S2NHarness, TlsBenchHarness,
+};
+use std::{env, error::Error, process::Command, time::Instant};
+
+const DEFAULT_ITERATIONS: usize = 1000;
+/// Amount of application data encrypted per iteration
+const DATA_LEN: usize = 100_000;
+const CHILD_ARG: &str = "--child";
+/// Clears the AES-NI and PCLMULQDQ capability bits, which are both used by
+/// the hardware AES-GCM implementation
+const SOFTWARE_AES_IA32CAP: &str = "~0x200000200000000";
+
+/// Encrypt `iterations` batches of data with each cipher suite, reporting
+/// the throughput of each in bytes per second on stdout
+fn measure_throughput(iterations: usize) -> Result<(), Box<dyn Error>> {
+    let data = vec![0; DATA_LEN];
+    for cipher_suite in [
+        CipherSuite::AES_128_GCM_SHA256,
+        CipherSuite::AES_256_GCM_SHA384,
+    ] {
+        let crypto_config = CryptoConfig {
+            cipher_suite,
+            ec_group: ECGroup::SECP256R1,
+        };
+        let mut harness = S2NHarness::new(&crypto_config, HandshakeType::ServerAuth)?;
+        harness.handshake()?;
+
+        let start = Instant::now();
+        for _ in 0..iterations {
+            harness.send(Mode::Client, &data)?;
+            harness.discard_pending_data(Mode::Server);
+        }
+        let bytes_per_sec = (iterations * DATA_LEN) as f64 / start.elapsed().as_secs_f64();
+        println!("{:?} {bytes_per_sec}", crypto_config.cipher_suite);
+    }
+    Ok(())
+}
+
+/// Run the measurement in a fresh process, optionally with AES-NI disabled
+fn run_child(iterations: usize, software_aes: bool) -> Result<String, Box<dyn Error>> {
+    let mut command = Command::new(env::current_exe()?);
+    command.arg(CHILD_ARG).arg(iterations.to_string());
+    if software_aes {
+        command.env("OPENSSL_ia32cap", SOFTWARE_AES_IA32CAP);
+    } else {
+        command.env_remove("OPENSSL_ia32cap");
+    }
+    let output = command.output()?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).into());
+    }
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let mut args = env::args().skip(1);
+    let mut arg = args.next();
+    let child = arg.as_deref() == Some(CHILD_ARG);
+    if child {
+        arg = args.next();
+    }
+    let iterations = match arg {
+        Some(iterations) => iterations.parse()?,
+        None => DEFAULT_ITERATIONS,
+    };
+    if child {
+        return measure_throughput(iterations);
+    }
+
+    for (name, software_aes) in [("hardware", false), ("software", true)] {
+        for line in run_child(iterations, software_aes)?.lines() {
+            let (cipher_suite, bytes_per_sec) = line.split_once(' ').ok_or("invalid output")?;
+            let bytes_per_sec: f64 = bytes_per_sec.parse()?;
+            println!(
+                "{name} {cipher_suite}: {:.1} MB/s",
+                bytes_per_sec / 1_000_000.0
+            );
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,82 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Compares cold-start and warm s2n-tls handshake latency
+//!
+//! The first handshake in a process pays one-time library initialization
+//! costs, such as libcrypto self-tests under FIPS. Cold-start latency is
+//! measured by running each handshake in a fresh child process.
+//!
+//! Usage: `cargo run --release --bin cold_start [samples]`
+
+use bench::{S2NHarness, TlsBenchHarness};
+use std::{
+    env,
+    error::Error,
+    process::Command,
+    time::{Duration, Instant},
+};
+
+const DEFAULT_SAMPLES: usize = 50;
+const CHILD_ARG: &str = "--child";
+
+/// Initialize connections and complete one handshake, including any library
+/// initialization that hasn't happened yet in this process
+fn timed_handshake() -> Result<Duration, Box<dyn Error>> {
+    let start = Instant::now();
+    let mut harness = S2NHarness::default()?;
+    harness.handshake()?;
+    let elapsed = start.elapsed();
+    assert!(harness.handshake_completed());
+    Ok(elapsed)
+}
+
+/// Run one handshake in a fresh process, which reports its latency on stdout
+fn cold_handshake() -> Result<Duration, Box<dyn Error>> {
+    let output = Command::new(env::current_exe()?).arg(CHILD_ARG).output()?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).into());
+    }
+    let nanos: u64 = String::from_utf8(output.stdout)?.trim().parse()?;
+    Ok(Duration::from_nanos(nanos))
+}
+
+fn report(name: &str, mut samples: Vec<Duration>) {
+    samples.sort();
+    let mean = samples.iter().sum::<Duration>() / samples.len() as u32;
+    println!(
+        "{name}: mean {mean:?}, median {:?}, min {:?}, max {:?}",
+        samples[samples.len() / 2],
+        samples[0],
+        samples[samples.len() - 1]
+    );
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let arg = env::args().nth(1);
+    if arg.as_deref() == Some(CHILD_ARG) {
+        println!("{}", timed_handshake()?.as_nanos());
+        return Ok(());
+    }
+    let samples = match arg {
+        Some(samples) => samples.parse()?,
+        None => DEFAULT_SAMPLES,
+    };
+    if samples == 0 {
+        return Err("at least one sample is required".into());
+    }
+
+    let cold = (0..samples)
+        .map(|_| cold_handshake())
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // the first handshake warms up this process
+    timed_handshake()?;
+    let warm = (0..samples)
+        .map(|_| timed_handshake())
+        .collect::<Result<Vec<_>, _>>()?;
+
+    report("cold", cold);
+    report("warm", warm);
+    Ok(())
+}
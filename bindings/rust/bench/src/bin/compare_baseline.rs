@@ -0,0 +1,132 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Compares the latest bench results with a saved baseline, failing if any
+//! bench regressed by more than a threshold
+//!
+//! Criterion saves the estimates of each bench as JSON. Save a baseline on
+//! the base commit, then run the benches on the commit under test, which
+//! saves them as the latest results:
+//!
+//! ```text
+//! cargo bench -- --save-baseline main
+//! git checkout my-change
+//! cargo bench
+//! cargo run --release --bin compare_baseline main [--threshold <percent>]
+//! ```
+//!
+//! The change in mean time of every bench with both results is printed,
+//! named by its criterion ID, which includes the library and config. The
+//! process exits with an error if any mean time increased by more than the
+//! threshold percentage, 5% by default, so CI can gate changes on it.
+
+use serde_json::Value;
+use std::{
+    env,
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+const THRESHOLD_ARG: &str = "--threshold";
+const DEFAULT_THRESHOLD_PERCENT: f64 = 5.0;
+/// Directory criterion saves the results of the latest run to
+const LATEST: &str = "new";
+
+/// Change in mean time of one bench between the baseline and latest results
+struct Comparison {
+    id: String,
+    baseline_nanos: f64,
+    latest_nanos: f64,
+}
+
+impl Comparison {
+    fn delta_percent(&self) -> f64 {
+        (self.latest_nanos - self.baseline_nanos) / self.baseline_nanos * 100.0
+    }
+}
+
+/// Read the mean time in nanoseconds from a criterion estimates file
+fn mean_nanos(path: &Path) -> Result<f64, Box<dyn Error>> {
+    let estimates: Value = serde_json::from_slice(&fs::read(path)?)?;
+    estimates["mean"]["point_estimate"]
+        .as_f64()
+        .ok_or_else(|| format!("no mean estimate in {}", path.display()).into())
+}
+
+/// Find every bench under `dir` with results for both `baseline` and the
+/// latest run
+fn compare(
+    criterion_dir: &Path,
+    dir: &Path,
+    baseline: &str,
+    comparisons: &mut Vec<Comparison>,
+) -> Result<(), Box<dyn Error>> {
+    let baseline_estimates = dir.join(baseline).join("estimates.json");
+    let latest_estimates = dir.join(LATEST).join("estimates.json");
+    if baseline_estimates.exists() && latest_estimates.exists() {
+        comparisons.push(Comparison {
+            id: dir.strip_prefix(criterion_dir)?.display().to_string(),
+            baseline_nanos: mean_nanos(&baseline_estimates)?,
+            latest_nanos: mean_nanos(&latest_estimates)?,
+        });
+        return Ok(());
+    }
+
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            compare(criterion_dir, &path, baseline, comparisons)?;
+        }
+    }
+    Ok(())
+}
+
+fn main() -> Result<ExitCode, Box<dyn Error>> {
+    let mut baseline = None;
+    let mut threshold = DEFAULT_THRESHOLD_PERCENT;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == THRESHOLD_ARG {
+            threshold = args.next().ok_or("missing threshold")?.parse()?;
+        } else {
+            baseline = Some(arg);
+        }
+    }
+    let baseline = baseline.ok_or("usage: compare_baseline <baseline> [--threshold <percent>]")?;
+
+    let target_dir = env::var_os("CARGO_TARGET_DIR").map_or_else(|| "target".into(), PathBuf::from);
+    let criterion_dir = target_dir.join("criterion");
+    let mut comparisons = Vec::new();
+    compare(&criterion_dir, &criterion_dir, &baseline, &mut comparisons)?;
+    if comparisons.is_empty() {
+        return Err(format!("no benches with results for baseline {baseline}").into());
+    }
+    comparisons.sort_by(|a, b| a.id.cmp(&b.id));
+
+    let mut regressions = 0;
+    for comparison in &comparisons {
+        let delta = comparison.delta_percent();
+        let regressed = delta > threshold;
+        if regressed {
+            regressions += 1;
+        }
+        println!(
+            "{}: {:.0} ns -> {:.0} ns ({delta:+.1}%){}",
+            comparison.id,
+            comparison.baseline_nanos,
+            comparison.latest_nanos,
+            if regressed { " REGRESSED" } else { "" }
+        );
+    }
+
+    if regressions > 0 {
+        println!(
+            "{regressions} of {} benches regressed by more than {threshold}%",
+            comparisons.len()
+        );
+        return Ok(ExitCode::FAILURE);
+    }
+    Ok(ExitCode::SUCCESS)
+}
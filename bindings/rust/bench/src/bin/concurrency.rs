@@ -0,0 +1,71 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Reports handshake throughput when a burst of handshakes is spread across
+//! a pool of threads, like a server accepting many connections at once
+//!
+//! Single-threaded benches can't show contention between threads, such as
+//! in the allocator or on locks inside the libraries. Each handshake still
+//! uses its own harness, since harness IO buffers can't be shared between
+//! threads.
+//!
+//! Usage: `cargo run --release --bin concurrency [handshakes]`
+
+use bench::{
+    CipherSuite, CryptoConfig, ECGroup, HandshakeType, RustlsHarness, S2NHarness, TlsBenchHarness,
+};
+use std::{
+    env,
+    error::Error,
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
+
+const DEFAULT_HANDSHAKES: usize = 10_000;
+const POOL_SIZES: [usize; 5] = [1, 2, 4, 8, 16];
+
+/// Complete `handshakes` handshakes using `pool_size` threads, returning the
+/// wall-clock time taken for all of them
+///
+/// Threads take handshakes from a shared counter, so threads that finish
+/// early keep working until the whole burst is complete.
+fn run_burst<T: TlsBenchHarness>(handshakes: usize, pool_size: usize) -> Duration {
+    let crypto_config = CryptoConfig {
+        cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+        ec_group: ECGroup::SECP256R1,
+    };
+    let started = AtomicUsize::new(0);
+
+    let start = Instant::now();
+    thread::scope(|scope| {
+        for _ in 0..pool_size {
+            scope.spawn(|| {
+                while started.fetch_add(1, Ordering::Relaxed) < handshakes {
+                    let mut harness = T::new(&crypto_config, HandshakeType::ServerAuth).unwrap();
+                    harness.handshake().unwrap();
+                    assert!(harness.handshake_completed());
+                }
+            });
+        }
+    });
+    start.elapsed()
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let handshakes = match env::args().nth(1) {
+        Some(handshakes) => handshakes.parse()?,
+        None => DEFAULT_HANDSHAKES,
+    };
+
+    for pool_size in POOL_SIZES {
+        for (library, elapsed) in [
+            ("s2n-tls", run_burst::<S2NHarness>(handshakes, pool_size)),
+            ("rustls", run_burst::<RustlsHarness>(handshakes, pool_size)),
+        ] {
+            let handshakes_per_sec = handshakes as f64 / elapsed.as_secs_f64();
+            println!("{library} {pool_size} threads: {handshakes_per_sec:.0} handshakes/s");
+        }
+    }
+    Ok(())
+}
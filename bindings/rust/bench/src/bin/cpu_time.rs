@@ -0,0 +1,168 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Reports the CPU time used by handshakes and data transfers for each
+//! library, alongside the wall time
+//!
+//! Wall time includes time spent descheduled, so it's noisy on a busy host.
+//! CPU time is the user and system time of the measuring thread from
+//! `getrusage`, which only counts time the thread was running.
+//!
+//! Usage: `cargo run --release --bin cpu_time [--json] [iterations]`
+//!
+//! With `--json`, results are written to stdout as a JSON array with one
+//! object per library and operation.
+
+use bench::{
+    harness::Mode, CipherSuite, CryptoConfig, ECGroup, HandshakeType, RustlsHarness, S2NHarness,
+    TlsBenchHarness,
+};
+use serde_json::json;
+use std::{
+    env,
+    error::Error,
+    time::{Duration, Instant},
+};
+
+const JSON_ARG: &str = "--json";
+const DEFAULT_ITERATIONS: usize = 1_000;
+/// Application data sent from client to server by each transfer
+const TRANSFER_LEN: usize = 100_000;
+
+// only Linux can measure the calling thread alone
+#[cfg(target_os = "linux")]
+const RUSAGE_WHO: libc::c_int = libc::RUSAGE_THREAD;
+#[cfg(not(target_os = "linux"))]
+const RUSAGE_WHO: libc::c_int = libc::RUSAGE_SELF;
+
+/// Get the user plus system CPU time used so far
+fn cpu_time() -> Duration {
+    let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+    assert_eq!(unsafe { libc::getrusage(RUSAGE_WHO, &mut usage) }, 0);
+    let to_duration =
+        |time: libc::timeval| Duration::new(time.tv_sec as u64, time.tv_usec as u32 * 1000);
+    to_duration(usage.ru_utime) + to_duration(usage.ru_stime)
+}
+
+/// Total wall and CPU time of one library and operation over all iterations
+struct Measurement {
+    library: &'static str,
+    crypto_backend: String,
+    operation: &'static str,
+    iterations: usize,
+    wall_time: Duration,
+    cpu_time: Duration,
+}
+
+impl Measurement {
+    /// Measure `operation` on `iterations` harnesses, excluding config and
+    /// connection initialization
+    fn new<T: TlsBenchHarness>(
+        library: &'static str,
+        operation: &'static str,
+        iterations: usize,
+        setup: impl Fn() -> Result<T, Box<dyn Error>>,
+        run: impl Fn(&mut T) -> Result<(), Box<dyn Error>>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let mut wall_time = Duration::ZERO;
+        let mut cpu_time_total = Duration::ZERO;
+        for _ in 0..iterations {
+            let mut harness = setup()?;
+            let (wall_start, cpu_start) = (Instant::now(), cpu_time());
+            run(&mut harness)?;
+            cpu_time_total += cpu_time() - cpu_start;
+            wall_time += wall_start.elapsed();
+        }
+
+        Ok(Self {
+            library,
+            crypto_backend: T::crypto_backend(),
+            operation,
+            iterations,
+            wall_time,
+            cpu_time: cpu_time_total,
+        })
+    }
+
+    fn measure_library<T: TlsBenchHarness>(
+        library: &'static str,
+        iterations: usize,
+    ) -> Result<Vec<Self>, Box<dyn Error>> {
+        let crypto_config = CryptoConfig {
+            cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+            ec_group: ECGroup::SECP256R1,
+        };
+        let new_harness = || T::new(&crypto_config, HandshakeType::ServerAuth);
+        let data = vec![1; TRANSFER_LEN];
+
+        let handshake = Self::new(library, "handshake", iterations, new_harness, |harness| {
+            harness.handshake()
+        })?;
+        let transfer = Self::new(
+            library,
+            "transfer",
+            iterations,
+            || {
+                let mut harness = new_harness()?;
+                harness.handshake()?;
+                Ok(harness)
+            },
+            |harness| {
+                let mut received = vec![0; data.len()];
+                harness.send(Mode::Client, &data)?;
+                harness.recv(Mode::Server, &mut received)
+            },
+        )?;
+        Ok(vec![handshake, transfer])
+    }
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let mut json = false;
+    let mut iterations = DEFAULT_ITERATIONS;
+    for arg in env::args().skip(1) {
+        if arg == JSON_ARG {
+            json = true;
+        } else {
+            iterations = arg.parse()?;
+        }
+    }
+    if iterations == 0 {
+        return Err("at least one iteration is required".into());
+    }
+
+    let mut measurements = Measurement::measure_library::<S2NHarness>("s2n-tls", iterations)?;
+    measurements.extend(Measurement::measure_library::<RustlsHarness>(
+        "rustls", iterations,
+    )?);
+
+    if json {
+        let results: Vec<_> = measurements
+            .iter()
+            .map(|measurement| {
+                json!({
+                    "library": measurement.library,
+                    "crypto_backend": measurement.crypto_backend,
+                    "operation": measurement.operation,
+                    "iterations": measurement.iterations,
+                    "wall_nanos": measurement.wall_time.as_nanos() as u64,
+                    "cpu_nanos": measurement.cpu_time.as_nanos() as u64,
+                })
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&results)?);
+    } else {
+        for measurement in &measurements {
+            let per_iteration = |time: Duration| time / measurement.iterations as u32;
+            println!(
+                "{} ({}) {}: {:?} wall, {:?} CPU per iteration",
+                measurement.library,
+                measurement.crypto_backend,
+                measurement.operation,
+                per_iteration(measurement.wall_time),
+                per_iteration(measurement.cpu_time)
+            );
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,57 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Reports the share of the bytes on the wire that are application data for
+//! a bulk transfer by s2n-tls, for each cipher suite and max fragment length
+//!
+//! Each record adds a header and an AEAD tag to its application data, so the
+//! smaller the records, the more of the bandwidth goes to record overhead.
+//!
+//! Usage: `cargo run --bin goodput`
+
+use bench::{
+    harness::{record_lengths, Mode},
+    CipherSuite, CryptoConfig, ECGroup, HandshakeType, S2NHarness, TlsBenchHarness,
+};
+use s2n_tls::enums::MaxFragmentLength;
+use std::error::Error;
+
+/// Application data sent by the server
+const DATA_LEN: usize = 1_000_000;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    for cipher_suite in [
+        CipherSuite::AES_128_GCM_SHA256,
+        CipherSuite::AES_256_GCM_SHA384,
+    ] {
+        let crypto_config = CryptoConfig {
+            cipher_suite,
+            ec_group: ECGroup::SECP256R1,
+        };
+        for max_fragment_length in [
+            Some(MaxFragmentLength::Bytes512),
+            Some(MaxFragmentLength::Bytes1024),
+            Some(MaxFragmentLength::Bytes2048),
+            Some(MaxFragmentLength::Bytes4096),
+            None,
+        ] {
+            let mut harness = match max_fragment_length {
+                Some(len) => S2NHarness::new_with_max_fragment_length(&crypto_config, len),
+                None => S2NHarness::new(&crypto_config, HandshakeType::ServerAuth),
+            }?;
+            harness.handshake()?;
+            harness.send(Mode::Server, &vec![1; DATA_LEN])?;
+            let wire = harness.pending_data(Mode::Client);
+            let records = record_lengths(&wire).len();
+            println!(
+                "{:?}, max fragment length {}: {} records, {} wire bytes, {:.2}% goodput",
+                crypto_config.cipher_suite,
+                max_fragment_length.map_or("default".into(), |len| format!("{len:?}")),
+                records,
+                wire.len(),
+                100.0 * DATA_LEN as f64 / wire.len() as f64
+            );
+        }
+    }
+    Ok(())
+}
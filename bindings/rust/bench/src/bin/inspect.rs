@@ -0,0 +1,118 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Runs one handshake with a library and prints everything it negotiated as
+//! JSON, so that the output for different libraries or configs can be diffed
+//!
+//! Usage: `cargo run --bin inspect <s2n-tls|rustls> [options]`
+//!
+//! Options:
+//! - `--cipher-suite <AES_128_GCM_SHA256|AES_256_GCM_SHA384>`
+//! - `--group <SECP256R1|X25519>`
+//! - `--mutual-auth`: require a client cert
+//! - `--resume`: inspect a handshake resuming the session of a full handshake
+//!
+//! Byte and record counts are of the inspected handshake alone.
+
+use bench::{
+    harness::Mode, CipherSuite, CryptoConfig, ECGroup, HandshakeType, RustlsHarness, S2NHarness,
+    TlsBenchHarness,
+};
+use serde_json::{json, Value};
+use std::{env, error::Error};
+
+struct Options {
+    crypto_config: CryptoConfig,
+    handshake_type: HandshakeType,
+    resume: bool,
+}
+
+impl Options {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, Box<dyn Error>> {
+        let mut options = Options {
+            crypto_config: CryptoConfig {
+                cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+                ec_group: ECGroup::SECP256R1,
+            },
+            handshake_type: HandshakeType::ServerAuth,
+            resume: false,
+        };
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--cipher-suite" => {
+                    options.crypto_config.cipher_suite =
+                        match args.next().ok_or("missing cipher suite")?.as_str() {
+                            "AES_128_GCM_SHA256" => CipherSuite::AES_128_GCM_SHA256,
+                            "AES_256_GCM_SHA384" => CipherSuite::AES_256_GCM_SHA384,
+                            other => return Err(format!("unknown cipher suite {other}").into()),
+                        }
+                }
+                "--group" => {
+                    options.crypto_config.ec_group =
+                        match args.next().ok_or("missing group")?.as_str() {
+                            "SECP256R1" => ECGroup::SECP256R1,
+                            "X25519" => ECGroup::X25519,
+                            other => return Err(format!("unknown group {other}").into()),
+                        }
+                }
+                "--mutual-auth" => options.handshake_type = HandshakeType::MutualAuth,
+                "--resume" => options.resume = true,
+                other => return Err(format!("unknown option {other}").into()),
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// Run the handshake described by `options` and describe what it negotiated
+fn inspect<T: TlsBenchHarness>(library: &str, options: &Options) -> Result<Value, Box<dyn Error>> {
+    let mut harness = if options.resume {
+        T::new_with_session_tickets(&options.crypto_config, options.handshake_type)?
+    } else {
+        T::new(&options.crypto_config, options.handshake_type)?
+    };
+    if options.resume {
+        harness.handshake()?;
+        // TLS1.3 session tickets are read along with application data
+        harness.send(Mode::Server, &[0])?;
+        harness.recv(Mode::Client, &mut [0])?;
+        harness = harness.new_resumed()?;
+    }
+    harness.handshake()?;
+
+    let info = harness.connection_info();
+    let count_by_side = |count: &dyn Fn(Mode) -> usize| {
+        json!({
+            "client": count(Mode::Client),
+            "server": count(Mode::Server),
+        })
+    };
+    Ok(json!({
+        "library": library,
+        "crypto_backend": T::crypto_backend(),
+        "version": if info.tls13 { "TLS1.3" } else { "TLS1.2" },
+        "cipher_suite": format!("{:?}", info.cipher_suite),
+        "group": format!("{:?}", info.ec_group),
+        "signature_scheme": info.signature_scheme,
+        "alpn": info.alpn.map(|alpn| String::from_utf8_lossy(&alpn).into_owned()),
+        "resumed": info.resumed,
+        "peer_cert_chain_len": info.peer_cert_chain_len,
+        "round_trips": harness.round_trips(),
+        "handshake_bytes": count_by_side(&|mode| harness.bytes_sent(mode)),
+        "handshake_records": count_by_side(&|mode| harness.records_sent(mode)),
+    }))
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let mut args = env::args().skip(1);
+    let library = args.next().ok_or("missing library")?;
+    let options = Options::parse(args)?;
+
+    let info = match library.as_str() {
+        "s2n-tls" => inspect::<S2NHarness>(&library, &options)?,
+        "rustls" => inspect::<RustlsHarness>(&library, &options)?,
+        other => return Err(format!("unknown library {other}").into()),
+    };
+    println!("{}", serde_json::to_string_pretty(&info)?);
+    Ok(())
+}
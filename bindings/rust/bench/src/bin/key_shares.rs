@@ -0,0 +1,36 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Reports the bytes sent by the client and the round trips of rustls
+//! handshakes as the list of groups offered by the client changes
+//!
+//! The client only sends a key share for its first group. Offering more
+//! groups makes the ClientHello larger, but a first group the server doesn't
+//! support costs a HelloRetryRequest round trip.
+//!
+//! Usage: `cargo run --bin key_shares`
+
+use bench::{harness::Mode, ECGroup, RustlsHarness, TlsBenchHarness};
+use std::error::Error;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    use ECGroup::*;
+
+    let server_groups = [SECP256R1];
+    for client_groups in [
+        vec![SECP256R1],
+        vec![SECP256R1, X25519],
+        vec![X25519, SECP256R1],
+    ] {
+        let mut harness = RustlsHarness::new_with_kx_groups(&client_groups, &server_groups)?;
+        // a HelloRetryRequest adds a round trip, which doesn't fit in one
+        // call to `handshake`
+        harness.handshake_with_timeout(2)?;
+        println!(
+            "client {client_groups:?}, server {server_groups:?}: {} client bytes, {} round trips",
+            harness.bytes_sent(Mode::Client),
+            harness.round_trips()
+        );
+    }
+    Ok(())
+}
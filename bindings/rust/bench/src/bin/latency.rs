@@ -0,0 +1,139 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Reports tail latency percentiles of handshakes for each library and
+//! handshake type
+//!
+//! Criterion reports the mean and median of batches of iterations, which hides
+//! occasional slow handshakes. This times every handshake individually.
+//!
+//! Usage: `cargo run --release --bin latency [--csv] [samples]`
+//!
+//! With `--csv`, results are written to stdout as CSV like the `memory`
+//! binary, with one row per percentile.
+
+use bench::{
+    CipherSuite, CryptoConfig, ECGroup, HandshakeType, RustlsHarness, S2NHarness, TlsBenchHarness,
+};
+use std::{
+    env,
+    error::Error,
+    time::{Duration, Instant},
+};
+
+const CSV_ARG: &str = "--csv";
+const CSV_HEADER: &str = "library,crypto_backend,handshake_type,percentile,nanos";
+const DEFAULT_SAMPLES: usize = 10_000;
+/// Handshakes run before sampling, so that one-time library setup and cold
+/// caches aren't counted
+const WARMUP_HANDSHAKES: usize = 100;
+const PERCENTILES: [f64; 4] = [50.0, 90.0, 99.0, 99.9];
+
+/// Handshake latencies of one library and handshake type, sorted ascending
+struct Measurement {
+    library: &'static str,
+    crypto_backend: String,
+    handshake_type: HandshakeType,
+    latencies: Vec<Duration>,
+}
+
+impl Measurement {
+    fn new<T: TlsBenchHarness>(
+        library: &'static str,
+        handshake_type: HandshakeType,
+        samples: usize,
+    ) -> Result<Self, Box<dyn Error>> {
+        for _ in 0..WARMUP_HANDSHAKES {
+            Self::timed_handshake::<T>(handshake_type)?;
+        }
+        let mut latencies = (0..samples)
+            .map(|_| Self::timed_handshake::<T>(handshake_type))
+            .collect::<Result<Vec<_>, _>>()?;
+        latencies.sort();
+
+        Ok(Self {
+            library,
+            crypto_backend: T::crypto_backend(),
+            handshake_type,
+            latencies,
+        })
+    }
+
+    /// Time one handshake, excluding config and connection initialization
+    fn timed_handshake<T: TlsBenchHarness>(
+        handshake_type: HandshakeType,
+    ) -> Result<Duration, Box<dyn Error>> {
+        let mut harness = T::new(
+            &CryptoConfig {
+                cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+                ec_group: ECGroup::SECP256R1,
+            },
+            handshake_type,
+        )?;
+        let start = Instant::now();
+        harness.handshake()?;
+        let elapsed = start.elapsed();
+        assert!(harness.handshake_completed());
+        Ok(elapsed)
+    }
+
+    /// Get the latency that `percentile` percent of handshakes completed
+    /// within, using the nearest-rank method
+    fn percentile(&self, percentile: f64) -> Duration {
+        let rank = (percentile / 100.0 * self.latencies.len() as f64).ceil() as usize;
+        self.latencies[rank.clamp(1, self.latencies.len()) - 1]
+    }
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let mut csv = false;
+    let mut samples = DEFAULT_SAMPLES;
+    for arg in env::args().skip(1) {
+        if arg == CSV_ARG {
+            csv = true;
+        } else {
+            samples = arg.parse()?;
+        }
+    }
+    if samples == 0 {
+        return Err("at least one sample is required".into());
+    }
+
+    let mut measurements = Vec::new();
+    for handshake_type in [HandshakeType::ServerAuth, HandshakeType::MutualAuth] {
+        measurements.push(Measurement::new::<S2NHarness>(
+            "s2n-tls",
+            handshake_type,
+            samples,
+        )?);
+        measurements.push(Measurement::new::<RustlsHarness>(
+            "rustls",
+            handshake_type,
+            samples,
+        )?);
+    }
+
+    if csv {
+        println!("{CSV_HEADER}");
+    }
+    for measurement in &measurements {
+        for percentile in PERCENTILES {
+            let latency = measurement.percentile(percentile);
+            if csv {
+                println!(
+                    "{},{},{:?},p{percentile},{}",
+                    measurement.library,
+                    measurement.crypto_backend,
+                    measurement.handshake_type,
+                    latency.as_nanos()
+                );
+            } else {
+                println!(
+                    "{} ({}) {:?} p{percentile}: {latency:?}",
+                    measurement.library, measurement.crypto_backend, measurement.handshake_type
+                );
+            }
+        }
+    }
+    Ok(())
+}
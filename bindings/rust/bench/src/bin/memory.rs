@@ -0,0 +1,210 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Reports memory allocated by each library to set up and complete a
+//! handshake, for each handshake type
+//!
+//! Only allocations routed through the Rust global allocator are counted, see
+//! [`bench::alloc_counter`].
+//!
+//! Usage: `cargo run --release --features alloc-counter --bin memory [--csv] [--peak-buffers]`
+//!
+//! With `--csv`, results are written to stdout as CSV so that they can be
+//! pasted into a spreadsheet or diffed between runs. Each row includes the
+//! cryptography library the TLS library was built against, since s2n-tls can
+//! be built against different libcryptos.
+//!
+//! With `--peak-buffers`, allocations aren't measured. Instead, the most data
+//! each connection had waiting in its IO buffer at once during a handshake is
+//! reported for each crypto config, cert, and handshake type. This transient
+//! peak, rather than the buffer size after the handshake, is the buffer size
+//! a connection needs.
+
+use bench::{
+    alloc_counter::{measure, AllocationStats},
+    harness::{ConnectedBuffer, Mode},
+    CertConfig, CipherSuite, CryptoConfig, ECGroup, HandshakeType, RustlsHarness, S2NHarness,
+    TlsBenchHarness,
+};
+use std::{env, error::Error};
+
+const CSV_ARG: &str = "--csv";
+const PEAK_BUFFERS_ARG: &str = "--peak-buffers";
+const CSV_HEADER: &str = "library,crypto_backend,handshake_type,phase,allocations,bytes";
+const PEAK_BUFFERS_CSV_HEADER: &str =
+    "library,crypto_backend,cipher_suite,ec_group,server_cert,handshake_type,client_peak_bytes,server_peak_bytes";
+
+const CRYPTO_CONFIG: CryptoConfig = CryptoConfig {
+    cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+    ec_group: ECGroup::SECP256R1,
+};
+
+/// Allocations made by one connection pair, measured per phase
+struct Measurement {
+    library: &'static str,
+    crypto_backend: String,
+    handshake_type: HandshakeType,
+    init: AllocationStats,
+    handshake: AllocationStats,
+}
+
+/// Get the most data sent by either connection during a handshake, which is
+/// the most that will be unread in an IO buffer
+fn max_handshake_bytes<T: TlsBenchHarness>(
+    handshake_type: HandshakeType,
+) -> Result<usize, Box<dyn Error>> {
+    let mut harness = T::new(&CRYPTO_CONFIG, handshake_type)?;
+    harness.handshake()?;
+    Ok(harness
+        .bytes_sent(Mode::Client)
+        .max(harness.bytes_sent(Mode::Server)))
+}
+
+impl Measurement {
+    /// Measure allocations, where the connections communicate over `buf`,
+    /// which is returned for reuse by the next measurement
+    ///
+    /// `buf` is preallocated and recycled, so neither allocating nor growing
+    /// IO buffers is counted
+    fn new<T: TlsBenchHarness>(
+        library: &'static str,
+        handshake_type: HandshakeType,
+        buf: ConnectedBuffer,
+    ) -> Result<(Self, ConnectedBuffer), Box<dyn Error>> {
+        let (harness, init) = measure(|| T::new_with_buffer(&CRYPTO_CONFIG, handshake_type, buf));
+        let mut harness = harness?;
+        let (result, handshake) = measure(|| harness.handshake());
+        result?;
+        assert!(harness.handshake_completed());
+
+        let measurement = Self {
+            library,
+            crypto_backend: T::crypto_backend(),
+            handshake_type,
+            init,
+            handshake,
+        };
+        Ok((measurement, harness.take_connected_buffer()))
+    }
+
+    fn phases(&self) -> [(&'static str, AllocationStats); 2] {
+        [("init", self.init), ("handshake", self.handshake)]
+    }
+}
+
+/// Report the most data each connection had waiting in its IO buffer during
+/// handshakes with every crypto config, server cert, and handshake type
+fn report_peak_buffers<T: TlsBenchHarness>(library: &str, csv: bool) -> Result<(), Box<dyn Error>> {
+    for cipher_suite in [
+        CipherSuite::AES_128_GCM_SHA256,
+        CipherSuite::AES_256_GCM_SHA384,
+    ] {
+        for ec_group in [ECGroup::SECP256R1, ECGroup::X25519] {
+            let crypto_config = CryptoConfig {
+                cipher_suite: cipher_suite.clone(),
+                ec_group,
+            };
+            if cfg!(feature = "fips") && !crypto_config.is_fips_approved() {
+                continue;
+            }
+            // RSA 4096 certs are the largest the server sends
+            for (server_cert, cert_config) in [
+                ("default", CertConfig::default()),
+                ("rsa-4096", CertConfig::with_rsa_key_size(4096)),
+            ] {
+                for handshake_type in [HandshakeType::ServerAuth, HandshakeType::MutualAuth] {
+                    let mut harness =
+                        T::new_with_certs(&crypto_config, handshake_type, &cert_config)?;
+                    harness.handshake()?;
+                    let (client_peak, server_peak) = (
+                        harness.peak_unread_bytes(Mode::Client),
+                        harness.peak_unread_bytes(Mode::Server),
+                    );
+                    if csv {
+                        println!(
+                            "{library},{},{:?},{:?},{server_cert},{handshake_type:?},{client_peak},{server_peak}",
+                            T::crypto_backend(),
+                            crypto_config.cipher_suite,
+                            crypto_config.ec_group,
+                        );
+                    } else {
+                        println!(
+                            "{library} ({}) {:?} {:?} {server_cert} cert {handshake_type:?}: \
+                            client peak {client_peak} bytes, server peak {server_peak} bytes",
+                            T::crypto_backend(),
+                            crypto_config.cipher_suite,
+                            crypto_config.ec_group,
+                        );
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let csv = args.iter().any(|arg| arg == CSV_ARG);
+
+    if args.iter().any(|arg| arg == PEAK_BUFFERS_ARG) {
+        if csv {
+            println!("{PEAK_BUFFERS_CSV_HEADER}");
+        }
+        report_peak_buffers::<S2NHarness>("s2n-tls", csv)?;
+        return report_peak_buffers::<RustlsHarness>("rustls", csv);
+    }
+
+    // initialize both libraries so that one-time setup isn't counted
+    S2NHarness::default()?.handshake()?;
+    RustlsHarness::default()?.handshake()?;
+
+    let handshake_types = [HandshakeType::ServerAuth, HandshakeType::MutualAuth];
+    // size buffers for the largest handshake of any configuration, so that
+    // buffer growth isn't counted in any measurement
+    let mut buffer_capacity = 0;
+    for handshake_type in handshake_types {
+        buffer_capacity = buffer_capacity
+            .max(max_handshake_bytes::<S2NHarness>(handshake_type)?)
+            .max(max_handshake_bytes::<RustlsHarness>(handshake_type)?);
+    }
+
+    let mut buf = ConnectedBuffer::with_capacity(buffer_capacity);
+    let mut measurements = Vec::new();
+    for handshake_type in handshake_types {
+        let measurement;
+        (measurement, buf) = Measurement::new::<S2NHarness>("s2n-tls", handshake_type, buf)?;
+        measurements.push(measurement);
+        let measurement;
+        (measurement, buf) = Measurement::new::<RustlsHarness>("rustls", handshake_type, buf)?;
+        measurements.push(measurement);
+    }
+
+    if csv {
+        println!("{CSV_HEADER}");
+    }
+    for measurement in &measurements {
+        for (phase, stats) in measurement.phases() {
+            if csv {
+                println!(
+                    "{},{},{:?},{phase},{},{}",
+                    measurement.library,
+                    measurement.crypto_backend,
+                    measurement.handshake_type,
+                    stats.allocations,
+                    stats.bytes
+                );
+            } else {
+                println!(
+                    "{} ({}) {:?} {phase}: {} allocations, {} bytes",
+                    measurement.library,
+                    measurement.crypto_backend,
+                    measurement.handshake_type,
+                    stats.allocations,
+                    stats.bytes
+                );
+            }
+        }
+    }
+    Ok(())
+}
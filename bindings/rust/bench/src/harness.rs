@@ -1,61 +1,485 @@
 // Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
 // SPDX-License-Identifier: Apache-2.0
 
+pub use crate::types::{
+    AlertDescription, CipherSuite, CryptoConfig, ECGroup, HandshakeType, IntermediateOrder, SigType,
+};
+use crate::{
+    CA_CERT_PATH, CLIENT_CERT_PATH, CLIENT_KEY_PATH, SERVER_CERT_CHAIN_PATH, SERVER_KEY_PATH,
+    SERVER_PKCS12_PASSWORD, SERVER_PKCS12_PATH,
+};
+use rustls_pemfile::{certs, pkcs8_private_keys};
 use std::{
     cell::RefCell,
-    collections::VecDeque,
+    collections::{HashMap, VecDeque},
     error::Error,
-    fs::read_to_string,
+    fmt,
+    fs::File,
     io::{ErrorKind, Read, Write},
+    path::Path,
     rc::Rc,
+    sync::{Arc, Mutex, OnceLock},
 };
 
-pub fn read_to_bytes(path: &str) -> Vec<u8> {
-    read_to_string(path).unwrap().into_bytes()
+/// Contents of the files read so far by [`read_to_bytes`], by path
+static FILE_CACHE: OnceLock<Mutex<HashMap<String, Arc<[u8]>>>> = OnceLock::new();
+
+/// Read a file, such as a PEM cert or key or a DER trust store
+///
+/// Each file is only read from disk the first time, and later reads share
+/// the cached contents, so that benches which build configs measure parsing
+/// rather than filesystem IO or copying
+pub fn read_to_bytes(path: &str) -> Arc<[u8]> {
+    FILE_CACHE
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap()
+        .entry(path.to_owned())
+        .or_insert_with(|| std::fs::read(path).unwrap().into())
+        .clone()
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Mode {
     Client,
     Server,
 }
 
-// these parameters were the only ones readily usable for all three libaries:
-// s2n-tls, rustls, and openssl
-#[allow(non_camel_case_types)]
+/// Parameters negotiated by a completed handshake, as seen by the client
+///
+/// Optional fields are `None` if the library doesn't report them, or for `alpn`,
+/// if no protocol was negotiated
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConnectionInfo {
+    pub cipher_suite: CipherSuite,
+    pub tls13: bool,
+    pub ec_group: ECGroup,
+    /// Library-specific name of the signature scheme used by the server
+    pub signature_scheme: Option<String>,
+    pub alpn: Option<Vec<u8>>,
+    pub resumed: bool,
+    /// Number of certs in the server's chain as validated by the client,
+    /// from the leaf up to and including the trust anchor, which the server
+    /// may not have sent
+    pub peer_cert_chain_len: Option<usize>,
+}
+
+/// Bundle of 1000 CA certs used by [`CertConfig::with_large_trust_store`]
+pub const LARGE_TRUST_STORE_PATH: &str = "certs/trust/ca-bundle.pem";
+/// [`LARGE_TRUST_STORE_PATH`] as concatenated DER certs
+pub const LARGE_DER_TRUST_STORE_PATH: &str = "certs/trust/ca-bundle.der";
+
+/// Load the DER-encoded CA certs in the trust store at `path`, which holds
+/// concatenated DER certs if its name ends in `.der`, or PEM certs otherwise
+pub fn load_trust_store(path: &str) -> Result<Vec<Vec<u8>>, Box<dyn Error>> {
+    if path.ends_with(".der") {
+        parse_der_certs(&read_to_bytes(path))
+    } else {
+        parse_pem_certs(&read_to_bytes(path))
+    }
+}
+
+/// Parse all certs in PEM data
+pub fn parse_pem_certs(pem: &[u8]) -> Result<Vec<Vec<u8>>, Box<dyn Error>> {
+    Ok(certs(&mut &pem[..])?)
+}
+
+/// Split concatenated DER certs
+pub fn parse_der_certs(mut der: &[u8]) -> Result<Vec<Vec<u8>>, Box<dyn Error>> {
+    const SEQUENCE: u8 = 0x30;
+    // long form lengths set the high bit and give the number of length bytes
+    const LONG_FORM: u8 = 0x80;
+
+    let mut certs = Vec::new();
+    while !der.is_empty() {
+        if der.len() < 2 || der[0] != SEQUENCE {
+            return Err("invalid DER cert".into());
+        }
+        let (header_len, content_len) = if der[1] & LONG_FORM == 0 {
+            (2, der[1] as usize)
+        } else {
+            let len_bytes = (der[1] & !LONG_FORM) as usize;
+            if len_bytes == 0 || len_bytes > 4 || der.len() < 2 + len_bytes {
+                return Err("invalid DER cert length".into());
+            }
+            let content_len = der[2..2 + len_bytes]
+                .iter()
+                .fold(0, |len, byte| (len << 8) | *byte as usize);
+            (2 + len_bytes, content_len)
+        };
+        let cert_len = header_len + content_len;
+        if der.len() < cert_len {
+            return Err("truncated DER cert".into());
+        }
+        certs.push(der[..cert_len].to_vec());
+        der = &der[cert_len..];
+    }
+    Ok(certs)
+}
+
+/// PEM-encode DER certs, for libraries that only load PEM
+pub fn certs_to_pem(certs: &[Vec<u8>]) -> Vec<u8> {
+    certs
+        .iter()
+        .flat_map(|cert| pem_encode("CERTIFICATE", cert))
+        .collect()
+}
+
+/// Paths to PEM files used to authenticate the server, and the client if
+/// the handshake uses client auth
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub enum CipherSuite {
-    AES_128_GCM_SHA256,
-    AES_256_GCM_SHA384,
+pub struct CertConfig {
+    pub server_cert_chain: String,
+    pub server_key: String,
+    pub ca_cert: String,
+    /// Path and password of a PKCS#12 bundle to load the server cert chain
+    /// and key from, instead of `server_cert_chain` and `server_key`
+    pub server_pkcs12: Option<(String, String)>,
+    pub client_cert: String,
+    pub client_key: String,
+    /// CA trusted by the server to verify client certs
+    pub client_ca_cert: String,
+}
+
+impl Default for CertConfig {
+    fn default() -> Self {
+        CertConfig {
+            server_cert_chain: SERVER_CERT_CHAIN_PATH.into(),
+            server_key: SERVER_KEY_PATH.into(),
+            ca_cert: CA_CERT_PATH.into(),
+            server_pkcs12: None,
+            client_cert: CLIENT_CERT_PATH.into(),
+            client_key: CLIENT_KEY_PATH.into(),
+            client_ca_cert: CA_CERT_PATH.into(),
+        }
+    }
 }
 
+/// DER-encoded server cert chain and PKCS#8 private key
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub enum ECGroup {
-    SECP256R1,
-    X25519,
+pub struct ServerIdentity {
+    pub cert_chain: Vec<Vec<u8>>,
+    pub key: Vec<u8>,
 }
 
-#[derive(Debug, PartialEq, Eq)]
-pub struct CryptoConfig {
-    pub cipher_suite: CipherSuite,
-    pub ec_group: ECGroup,
+impl ServerIdentity {
+    /// Load the server identity from `cert_config`, from either its PKCS#12
+    /// bundle or its PEM files
+    pub fn load(cert_config: &CertConfig) -> Result<Self, Box<dyn Error>> {
+        match &cert_config.server_pkcs12 {
+            Some((path, password)) => Self::from_pkcs12(&read_to_bytes(path), password),
+            None => Self::from_pem(
+                &read_to_bytes(&cert_config.server_cert_chain),
+                &read_to_bytes(&cert_config.server_key),
+            ),
+        }
+    }
+
+    /// Parse a PKCS#12 bundle, verifying its MAC and decrypting it with
+    /// `password`
+    pub fn from_pkcs12(bundle: &[u8], password: &str) -> Result<Self, Box<dyn Error>> {
+        let pfx = p12::PFX::parse(bundle)?;
+        if !pfx.verify_mac(password) {
+            return Err("PKCS#12 MAC verification failed".into());
+        }
+        let cert_chain = pfx.cert_x509_bags(password)?;
+        let key = pfx
+            .key_bags(password)?
+            .into_iter()
+            .next()
+            .ok_or("PKCS#12 bundle has no private key")?;
+        Ok(Self { cert_chain, key })
+    }
+
+    /// Parse a PEM cert chain and PEM PKCS#8 private key
+    pub fn from_pem(cert_chain: &[u8], key: &[u8]) -> Result<Self, Box<dyn Error>> {
+        let cert_chain = certs(&mut &cert_chain[..])?;
+        let key = pkcs8_private_keys(&mut &key[..])?
+            .into_iter()
+            .next()
+            .ok_or("no PKCS#8 private key")?;
+        Ok(Self { cert_chain, key })
+    }
+
+    /// PEM-encoded cert chain and private key, for libraries that only load
+    /// PEM
+    pub fn to_pem(&self) -> (Vec<u8>, Vec<u8>) {
+        (
+            certs_to_pem(&self.cert_chain),
+            pem_encode("PRIVATE KEY", &self.key),
+        )
+    }
+}
+
+fn pem_encode(label: &str, der: &[u8]) -> Vec<u8> {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    // PEM lines hold 64 base64 characters, which encode 48 bytes
+    const LINE_BYTES: usize = 48;
+
+    let mut pem = format!("-----BEGIN {label}-----\n").into_bytes();
+    for line in der.chunks(LINE_BYTES) {
+        for chunk in line.chunks(3) {
+            let bits = chunk.iter().enumerate().fold(0u32, |bits, (i, byte)| {
+                bits | ((*byte as u32) << (16 - 8 * i))
+            });
+            for i in 0..4 {
+                // pad when the chunk has fewer than 3 bytes
+                if i <= chunk.len() {
+                    pem.push(ALPHABET[(bits >> (18 - 6 * i)) as usize & 0x3f]);
+                } else {
+                    pem.push(b'=');
+                }
+            }
+        }
+        pem.push(b'\n');
+    }
+    pem.extend_from_slice(format!("-----END {label}-----\n").as_bytes());
+    pem
+}
+
+impl CertConfig {
+    /// Server cert with `count` subject alternative names, where only the
+    /// last name matches "localhost"
+    ///
+    /// Generated by `certs/generate_san_certs.sh` for counts 1, 10, 100, and 1000
+    pub fn with_san_count(count: usize) -> Self {
+        CertConfig {
+            server_cert_chain: format!("certs/sans/server-{count}-fullchain.pem"),
+            server_key: format!("certs/sans/server-{count}-key.pem"),
+            ca_cert: "certs/sans/ca-cert.pem".into(),
+            ..Default::default()
+        }
+    }
+
+    /// Default certs, where clients trust a bundle of 1000 CAs that ends
+    /// with the CA that issued the server cert
+    ///
+    /// Generated by `certs/generate_trust_certs.sh`
+    pub fn with_large_trust_store() -> Self {
+        CertConfig {
+            ca_cert: LARGE_TRUST_STORE_PATH.into(),
+            ..Default::default()
+        }
+    }
+
+    /// [`Self::with_large_trust_store`], with the bundle loaded from
+    /// concatenated DER certs instead of PEM
+    pub fn with_large_der_trust_store() -> Self {
+        CertConfig {
+            ca_cert: LARGE_DER_TRUST_STORE_PATH.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Default server cert chain and key, bundled as a password-protected
+    /// PKCS#12 file by `certs/generate_certs.sh`
+    pub fn with_pkcs12() -> Self {
+        CertConfig {
+            server_pkcs12: Some((SERVER_PKCS12_PATH.into(), SERVER_PKCS12_PASSWORD.into())),
+            ..Default::default()
+        }
+    }
+
+    /// Server cert issued through two intermediate CAs, sent with the
+    /// intermediates in `order`
+    ///
+    /// Generated by `certs/generate_chain_certs.sh`
+    pub fn with_intermediates(order: IntermediateOrder) -> Self {
+        let chain = Self::intermediate_order_name(order);
+        CertConfig {
+            server_cert_chain: format!("certs/chain/{chain}-fullchain.pem"),
+            server_key: "certs/chain/server-key.pem".into(),
+            ca_cert: "certs/chain/ca-cert.pem".into(),
+            ..Default::default()
+        }
+    }
+
+    /// Server cert chain of `len` certs, where the server cert is issued
+    /// through `len - 1` intermediate CAs, sent with the intermediates in
+    /// `order`
+    ///
+    /// The root CA isn't sent. Generated by
+    /// `certs/generate_long_chain_certs.sh` for lengths 2 to 6, which are
+    /// within the default chain length limits of both libraries.
+    pub fn with_chain_len(len: usize, order: IntermediateOrder) -> Self {
+        let chain = Self::intermediate_order_name(order);
+        CertConfig {
+            server_cert_chain: format!("certs/long-chain/server-{len}-{chain}-fullchain.pem"),
+            server_key: "certs/long-chain/server-key.pem".into(),
+            ca_cert: "certs/long-chain/ca-cert.pem".into(),
+            ..Default::default()
+        }
+    }
+
+    fn intermediate_order_name(order: IntermediateOrder) -> &'static str {
+        match order {
+            IntermediateOrder::Ordered => "ordered",
+            IntermediateOrder::Reversed => "reversed",
+            IntermediateOrder::Missing => "missing",
+        }
+    }
+
+    /// RSA server cert with a `bits` bit key
+    ///
+    /// Generated by `certs/generate_rsa_size_certs.sh` for 2048, 3072, and
+    /// 4096 bit keys
+    pub fn with_rsa_key_size(bits: u32) -> Self {
+        CertConfig {
+            server_cert_chain: format!("certs/rsa-size/server-{bits}-fullchain.pem"),
+            server_key: format!("certs/rsa-size/server-{bits}-key.pem"),
+            ca_cert: "certs/rsa-size/ca-cert.pem".into(),
+            ..Default::default()
+        }
+    }
+
+    /// Server cert with a `leaf` key, issued through an intermediate with
+    /// the same key type as the `ca` root
+    ///
+    /// Generated by `certs/generate_mixed_chain_certs.sh`
+    pub fn with_chain_sig_types(ca: SigType, leaf: SigType) -> Self {
+        let (ca, leaf) = (Self::sig_type_name(ca), Self::sig_type_name(leaf));
+        CertConfig {
+            server_cert_chain: format!("certs/mixed-chain/{ca}-ca-{leaf}-fullchain.pem"),
+            server_key: format!("certs/mixed-chain/{ca}-ca-{leaf}-key.pem"),
+            ca_cert: format!("certs/mixed-chain/{ca}-ca-cert.pem"),
+            ..Default::default()
+        }
+    }
+
+    /// Server and client certs with independently chosen key types, all
+    /// issued by one CA
+    ///
+    /// Generated by `certs/generate_sig_certs.sh`
+    pub fn with_sig_types(server: SigType, client: SigType) -> Self {
+        let (server, client) = (Self::sig_type_name(server), Self::sig_type_name(client));
+        CertConfig {
+            server_cert_chain: format!("certs/sig/server-{server}-fullchain.pem"),
+            server_key: format!("certs/sig/server-{server}-key.pem"),
+            ca_cert: "certs/sig/ca-cert.pem".into(),
+            server_pkcs12: None,
+            client_cert: format!("certs/sig/client-{client}-cert.pem"),
+            client_key: format!("certs/sig/client-{client}-key.pem"),
+            client_ca_cert: "certs/sig/ca-cert.pem".into(),
+        }
+    }
+
+    fn sig_type_name(sig_type: SigType) -> &'static str {
+        match sig_type {
+            SigType::Ecdsa => "ecdsa",
+            SigType::Rsa => "rsa",
+        }
+    }
 }
 
 pub trait TlsBenchHarness: Sized {
     /// Default harness
     fn default() -> Result<Self, Box<dyn Error>> {
-        Self::new(&CryptoConfig {
-            cipher_suite: CipherSuite::AES_128_GCM_SHA256,
-            ec_group: ECGroup::SECP256R1,
-        })
+        Self::new(
+            &CryptoConfig {
+                cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+                ec_group: ECGroup::SECP256R1,
+            },
+            HandshakeType::ServerAuth,
+        )
     }
 
     /// Initialize buffers, configs, and connections (pre-handshake)
-    fn new(crypto_config: &CryptoConfig) -> Result<Self, Box<dyn Error>>;
+    fn new(
+        crypto_config: &CryptoConfig,
+        handshake_type: HandshakeType,
+    ) -> Result<Self, Box<dyn Error>> {
+        Self::new_with_certs(crypto_config, handshake_type, &CertConfig::default())
+    }
+
+    /// Initialize buffers, configs, and connections where the server uses the
+    /// certs in `cert_config` (pre-handshake)
+    fn new_with_certs(
+        crypto_config: &CryptoConfig,
+        handshake_type: HandshakeType,
+        cert_config: &CertConfig,
+    ) -> Result<Self, Box<dyn Error>>;
+
+    /// Initialize buffers, configs, and connections where the server issues
+    /// session tickets, so the session can be resumed with
+    /// [`Self::new_resumed`] after the handshake (pre-handshake)
+    ///
+    /// Servers from other constructors may not issue tickets, so that full
+    /// handshakes aren't measured with a ticket nothing resumes.
+    fn new_with_session_tickets(
+        crypto_config: &CryptoConfig,
+        handshake_type: HandshakeType,
+    ) -> Result<Self, Box<dyn Error>>;
+
+    /// Initialize configs and connections communicating over `client_buf`
+    /// and its inverse (pre-handshake)
+    fn new_with_buffer(
+        crypto_config: &CryptoConfig,
+        handshake_type: HandshakeType,
+        client_buf: ConnectedBuffer,
+    ) -> Result<Self, Box<dyn Error>>;
+
+    /// Initialize connections where the client offers `client_protocols`
+    /// and the server supports `server_protocols` with ALPN, each in order
+    /// of preference (pre-handshake)
+    ///
+    /// Both libraries select the server's most preferred protocol that the
+    /// client offers
+    fn new_with_alpn(
+        crypto_config: &CryptoConfig,
+        client_protocols: &[&[u8]],
+        server_protocols: &[&[u8]],
+    ) -> Result<Self, Box<dyn Error>>;
+
+    /// Initialize connections that log the secrets they derive to
+    /// `client_log` and `server_log` (pre-handshake)
+    ///
+    /// The logged secrets depend on the whole key schedule, so comparing
+    /// them between the two connections checks that both derived the same
+    /// keys with the same PRF
+    fn new_with_key_logs(
+        crypto_config: &CryptoConfig,
+        handshake_type: HandshakeType,
+        client_log: &KeyLog,
+        server_log: &KeyLog,
+    ) -> Result<Self, Box<dyn Error>>;
+
+    /// Close the connections and get back the buffer passed to
+    /// [`Self::new_with_buffer`], recycled with [`ConnectedBuffer::recycle`]
+    /// so that its allocated capacity can be reused by another harness
+    fn take_connected_buffer(self) -> ConnectedBuffer;
 
     /// Run handshake on initialized connection
     /// Returns error if handshake has already completed
     fn handshake(&mut self) -> Result<(), Box<dyn Error>>;
 
+    /// Advance the handshake of one connection only, processing any data
+    /// sent by its peer and sending its response, so the handshake can be
+    /// inspected between messages
+    fn handshake_step(&mut self, mode: Mode) -> Result<(), Box<dyn Error>>;
+
+    /// Get the number of handshake steps taken so far by connections that
+    /// hadn't yet completed their handshake
+    ///
+    /// Each call to [`Self::handshake_step`] is one step, and
+    /// [`Self::handshake`] takes up to four. Steps by a connection that has
+    /// already completed its handshake aren't counted, so the count is the
+    /// number of client/server alternations the handshake needed.
+    fn handshake_step_count(&self) -> usize;
+
+    /// Run handshake steps until the handshake completes
+    /// Returns [`HandshakeTimeout`] if the handshake hasn't completed after
+    /// `max_steps` calls to [`Self::handshake`], so a stalled handshake fails
+    /// instead of hanging
+    fn handshake_with_timeout(&mut self, max_steps: usize) -> Result<(), Box<dyn Error>> {
+        for _ in 0..max_steps {
+            self.handshake()?;
+            if self.handshake_completed() {
+                return Ok(());
+            }
+        }
+        Err(HandshakeTimeout { max_steps }.into())
+    }
+
     /// Checks if handshake is finished for both client and server
     fn handshake_completed(&self) -> bool;
 
@@ -64,14 +488,181 @@ pub trait TlsBenchHarness: Sized {
 
     /// Get whether or negotiated version is TLS1.3
     fn negotiated_tls13(&self) -> bool;
+
+    /// Get all negotiated parameters after the handshake completes
+    fn connection_info(&self) -> ConnectionInfo;
+
+    /// Get whether the server sent a HelloRetryRequest, because the client
+    /// didn't send a key share for any group the server supports
+    fn hello_retry_requested(&self) -> bool;
+
+    /// Get the name and version of the cryptography library that the TLS
+    /// library is linked against, so that results from builds against
+    /// different libraries aren't conflated
+    fn crypto_backend() -> String;
+
+    /// Send application data from one connection to the other
+    /// Must be called after handshake has completed
+    fn send(&mut self, sender: Mode, data: &[u8]) -> Result<(), Box<dyn Error>>;
+
+    /// Receive application data sent to one connection, filling `data`
+    /// Returns error if not enough data has been sent
+    fn recv(&mut self, receiver: Mode, data: &mut [u8]) -> Result<(), Box<dyn Error>>;
+
+    /// Gracefully close both connections, the client first, so that each
+    /// sends a close_notify alert and receives the peer's
+    fn shutdown(&mut self) -> Result<(), Box<dyn Error>>;
+
+    /// Get whether the handshake resumed a previous session, rather than
+    /// silently falling back to a full handshake
+    fn was_resumed(&self) -> bool;
+
+    /// Initialize connections that resume the session of this harness, using
+    /// the same configs (pre-handshake)
+    /// Returns error if the client has not yet received a session ticket
+    fn new_resumed(&self) -> Result<Self, Box<dyn Error>>;
+
+    /// Initialize new connections that share the configs of this harness,
+    /// like a server accepting another connection
+    ///
+    /// Clients that have already stored a session ticket in a shared config
+    /// may resume it, so harnesses should be initialized before handshaking
+    /// to get identical full handshakes.
+    fn new_with_same_configs(&self) -> Result<Self, Box<dyn Error>>;
+
+    /// Get number of round trips started by the client so far
+    fn round_trips(&self) -> usize;
+
+    /// Get number of bytes written to the wire by one connection so far,
+    /// including record headers
+    fn bytes_sent(&self, sender: Mode) -> usize;
+
+    /// Get number of TLS records written to the wire by one connection so
+    /// far, which is the number of handshake records if called right after
+    /// the handshake
+    fn records_sent(&self, sender: Mode) -> usize;
+
+    /// Get the most bytes sent by one connection that were waiting to be
+    /// read by its peer at once, which is the IO buffer size the connection
+    /// needs
+    fn peak_unread_bytes(&self, sender: Mode) -> usize;
+
+    /// Get the raw bytes sent to one connection that it hasn't read yet,
+    /// without consuming them
+    fn pending_data(&self, receiver: Mode) -> Vec<u8>;
+
+    /// Discard the raw bytes sent to one connection that it hasn't read yet
+    ///
+    /// The connection can't decrypt later records after records are discarded
+    fn discard_pending_data(&mut self, receiver: Mode);
+
+    /// Get whether every raw byte sent by either connection has been read by
+    /// its peer
+    ///
+    /// After a TLS1.3 handshake, the client may not have read the session
+    /// tickets sent by the server yet. They're read along with the next
+    /// application data the server sends.
+    fn is_drained(&self) -> bool;
+
+    /// Add raw bytes to the data sent to one connection, after any it hasn't
+    /// read yet, to simulate a peer sending arbitrary bytes
+    fn inject_pending_data(&mut self, receiver: Mode, data: &[u8]);
+
+    /// Flip the lowest bit of the byte at `index` in the raw data sent to
+    /// one connection that it hasn't read yet, to simulate corruption or
+    /// tampering in transit
+    fn corrupt_pending_data(&mut self, receiver: Mode, index: usize);
+
+    /// Get the alert for the most recent handshake or receive failure,
+    /// either received from the peer or corresponding to the local error,
+    /// if any
+    fn last_alert(&self) -> Option<AlertDescription>;
+}
+
+/// Error returned by [`TlsBenchHarness::handshake_with_timeout`] when the
+/// handshake doesn't complete within the allowed number of steps
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HandshakeTimeout {
+    pub max_steps: usize,
+}
+
+impl fmt::Display for HandshakeTimeout {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "handshake not completed after {} steps", self.max_steps)
+    }
+}
+
+impl Error for HandshakeTimeout {}
+
+/// Secrets logged by one connection, as lines of the NSS key log format
+/// (`<label> <client random> <secret>`, hex-encoded)
+///
+/// Clones share the same lines, so a clone can be handed to a library
+/// callback and read back after the handshake.
+#[derive(Debug, Clone, Default)]
+pub struct KeyLog(Arc<Mutex<Vec<String>>>);
+
+impl KeyLog {
+    pub fn log_line(&self, line: String) {
+        self.0.lock().unwrap().push(line);
+    }
+
+    pub fn lines(&self) -> Vec<String> {
+        self.0.lock().unwrap().clone()
+    }
+
+    /// Get the hex-encoded secret logged with `label`, if any
+    pub fn secret(&self, label: &str) -> Option<String> {
+        self.0.lock().unwrap().iter().find_map(|line| {
+            let mut fields = line.trim().split(' ');
+            if fields.next() != Some(label) {
+                return None;
+            }
+            fields.nth(1).map(str::to_owned)
+        })
+    }
 }
 
+/// Upper bound on the length of each read from
+/// [`ConnectedBuffer::with_fragmented_reads`], small enough to split most
+/// records across several reads
+pub const FRAGMENTED_READ_MAX_LEN: usize = 64;
+
 /// Wrapper of two shared buffers to pass as stream
 /// This wrapper `read()`s into one buffer and `write()`s to another
 #[derive(Clone)]
 pub struct ConnectedBuffer {
     recv: Rc<RefCell<VecDeque<u8>>>,
     send: Rc<RefCell<VecDeque<u8>>>,
+    capacity: Option<usize>,
+    max_read_len: Option<usize>,
+    // whether every other read returns WouldBlock, and whether the next one will
+    block_between_reads: bool,
+    block_next_read: bool,
+    // state of the generator for pseudo-random read lengths
+    read_len_rng: Option<u64>,
+    sent_flights: usize,
+    read_since_write: bool,
+    sent_bytes: usize,
+    // number of records written, and the state of parsing the written stream
+    // into records: a partially written record header, or the number of
+    // bytes left in the current record
+    sent_records: usize,
+    partial_record_header: Vec<u8>,
+    record_remaining: usize,
+    // most data written through this struct that was unread at once
+    peak_unread: usize,
+    read_calls: usize,
+    write_calls: usize,
+    // data written while flushing is deferred, which the peer can't read
+    // until the next flush
+    deferred_flush: bool,
+    unflushed: Vec<u8>,
+    flush_calls: usize,
+    write_interceptor: Option<fn(&mut [u8])>,
+    // transcript file shared with the inverse struct, and which side of the
+    // connection this struct is
+    transcript: Option<(Rc<RefCell<File>>, Mode)>,
 }
 
 impl ConnectedBuffer {
@@ -80,24 +671,378 @@ impl ConnectedBuffer {
         ConnectedBuffer {
             recv: Rc::new(RefCell::new(VecDeque::new())),
             send: Rc::new(RefCell::new(VecDeque::new())),
+            capacity: None,
+            max_read_len: None,
+            block_between_reads: false,
+            block_next_read: false,
+            read_len_rng: None,
+            sent_flights: 0,
+            read_since_write: true,
+            sent_bytes: 0,
+            sent_records: 0,
+            partial_record_header: Vec::new(),
+            record_remaining: 0,
+            peak_unread: 0,
+            read_calls: 0,
+            write_calls: 0,
+            deferred_flush: false,
+            unflushed: Vec::new(),
+            flush_calls: 0,
+            write_interceptor: None,
+            transcript: None,
+        }
+    }
+
+    /// Make a new struct with new internal buffers that are each preallocated
+    /// to hold `capacity` bytes, so that they don't reallocate while less
+    /// than `capacity` bytes are unread
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacities(capacity, capacity)
+    }
+
+    /// Make a new struct with new internal buffers preallocated to hold
+    /// `send_capacity` bytes written through this struct and `recv_capacity`
+    /// bytes written through the inverse struct, for workloads that send
+    /// much more data in one direction
+    pub fn with_capacities(send_capacity: usize, recv_capacity: usize) -> Self {
+        ConnectedBuffer {
+            recv: Rc::new(RefCell::new(VecDeque::with_capacity(recv_capacity))),
+            send: Rc::new(RefCell::new(VecDeque::with_capacity(send_capacity))),
+            ..Self::new()
+        }
+    }
+
+    /// Make a new struct with new internal buffers that each hold at most
+    /// `capacity` bytes; writes to a full buffer return WouldBlock
+    pub fn bounded(capacity: usize) -> Self {
+        ConnectedBuffer {
+            capacity: Some(capacity),
+            ..Self::new()
+        }
+    }
+
+    /// Make a new struct with new internal buffers where each `read()`
+    /// returns at most `max_read_len` bytes, to simulate data arriving in
+    /// small chunks
+    pub fn with_max_read_len(max_read_len: usize) -> Self {
+        ConnectedBuffer {
+            max_read_len: Some(max_read_len),
+            ..Self::new()
+        }
+    }
+
+    /// Make a new struct with new internal buffers where at most `chunk_len`
+    /// bytes arrive at a time: each `read()` that returns data is followed by
+    /// one that returns WouldBlock, as if the reader had to wait for the next
+    /// chunk
+    pub fn with_chunked_arrival(chunk_len: usize) -> Self {
+        ConnectedBuffer {
+            max_read_len: Some(chunk_len),
+            block_between_reads: true,
+            ..Self::new()
+        }
+    }
+
+    /// Make a new struct with new internal buffers where each `read()`
+    /// returns a pseudo-random number of bytes, between 1 and
+    /// [`FRAGMENTED_READ_MAX_LEN`], to simulate fragmented TCP reads
+    ///
+    /// The read lengths are determined by `seed`, so failures can be
+    /// reproduced
+    pub fn with_fragmented_reads(seed: u64) -> Self {
+        ConnectedBuffer {
+            // xorshift state must be nonzero
+            read_len_rng: Some(seed | 1),
+            ..Self::new()
+        }
+    }
+
+    /// Make a new struct with new internal buffers where all data written
+    /// through this struct is modified by `interceptor` before the peer can
+    /// read it, to simulate an attacker in the middle
+    ///
+    /// The inverse struct's writes are not intercepted
+    pub fn with_write_interceptor(interceptor: fn(&mut [u8])) -> Self {
+        ConnectedBuffer {
+            write_interceptor: Some(interceptor),
+            ..Self::new()
         }
     }
+
+    /// Make a new struct with new internal buffers where all data written
+    /// through this struct or its inverse is also appended to the file at
+    /// `path`, to capture the transcript of a connection
+    ///
+    /// This struct is the client and the inverse struct is the server. See
+    /// [`read_transcript`] for the file format.
+    pub fn recording(path: &Path) -> Result<Self, std::io::Error> {
+        Ok(ConnectedBuffer {
+            transcript: Some((Rc::new(RefCell::new(File::create(path)?)), Mode::Client)),
+            ..Self::new()
+        })
+    }
+
     /// Make a new struct that shares internal buffers but swapped, ex.
     /// `write()` writes to the buffer that the inverse `read()`s from
     pub fn clone_inverse(&self) -> Self {
         ConnectedBuffer {
             recv: Rc::clone(&self.send),
             send: Rc::clone(&self.recv),
+            capacity: self.capacity,
+            max_read_len: self.max_read_len,
+            block_between_reads: self.block_between_reads,
+            block_next_read: false,
+            // the inverse struct gets a different but still deterministic
+            // sequence of read lengths
+            read_len_rng: self
+                .read_len_rng
+                .map(|state| (state ^ 0x9e37_79b9_7f4a_7c15) | 1),
+            sent_flights: 0,
+            read_since_write: true,
+            sent_bytes: 0,
+            sent_records: 0,
+            partial_record_header: Vec::new(),
+            record_remaining: 0,
+            peak_unread: 0,
+            read_calls: 0,
+            write_calls: 0,
+            deferred_flush: false,
+            unflushed: Vec::new(),
+            flush_calls: 0,
+            write_interceptor: None,
+            transcript: self
+                .transcript
+                .as_ref()
+                .map(|(file, _)| (Rc::clone(file), Mode::Server)),
+        }
+    }
+
+    /// Clear the internal buffers and reset this struct's counters, keeping
+    /// the buffers' allocated capacity
+    ///
+    /// The inverse struct shares the buffers but has its own counters, so it
+    /// should be reset too
+    pub fn reset(&mut self) {
+        self.recv.borrow_mut().clear();
+        self.send.borrow_mut().clear();
+        self.block_next_read = false;
+        self.sent_flights = 0;
+        self.read_since_write = true;
+        self.sent_bytes = 0;
+        self.sent_records = 0;
+        self.partial_record_header.clear();
+        self.record_remaining = 0;
+        self.peak_unread = 0;
+        self.read_calls = 0;
+        self.write_calls = 0;
+        self.unflushed.clear();
+        self.flush_calls = 0;
+    }
+
+    /// Clear the internal buffers and reset all counters, keeping the
+    /// buffers' allocated capacity, so this struct can be used for a new
+    /// connection
+    ///
+    /// Panics if the inverse struct or another clone still exists. Recording
+    /// stops, but other options are kept.
+    pub fn recycle(mut self) -> Self {
+        assert!(
+            Rc::strong_count(&self.recv) == 1 && Rc::strong_count(&self.send) == 1,
+            "buffers are still shared"
+        );
+        self.reset();
+        self.transcript = None;
+        self
+    }
+
+    /// Number of flights written through this buffer
+    /// A new flight starts on the first write after reading from the peer,
+    /// so for the client this is the number of round trips
+    pub fn sent_flights(&self) -> usize {
+        self.sent_flights
+    }
+
+    /// Copy of the data written by the inverse struct that hasn't been read
+    /// through this struct yet
+    pub fn peek_unread(&self) -> Vec<u8> {
+        self.recv.borrow().iter().copied().collect()
+    }
+
+    /// Call `f` with the data written by the inverse struct that hasn't been
+    /// read through this struct yet, without copying it
+    pub fn inspect_unread<R>(&self, f: impl FnOnce(&[u8]) -> R) -> R {
+        f(self.recv.borrow_mut().make_contiguous())
+    }
+
+    /// Discard the data written by the inverse struct that hasn't been read
+    /// through this struct yet
+    pub fn discard_unread(&self) {
+        self.recv.borrow_mut().clear();
+    }
+
+    /// Add data to be read through this struct after any data already
+    /// written by the inverse struct, to simulate a peer sending arbitrary
+    /// bytes
+    pub fn inject(&self, data: &[u8]) {
+        self.recv.borrow_mut().extend(data);
+    }
+
+    /// Get whether all data written by this struct and the inverse struct,
+    /// including any held back until the next flush, has been read
+    ///
+    /// Only data held back by this struct is checked, so a flush deferred by
+    /// the inverse struct should be checked on the inverse struct.
+    pub fn is_drained(&self) -> bool {
+        self.recv.borrow().is_empty() && self.send.borrow().is_empty() && self.unflushed.is_empty()
+    }
+
+    /// Flip the lowest bit of the byte at `index` in the data written by the
+    /// inverse struct that hasn't been read through this struct yet
+    pub fn corrupt_unread(&self, index: usize) {
+        self.recv.borrow_mut()[index] ^= 1;
+    }
+
+    /// Number of bytes written through this buffer
+    pub fn sent_bytes(&self) -> usize {
+        self.sent_bytes
+    }
+
+    /// Number of TLS records written through this buffer, counting each
+    /// record once its header has been written
+    pub fn sent_records(&self) -> usize {
+        self.sent_records
+    }
+
+    /// Most bytes written through this buffer that were waiting to be read
+    /// by the inverse struct at once, which is the most memory the IO buffer
+    /// needed
+    pub fn peak_unread(&self) -> usize {
+        self.peak_unread
+    }
+
+    /// Number of calls to `read()`, including calls that would block
+    pub fn read_calls(&self) -> usize {
+        self.read_calls
+    }
+
+    /// Number of calls to `write()`, including calls that would block
+    pub fn write_calls(&self) -> usize {
+        self.write_calls
+    }
+
+    /// Hold back data written through this struct until the next `flush()`,
+    /// like a socket that buffers writes, or pass it straight to the peer
+    ///
+    /// Turning deferral off flushes any data held back.
+    pub fn set_deferred_flush(&mut self, deferred: bool) {
+        self.deferred_flush = deferred;
+        if !deferred {
+            self.flush().unwrap();
         }
     }
+
+    /// Number of calls to `flush()` that made held back data readable by
+    /// the peer
+    pub fn flush_calls(&self) -> usize {
+        self.flush_calls
+    }
+
+    /// Parse data sent to the peer into records, which may be split across
+    /// writes, to count them
+    fn count_records(&mut self, mut data: &[u8]) {
+        const RECORD_HEADER_LEN: usize = 5;
+
+        while !data.is_empty() {
+            if self.record_remaining > 0 {
+                let len = self.record_remaining.min(data.len());
+                self.record_remaining -= len;
+                data = &data[len..];
+                continue;
+            }
+
+            let len = (RECORD_HEADER_LEN - self.partial_record_header.len()).min(data.len());
+            self.partial_record_header.extend_from_slice(&data[..len]);
+            data = &data[len..];
+            if let [_, _, _, len_high, len_low] = self.partial_record_header[..] {
+                self.sent_records += 1;
+                self.record_remaining = u16::from_be_bytes([len_high, len_low]) as usize;
+                self.partial_record_header.clear();
+            }
+        }
+    }
+
+    /// Append data sent to the peer to the transcript, if recording
+    fn record(&self, data: &[u8]) -> Result<(), std::io::Error> {
+        if let Some((file, sender)) = &self.transcript {
+            let mut file = file.borrow_mut();
+            file.write_all(&[transcript_tag(*sender)])?;
+            file.write_all(&(data.len() as u32).to_be_bytes())?;
+            file.write_all(data)?;
+        }
+        Ok(())
+    }
+}
+
+fn transcript_tag(sender: Mode) -> u8 {
+    match sender {
+        Mode::Client => b'C',
+        Mode::Server => b'S',
+    }
+}
+
+/// Read a transcript recorded by [`ConnectedBuffer::recording`], returning
+/// each write in order along with which side sent it
+///
+/// Each write is stored as a one byte tag of 'C' or 'S' for the sender, the
+/// big-endian u32 length of the data, and then the data itself
+pub fn read_transcript(path: &Path) -> Result<Vec<(Mode, Vec<u8>)>, Box<dyn Error>> {
+    let mut file = File::open(path)?;
+    let mut transcript = Vec::new();
+    let mut header = [0; 5];
+    loop {
+        match file.read_exact(&mut header) {
+            Ok(()) => {}
+            Err(err) if err.kind() == ErrorKind::UnexpectedEof => return Ok(transcript),
+            Err(err) => return Err(err.into()),
+        }
+        let sender = match header[0] {
+            b'C' => Mode::Client,
+            b'S' => Mode::Server,
+            _ => return Err("invalid transcript tag".into()),
+        };
+        let len = u32::from_be_bytes([header[1], header[2], header[3], header[4]]) as usize;
+        let mut data = vec![0; len];
+        file.read_exact(&mut data)?;
+        transcript.push((sender, data));
+    }
 }
 
 impl Read for ConnectedBuffer {
     fn read(&mut self, dest: &mut [u8]) -> Result<usize, std::io::Error> {
-        match self.recv.borrow_mut().read(dest) {
+        self.read_calls += 1;
+        if self.block_next_read {
+            self.block_next_read = false;
+            return Err(std::io::Error::new(ErrorKind::WouldBlock, "waiting"));
+        }
+        let mut len = match self.max_read_len {
+            Some(max_read_len) => dest.len().min(max_read_len),
+            None => dest.len(),
+        };
+        if let Some(state) = &mut self.read_len_rng {
+            // xorshift64
+            *state ^= *state << 13;
+            *state ^= *state >> 7;
+            *state ^= *state << 17;
+            len = len.min(1 + (*state % FRAGMENTED_READ_MAX_LEN as u64) as usize);
+        }
+        match self.recv.borrow_mut().read(&mut dest[..len]) {
             // rustls expects WouldBlock on read of length 0
             Ok(0) => Err(std::io::Error::new(ErrorKind::WouldBlock, "blocking")),
-            Ok(len) => Ok(len),
+            Ok(len) => {
+                self.read_since_write = true;
+                self.block_next_read = self.block_between_reads;
+                Ok(len)
+            }
             Err(err) => Err(err),
         }
     }
@@ -105,53 +1050,1371 @@ impl Read for ConnectedBuffer {
 
 impl Write for ConnectedBuffer {
     fn write(&mut self, src: &[u8]) -> Result<usize, std::io::Error> {
-        self.send.borrow_mut().write(src)
+        self.write_calls += 1;
+        let len = match self.capacity {
+            Some(capacity) => src
+                .len()
+                .min(capacity.saturating_sub(self.send.borrow().len() + self.unflushed.len())),
+            None => src.len(),
+        };
+        if len == 0 && !src.is_empty() {
+            return Err(std::io::Error::new(ErrorKind::WouldBlock, "buffer full"));
+        }
+
+        if self.read_since_write && len > 0 {
+            self.sent_flights += 1;
+            self.read_since_write = false;
+        }
+        self.sent_bytes += len;
+        let mut intercepted = Vec::new();
+        let data = match self.write_interceptor {
+            Some(interceptor) => {
+                intercepted.extend_from_slice(&src[..len]);
+                interceptor(&mut intercepted);
+                &intercepted[..]
+            }
+            None => &src[..len],
+        };
+        self.record(data)?;
+        self.count_records(data);
+        if self.deferred_flush {
+            self.unflushed.extend_from_slice(data);
+            Ok(len)
+        } else {
+            let mut send = self.send.borrow_mut();
+            let written = send.write(data)?;
+            self.peak_unread = self.peak_unread.max(send.len());
+            Ok(written)
+        }
     }
     fn flush(&mut self) -> Result<(), std::io::Error> {
-        Ok(()) // data already available to destination
+        // data is already available to destination unless flushing is deferred
+        if !self.unflushed.is_empty() {
+            self.flush_calls += 1;
+            let mut send = self.send.borrow_mut();
+            send.extend(self.unflushed.drain(..));
+            self.peak_unread = self.peak_unread.max(send.len());
+        }
+        Ok(())
     }
 }
 
-#[cfg(test)]
-macro_rules! test_tls_bench_harnesses {
-    ($($lib_name:ident: $harness_type:ty,)*) => {
-    $(
-        mod $lib_name {
-            use super::*;
+/// Rename the supported_versions extension in a ClientHello record, so the
+/// server doesn't see that the client supports TLS1.3
+///
+/// Used with [`ConnectedBuffer::with_write_interceptor`] to simulate a downgrade
+/// attack. Records other than a complete ClientHello are left unchanged.
+pub fn hide_tls13_support(record: &mut [u8]) {
+    const SUPPORTED_VERSIONS: u16 = 0x002b;
+    hide_client_hello_extension(record, SUPPORTED_VERSIONS);
+}
 
-            #[test]
-            fn test_handshake() {
-                let mut harness = <$harness_type>::default().unwrap();
-                assert!(!harness.handshake_completed());
-                harness.handshake().unwrap();
-                assert!(harness.handshake_completed());
-                assert!(harness.negotiated_tls13());
-            }
+/// Rename the extended_master_secret extension in a ClientHello record, so
+/// the server doesn't see that the client supports extended master secrets
+///
+/// Used with [`ConnectedBuffer::with_write_interceptor`] to simulate a TLS1.2
+/// client without RFC 7627 support. Records other than a complete
+/// ClientHello are left unchanged.
+pub fn hide_extended_master_secret(record: &mut [u8]) {
+    const EXTENDED_MASTER_SECRET: u16 = 0x0017;
+    hide_client_hello_extension(record, EXTENDED_MASTER_SECRET);
+}
 
-            #[test]
-            fn test_different_crypto_config() {
-                use CipherSuite::*;
-                use ECGroup::*;
+/// Rename `extension` in a ClientHello record to a reserved GREASE value,
+/// which servers must ignore, leaving other records unchanged
+fn hide_client_hello_extension(record: &mut [u8], extension: u16) {
+    const UNKNOWN_EXTENSION: [u8; 2] = [0xfa, 0xfa];
 
-                let (mut harness, mut crypto_config);
-                for cipher_suite in [AES_128_GCM_SHA256, AES_256_GCM_SHA384].iter() {
-                    for ec_group in [SECP256R1, X25519].iter() {
-                        crypto_config = CryptoConfig { cipher_suite: cipher_suite.clone(), ec_group: ec_group.clone() };
-                        harness = <$harness_type>::new(&crypto_config).unwrap();
-                        harness.handshake().unwrap();
-                        assert_eq!(cipher_suite, &harness.get_negotiated_cipher_suite());
-                    }
-                }
-            }
+    for (pos, _) in client_hello_extension_positions(record).unwrap_or_default() {
+        if record[pos..pos + 2] == extension.to_be_bytes() {
+            record[pos..pos + 2].copy_from_slice(&UNKNOWN_EXTENSION);
         }
-    )*
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::{RustlsHarness, S2NHarness, TlsBenchHarness};
+/// Get the position of each extension in a ClientHello record at the start
+/// of `data`, along with the length of its body
+///
+/// Returns `None` if `data` doesn't start with a complete ClientHello record.
+fn client_hello_extension_positions(data: &[u8]) -> Option<Vec<(usize, usize)>> {
+    const HANDSHAKE_RECORD: u8 = 22;
+    const CLIENT_HELLO: u8 = 1;
+
+    let len = *record_lengths(data).first()?;
+    let record = &data[..5 + len];
+    if record[0] != HANDSHAKE_RECORD || record.get(5) != Some(&CLIENT_HELLO) {
+        return None;
+    }
+    let read_u16 = |pos: usize| {
+        let bytes = record.get(pos..pos + 2)?;
+        Some(u16::from_be_bytes([bytes[0], bytes[1]]) as usize)
+    };
+
+    // skip record header, handshake header, legacy version, and random
+    let mut pos = 5 + 4 + 2 + 32;
+    // skip session id, cipher suites, compression methods, and extensions length
+    pos += 1 + *record.get(pos)? as usize;
+    pos += 2 + read_u16(pos)?;
+    pos += 1 + *record.get(pos)? as usize;
+    pos += 2;
+
+    let mut positions = Vec::new();
+    while pos + 4 <= record.len() {
+        let body_len = read_u16(pos + 2)?;
+        if pos + 4 + body_len > record.len() {
+            return None;
+        }
+        positions.push((pos, body_len));
+        pos += 4 + body_len;
+    }
+    Some(positions)
+}
+
+/// Get the key exchange group selected by a ServerHello or HelloRetryRequest
+/// record at the start of `data`, from its key_share extension
+///
+/// Returns `None` if `data` doesn't start with a complete ServerHello record
+/// with a key_share extension.
+pub fn server_hello_group(data: &[u8]) -> Option<u16> {
+    const HANDSHAKE_RECORD: u8 = 22;
+    const SERVER_HELLO: u8 = 2;
+    const KEY_SHARE: u16 = 0x0033;
+
+    let len = *record_lengths(data).first()?;
+    if data[0] != HANDSHAKE_RECORD || len < 1 || data[5] != SERVER_HELLO {
+        return None;
+    }
+    let record = &data[..5 + len];
+    let read_u16 = |pos: usize| {
+        let bytes = record.get(pos..pos + 2)?;
+        Some(u16::from_be_bytes([bytes[0], bytes[1]]))
+    };
+
+    // skip record header, handshake header, legacy version, and random
+    let mut pos = 5 + 4 + 2 + 32;
+    // skip session id, cipher suite, compression method, and extensions length
+    pos += 1 + *record.get(pos)? as usize;
+    pos += 2 + 1 + 2;
+
+    while pos + 4 <= record.len() {
+        let extension_len = read_u16(pos + 2)? as usize;
+        if read_u16(pos)? == KEY_SHARE {
+            // the group comes first in both ServerHellos and
+            // HelloRetryRequests
+            return read_u16(pos + 4);
+        }
+        pos += 4 + extension_len;
+    }
+    None
+}
+
+/// Get whether `data` starts with a HelloRetryRequest record, which is a
+/// ServerHello with a fixed random
+pub fn is_hello_retry_request(data: &[u8]) -> bool {
+    const HANDSHAKE_RECORD: u8 = 22;
+    const SERVER_HELLO: u8 = 2;
+    // SHA-256 of "HelloRetryRequest", from RFC 8446 section 4.1.3
+    const HELLO_RETRY_REQUEST_RANDOM: [u8; 32] = [
+        0xcf, 0x21, 0xad, 0x74, 0xe5, 0x9a, 0x61, 0x11, 0xbe, 0x1d, 0x8c, 0x02, 0x1e, 0x65, 0xb8,
+        0x91, 0xc2, 0xa2, 0x11, 0x16, 0x7a, 0xbb, 0x8c, 0x5e, 0x07, 0x9e, 0x09, 0xe2, 0xc8, 0xa8,
+        0x33, 0x9c,
+    ];
+
+    // the random follows the record header, handshake header, and legacy
+    // version
+    let random_start = 5 + 4 + 2;
+    data.len() >= random_start + 32
+        && data[0] == HANDSHAKE_RECORD
+        && data[5] == SERVER_HELLO
+        && data[random_start..random_start + 32] == HELLO_RETRY_REQUEST_RANDOM
+}
+
+/// Get the types of the extensions in a ClientHello or ServerHello record at
+/// the start of `data`, in the order they were sent
+///
+/// Returns `None` if `data` doesn't start with a complete ClientHello or
+/// ServerHello record. In TLS1.3, most extensions negotiated by the server
+/// are sent encrypted after the ServerHello, so they can't be read here.
+pub fn hello_extensions(data: &[u8]) -> Option<Vec<u16>> {
+    let hello = Hello::parse(data)?;
+    Some(hello.extensions.iter().map(|(ext, _)| *ext).collect())
+}
+
+/// Fields of a ClientHello or ServerHello that don't change between
+/// handshakes with the same configs, unlike its random and session id
+struct Hello<'a> {
+    /// Cipher suites offered by a ClientHello, or the one selected by a
+    /// ServerHello
+    cipher_suites: Vec<u16>,
+    /// Type and body of each extension, in the order they were sent
+    extensions: Vec<(u16, &'a [u8])>,
+}
+
+impl<'a> Hello<'a> {
+    /// Parse a ClientHello or ServerHello record at the start of `data`
+    fn parse(data: &'a [u8]) -> Option<Self> {
+        const HANDSHAKE_RECORD: u8 = 22;
+        const CLIENT_HELLO: u8 = 1;
+        const SERVER_HELLO: u8 = 2;
+
+        let len = *record_lengths(data).first()?;
+        if data[0] != HANDSHAKE_RECORD || len < 1 {
+            return None;
+        }
+        let record = &data[..5 + len];
+        let read_u16 = |pos: usize| {
+            let bytes = record.get(pos..pos + 2)?;
+            Some(u16::from_be_bytes([bytes[0], bytes[1]]))
+        };
+
+        // skip record header, handshake header, legacy version, and random
+        let mut pos = 5 + 4 + 2 + 32;
+        // skip session id
+        pos += 1 + *record.get(pos)? as usize;
+        let mut cipher_suites = Vec::new();
+        match record[5] {
+            CLIENT_HELLO => {
+                let suites_end = pos + 2 + read_u16(pos)? as usize;
+                pos += 2;
+                while pos < suites_end {
+                    cipher_suites.push(read_u16(pos)?);
+                    pos += 2;
+                }
+                // skip compression methods
+                pos += 1 + *record.get(pos)? as usize;
+            }
+            SERVER_HELLO => {
+                cipher_suites.push(read_u16(pos)?);
+                // skip compression method
+                pos += 2 + 1;
+            }
+            _ => return None,
+        }
+        // skip extensions length
+        pos += 2;
+
+        let mut extensions = Vec::new();
+        while pos + 4 <= record.len() {
+            let body_len = read_u16(pos + 2)? as usize;
+            extensions.push((read_u16(pos)?, record.get(pos + 4..pos + 4 + body_len)?));
+            pos += 4 + body_len;
+        }
+        Some(Self {
+            cipher_suites,
+            extensions,
+        })
+    }
+}
+
+/// Summarize the records in a transcript recorded by
+/// [`ConnectedBuffer::recording`], one line per record or hello field, to
+/// compare wire formats across library versions
+///
+/// Anything that changes between handshakes with the same configs is left
+/// out: randoms, session ids, key share public keys, and encrypted records,
+/// whose lengths vary with the length of signatures. Each hello's cipher
+/// suites and extensions are listed in the order they were sent, with each
+/// extension's body in hex, except key shares which only list their groups.
+pub fn wire_format_summary(transcript: &[(Mode, Vec<u8>)]) -> String {
+    const RECORD_HEADER_LEN: usize = 5;
+    const CHANGE_CIPHER_SPEC: u8 = 20;
+    const ALERT: u8 = 21;
+    const HANDSHAKE: u8 = 22;
+    const APPLICATION_DATA: u8 = 23;
+    const CLIENT_HELLO: u8 = 1;
+    const KEY_SHARE: u16 = 0x0033;
+
+    let hex = |bytes: &[u8]| bytes.iter().map(|b| format!("{b:02x}")).collect::<String>();
+    let mut lines = Vec::new();
+    // data written by each side that doesn't make up a whole record yet
+    let (mut client_stream, mut server_stream) = (Vec::new(), Vec::new());
+    for (sender, data) in transcript {
+        let stream = match sender {
+            Mode::Client => &mut client_stream,
+            Mode::Server => &mut server_stream,
+        };
+        stream.extend_from_slice(data);
+
+        let tag = transcript_tag(*sender) as char;
+        let mut pos = 0;
+        for len in record_lengths(stream) {
+            let record = &stream[pos..pos + RECORD_HEADER_LEN + len];
+            pos += record.len();
+            match record[0] {
+                CHANGE_CIPHER_SPEC => lines.push(format!("{tag} change_cipher_spec")),
+                ALERT => lines.push(format!("{tag} alert {}", hex(&record[RECORD_HEADER_LEN..]))),
+                HANDSHAKE => {
+                    let message_type = record.get(RECORD_HEADER_LEN).copied().unwrap_or(0);
+                    lines.push(format!("{tag} handshake {message_type}"));
+                    let hello = match Hello::parse(record) {
+                        Some(hello) => hello,
+                        // other plaintext messages are only sent by TLS1.2
+                        None => continue,
+                    };
+                    let suites: Vec<_> = hello
+                        .cipher_suites
+                        .iter()
+                        .map(|suite| format!("{suite:04x}"))
+                        .collect();
+                    lines.push(format!("{tag}   cipher_suites {}", suites.join(" ")));
+                    for (ext, body) in hello.extensions {
+                        let body = if ext == KEY_SHARE {
+                            // ClientHellos list key shares after their length,
+                            // while ServerHellos and HelloRetryRequests hold
+                            // one key share or group
+                            let mut shares = match message_type {
+                                CLIENT_HELLO => &body[2.min(body.len())..],
+                                _ => body,
+                            };
+                            let mut groups = Vec::new();
+                            while shares.len() >= 2 {
+                                groups.push(hex(&shares[..2]));
+                                let key_len = match shares.get(2..4) {
+                                    Some(len) => u16::from_be_bytes([len[0], len[1]]) as usize,
+                                    None => break,
+                                };
+                                shares = shares.get(4 + key_len..).unwrap_or_default();
+                            }
+                            format!("groups {}", groups.join(" "))
+                        } else {
+                            hex(body)
+                        };
+                        lines.push(format!("{tag}   extension {ext:04x} {body}"));
+                    }
+                }
+                APPLICATION_DATA => lines.push(format!("{tag} application_data")),
+                other => lines.push(format!("{tag} content_type {other}")),
+            }
+        }
+        stream.drain(..pos);
+    }
+    lines.into_iter().map(|line| line + "\n").collect()
+}
+
+/// Get the length of each record in `data`, excluding the record header
+///
+/// `data` must start at a record boundary. A trailing partial record is
+/// ignored.
+pub fn record_lengths(data: &[u8]) -> Vec<usize> {
+    const RECORD_HEADER_LEN: usize = 5;
+
+    let mut lengths = Vec::new();
+    let mut pos = 0;
+    while pos + RECORD_HEADER_LEN <= data.len() {
+        let len = u16::from_be_bytes([data[pos + 3], data[pos + 4]]) as usize;
+        pos += RECORD_HEADER_LEN + len;
+        if pos <= data.len() {
+            lengths.push(len);
+        }
+    }
+    lengths
+}
+
+/// Build a plaintext handshake record holding only the header of a
+/// handshake message of `message_type` that claims to be `message_len` bytes
+/// long
+///
+/// Used with [`ConnectedBuffer::inject`] to test limits on handshake message
+/// sizes without sending the message body.
+pub fn handshake_header_record(message_type: u8, message_len: usize) -> Vec<u8> {
+    const HANDSHAKE_RECORD: u8 = 22;
+    const TLS12_VERSION: [u8; 2] = [0x03, 0x03];
+    const HANDSHAKE_HEADER_LEN: u16 = 4;
+
+    let mut record = vec![HANDSHAKE_RECORD];
+    record.extend(TLS12_VERSION);
+    record.extend(HANDSHAKE_HEADER_LEN.to_be_bytes());
+    record.push(message_type);
+    // handshake message lengths are 24 bits
+    record.extend(&(message_len as u32).to_be_bytes()[1..]);
+    record
+}
+
+/// Ways to malform a ClientHello, to measure how cheaply servers reject
+/// garbage from scanners and fuzzers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClientHelloMalformation {
+    /// The message ends partway through its random, with record and message
+    /// lengths that match the shortened message
+    Truncated,
+    /// The session ID length is larger than any session ID can be
+    BadLength,
+    /// The supported_versions extension only offers an unknown version
+    UnknownVersion,
+}
+
+impl ClientHelloMalformation {
+    pub const ALL: [Self; 3] = [Self::Truncated, Self::BadLength, Self::UnknownVersion];
+
+    /// Malform `client_hello`, a plaintext record holding a whole ClientHello
+    ///
+    /// Parts of the message that are missing from a short or malformed
+    /// `client_hello` are left unchanged.
+    pub fn apply(self, client_hello: &[u8]) -> Vec<u8> {
+        const CLIENT_HELLO: u8 = 1;
+        const RECORD_HEADER_LEN: usize = 5;
+        const HANDSHAKE_HEADER_LEN: usize = 4;
+        const BODY_START: usize = RECORD_HEADER_LEN + HANDSHAKE_HEADER_LEN;
+        // legacy_version and random come before the session ID
+        const SESSION_ID_LEN_INDEX: usize = BODY_START + 2 + 32;
+        const TRUNCATED_BODY_LEN: usize = 20;
+        const SUPPORTED_VERSIONS: [u8; 2] = [0x00, 0x2b];
+        const UNKNOWN_VERSION: [u8; 2] = [0x03, 0x09];
+
+        let mut malformed = client_hello.to_vec();
+        match self {
+            Self::Truncated => {
+                if let Some(body) = client_hello.get(BODY_START..BODY_START + TRUNCATED_BODY_LEN) {
+                    malformed = handshake_header_record(CLIENT_HELLO, TRUNCATED_BODY_LEN);
+                    let record_len = (HANDSHAKE_HEADER_LEN + TRUNCATED_BODY_LEN) as u16;
+                    malformed[3..RECORD_HEADER_LEN].copy_from_slice(&record_len.to_be_bytes());
+                    malformed.extend(body);
+                }
+            }
+            Self::BadLength => {
+                if let Some(session_id_len) = malformed.get_mut(SESSION_ID_LEN_INDEX) {
+                    *session_id_len = u8::MAX;
+                }
+            }
+            Self::UnknownVersion => {
+                let positions = client_hello_extension_positions(client_hello);
+                for (index, extension_len) in positions.unwrap_or_default() {
+                    if client_hello[index..index + 2] == SUPPORTED_VERSIONS {
+                        // a one byte list length, then two bytes per version
+                        let versions = malformed
+                            .get_mut(index + 5..index + 4 + extension_len)
+                            .unwrap_or_default();
+                        for version in versions.chunks_exact_mut(2) {
+                            version.copy_from_slice(&UNKNOWN_VERSION);
+                        }
+                    }
+                }
+            }
+        }
+        malformed
+    }
+}
+
+#[cfg(test)]
+macro_rules! test_tls_bench_harnesses {
+    ($($lib_name:ident: $harness_type:ty,)*) => {
+    $(
+        mod $lib_name {
+            use super::*;
+
+            #[test]
+            fn test_handshake() {
+                let mut harness = <$harness_type>::default().unwrap();
+                assert!(!harness.handshake_completed());
+                harness.handshake().unwrap();
+                assert!(harness.handshake_completed());
+                assert!(harness.negotiated_tls13());
+                assert!(!harness.hello_retry_requested());
+            }
+
+            #[test]
+            fn test_corrupted_record_rejected() {
+                const RECORD_HEADER_LEN: usize = 5;
+
+                let mut harness = <$harness_type>::default().unwrap();
+                harness.handshake().unwrap();
+                assert_eq!(harness.last_alert(), None);
+
+                harness.send(Mode::Client, &[1; 100]).unwrap();
+                harness.corrupt_pending_data(Mode::Server, RECORD_HEADER_LEN);
+                let mut received = [0; 100];
+                assert!(harness.recv(Mode::Server, &mut received).is_err());
+                // no corrupted plaintext is delivered
+                assert_eq!(received, [0; 100]);
+                assert_eq!(harness.last_alert(), Some(AlertDescription::BadRecordMac));
+            }
+
+            #[test]
+            fn test_malformed_client_hello_rejected() {
+                for malformation in ClientHelloMalformation::ALL {
+                    let mut harness = <$harness_type>::default().unwrap();
+                    harness.handshake_step(Mode::Client).unwrap();
+                    let client_hello = harness.pending_data(Mode::Server);
+                    let malformed = malformation.apply(&client_hello);
+                    assert_ne!(malformed, client_hello);
+                    harness.discard_pending_data(Mode::Server);
+                    harness.inject_pending_data(Mode::Server, &malformed);
+
+                    // the whole record has arrived, so the server must fail
+                    // rather than wait for more data
+                    assert!(
+                        harness.handshake_step(Mode::Server).is_err(),
+                        "{malformation:?}"
+                    );
+                    assert!(!harness.handshake_completed());
+                }
+            }
+
+            #[test]
+            fn test_malform_partial_client_hello() {
+                let mut harness = <$harness_type>::default().unwrap();
+                harness.handshake_step(Mode::Client).unwrap();
+                let client_hello = harness.pending_data(Mode::Server);
+
+                // only the parts of the message that are present are malformed
+                for malformation in ClientHelloMalformation::ALL {
+                    for len in 0..client_hello.len() {
+                        let malformed = malformation.apply(&client_hello[..len]);
+                        if malformation != ClientHelloMalformation::Truncated {
+                            assert_eq!(malformed.len(), len, "{malformation:?}");
+                        }
+                    }
+                }
+            }
+
+            #[test]
+            fn test_hide_tls13_support_partial_records() {
+                let mut harness = <$harness_type>::default().unwrap();
+                harness.handshake_step(Mode::Client).unwrap();
+                let client_hello = harness.pending_data(Mode::Server);
+
+                let mut hidden = client_hello.clone();
+                hide_tls13_support(&mut hidden);
+                assert_ne!(hidden, client_hello);
+
+                // partial records can't be parsed, so they're left unchanged
+                for len in 0..client_hello.len() {
+                    let mut partial = client_hello[..len].to_vec();
+                    hide_tls13_support(&mut partial);
+                    assert_eq!(partial, client_hello[..len]);
+                }
+            }
+
+            #[test]
+            fn test_handshake_step() {
+                const HANDSHAKE_RECORD: u8 = 22;
+                const CLIENT_HELLO: u8 = 1;
+
+                let mut harness = <$harness_type>::default().unwrap();
+                harness.handshake_step(Mode::Client).unwrap();
+                let client_hello = harness.pending_data(Mode::Server);
+                assert_eq!(client_hello[0], HANDSHAKE_RECORD);
+                assert_eq!(client_hello[5], CLIENT_HELLO);
+                assert_eq!(record_lengths(&client_hello).len(), 1);
+                // the server hasn't run, so it hasn't responded
+                assert!(harness.pending_data(Mode::Client).is_empty());
+
+                harness.handshake_step(Mode::Server).unwrap();
+                assert!(harness.pending_data(Mode::Server).is_empty());
+                assert!(!harness.pending_data(Mode::Client).is_empty());
+                assert!(!harness.handshake_completed());
+
+                harness.handshake_step(Mode::Client).unwrap();
+                harness.handshake_step(Mode::Server).unwrap();
+                assert!(harness.handshake_completed());
+            }
+
+            #[test]
+            fn test_handshake_step_count() {
+                // ClientHello, the server's flight, the client's Finished,
+                // and the server reading the client's Finished
+                const FULL_HANDSHAKE_STEPS: usize = 4;
+
+                let crypto_config = CryptoConfig {
+                    cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+                    ec_group: ECGroup::SECP256R1,
+                };
+                for handshake_type in [HandshakeType::ServerAuth, HandshakeType::MutualAuth] {
+                    let mut harness = <$harness_type>::new(&crypto_config, handshake_type).unwrap();
+                    assert_eq!(harness.handshake_step_count(), 0);
+                    harness.handshake().unwrap();
+                    assert!(harness.handshake_completed());
+                    assert_eq!(harness.handshake_step_count(), FULL_HANDSHAKE_STEPS);
+
+                    // steps after the handshake completes aren't counted
+                    harness.handshake_step(Mode::Client).unwrap();
+                    assert_eq!(harness.handshake_step_count(), FULL_HANDSHAKE_STEPS);
+                }
+
+                // TLS1.3 resumption saves computation, but not round trips
+                let mut harness = <$harness_type>::new_with_session_tickets(
+                    &crypto_config,
+                    HandshakeType::ServerAuth,
+                )
+                .unwrap();
+                harness.handshake().unwrap();
+                harness.send(Mode::Server, &[0]).unwrap();
+                harness.recv(Mode::Client, &mut [0]).unwrap();
+                let mut resumed = harness.new_resumed().unwrap();
+                resumed.handshake().unwrap();
+                assert!(resumed.was_resumed());
+                assert_eq!(resumed.handshake_step_count(), FULL_HANDSHAKE_STEPS);
+            }
+
+            #[test]
+            fn test_alpn_selected_from_long_list() {
+                let crypto_config = CryptoConfig {
+                    cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+                    ec_group: ECGroup::SECP256R1,
+                };
+                let mut harness = <$harness_type>::default().unwrap();
+                harness.handshake().unwrap();
+                let client_bytes = harness.bytes_sent(Mode::Client);
+
+                // the only protocol the server supports is the last one offered
+                let protocols: Vec<Vec<u8>> =
+                    (0..50).map(|i| format!("protocol-{i}").into_bytes()).collect();
+                let client_protocols: Vec<&[u8]> = protocols.iter().map(Vec::as_slice).collect();
+                let mut harness = <$harness_type>::new_with_alpn(
+                    &crypto_config,
+                    &client_protocols,
+                    &[b"unknown".as_slice(), b"protocol-49".as_slice()],
+                )
+                .unwrap();
+                harness.handshake().unwrap();
+                assert!(harness.handshake_completed());
+                assert_eq!(harness.connection_info().alpn, Some(b"protocol-49".to_vec()));
+                assert!(harness.bytes_sent(Mode::Client) > client_bytes);
+            }
+
+            #[test]
+            fn test_take_connected_buffer() {
+                const CAPACITY: usize = 100_000;
+
+                let crypto_config = CryptoConfig {
+                    cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+                    ec_group: ECGroup::SECP256R1,
+                };
+                let mut buf = ConnectedBuffer::with_capacity(CAPACITY);
+                for _ in 0..3 {
+                    let mut harness = <$harness_type>::new_with_buffer(
+                        &crypto_config,
+                        HandshakeType::ServerAuth,
+                        buf,
+                    )
+                    .unwrap();
+                    harness.handshake().unwrap();
+                    assert!(harness.handshake_completed());
+                    harness.send(Mode::Client, &[1; 100]).unwrap();
+
+                    buf = harness.take_connected_buffer();
+                    assert!(buf.recv.borrow().capacity() >= CAPACITY);
+                    assert!(buf.send.borrow().capacity() >= CAPACITY);
+                    assert!(buf.send.borrow().is_empty());
+                    assert_eq!(buf.sent_bytes(), 0);
+                }
+            }
+
+            #[test]
+            fn test_handshake_with_timeout() {
+                const MAX_STEPS: usize = 10;
+
+                let mut harness = <$harness_type>::default().unwrap();
+                harness.handshake_with_timeout(MAX_STEPS).unwrap();
+                assert!(harness.handshake_completed());
+
+                // every read returns no data, so the handshake never progresses
+                let crypto_config = CryptoConfig {
+                    cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+                    ec_group: ECGroup::SECP256R1,
+                };
+                let mut stalled = <$harness_type>::new_with_buffer(
+                    &crypto_config,
+                    HandshakeType::ServerAuth,
+                    ConnectedBuffer::with_max_read_len(0),
+                )
+                .unwrap();
+                let err = stalled.handshake_with_timeout(MAX_STEPS).unwrap_err();
+                assert_eq!(
+                    err.downcast_ref::<HandshakeTimeout>(),
+                    Some(&HandshakeTimeout { max_steps: MAX_STEPS })
+                );
+                assert!(!stalled.handshake_completed());
+            }
+
+            #[test]
+            fn test_crypto_backend() {
+                assert!(!<$harness_type>::crypto_backend().is_empty());
+            }
+
+            #[test]
+            fn test_connection_info() {
+                let crypto_config = CryptoConfig {
+                    cipher_suite: CipherSuite::AES_256_GCM_SHA384,
+                    ec_group: ECGroup::X25519,
+                };
+                if cfg!(feature = "fips") && !crypto_config.is_fips_approved() {
+                    return;
+                }
+                let mut harness =
+                    <$harness_type>::new(&crypto_config, HandshakeType::ServerAuth).unwrap();
+                harness.handshake().unwrap();
+
+                let info = harness.connection_info();
+                assert_eq!(info.cipher_suite, crypto_config.cipher_suite);
+                assert!(info.tls13);
+                assert_eq!(info.ec_group, crypto_config.ec_group);
+                assert_eq!(info.alpn, None);
+                assert!(!info.resumed);
+                // the leaf and the CA
+                assert_eq!(info.peer_cert_chain_len, Some(2));
+            }
+
+            #[test]
+            fn test_transfer() {
+                let mut harness = <$harness_type>::default().unwrap();
+                harness.handshake().unwrap();
+
+                let (request, response) = (b"request", b"response");
+                let (mut received_request, mut received_response) = ([0; 7], [0; 8]);
+                harness.send(Mode::Client, request).unwrap();
+                harness.recv(Mode::Server, &mut received_request).unwrap();
+                harness.send(Mode::Server, response).unwrap();
+                harness.recv(Mode::Client, &mut received_response).unwrap();
+                assert_eq!(request, &received_request);
+                assert_eq!(response, &received_response);
+                assert!(harness.is_drained());
+
+                // the request is sent with the client's last handshake flight
+                assert_eq!(harness.round_trips(), 2);
+            }
+
+            #[test]
+            fn test_large_transfer() {
+                const LEN: usize = 4_000_000;
+
+                let mut harness = <$harness_type>::default().unwrap();
+                harness.handshake().unwrap();
+
+                // many full-size records in each direction
+                let data: Vec<u8> = (0..LEN).map(|i| (i % 251) as u8).collect();
+                for (sender, receiver) in [(Mode::Client, Mode::Server), (Mode::Server, Mode::Client)] {
+                    let mut received = vec![0; LEN];
+                    harness.send(sender, &data).unwrap();
+                    harness.recv(receiver, &mut received).unwrap();
+                    assert!(data == received);
+                }
+                assert!(harness.is_drained());
+            }
+
+            #[test]
+            fn test_drained_after_handshake() {
+                let mut harness = <$harness_type>::default().unwrap();
+                harness.handshake().unwrap();
+                // only session tickets for the client may be left
+                assert!(harness.pending_data(Mode::Server).is_empty());
+
+                harness.send(Mode::Server, &[0]).unwrap();
+                harness.recv(Mode::Client, &mut [0]).unwrap();
+                assert!(harness.is_drained());
+            }
+
+            #[test]
+            fn test_reads_smaller_than_record() {
+                const READ_LEN: usize = 128;
+                const LEN: usize = 100_000;
+
+                let mut harness = <$harness_type>::default().unwrap();
+                harness.handshake().unwrap();
+
+                // each full-size record is read in many pieces
+                let data: Vec<u8> = (0..LEN).map(|i| (i % 251) as u8).collect();
+                harness.send(Mode::Client, &data).unwrap();
+                let mut received = Vec::new();
+                let mut buf = [0; READ_LEN];
+                while received.len() < LEN {
+                    let len = READ_LEN.min(LEN - received.len());
+                    harness.recv(Mode::Server, &mut buf[..len]).unwrap();
+                    received.extend_from_slice(&buf[..len]);
+                }
+                assert!(data == received);
+                assert!(harness.recv(Mode::Server, &mut buf).is_err());
+            }
+
+            #[test]
+            fn test_pending_data() {
+                const APPLICATION_DATA_RECORD: u8 = 23;
+
+                let crypto_config = CryptoConfig {
+                    cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+                    ec_group: ECGroup::SECP256R1,
+                };
+                let mut harness = <$harness_type>::new_with_session_tickets(
+                    &crypto_config,
+                    HandshakeType::ServerAuth,
+                )
+                .unwrap();
+                harness.handshake().unwrap();
+
+                // the server sends an encrypted session ticket after the
+                // handshake, which the client hasn't read
+                assert!(harness.pending_data(Mode::Server).is_empty());
+                let pending = harness.pending_data(Mode::Client);
+                assert_eq!(pending[0], APPLICATION_DATA_RECORD);
+                assert_eq!(harness.pending_data(Mode::Client), pending);
+
+                harness.send(Mode::Server, &[0]).unwrap();
+                harness.recv(Mode::Client, &mut [0]).unwrap();
+                assert!(harness.pending_data(Mode::Client).is_empty());
+            }
+
+            #[test]
+            fn test_discard_pending_data() {
+                let mut harness = <$harness_type>::default().unwrap();
+                harness.handshake().unwrap();
+
+                // the sender keeps encrypting while the receiver reads nothing
+                for _ in 0..3 {
+                    harness.send(Mode::Client, &[0; 1000]).unwrap();
+                    assert!(harness.pending_data(Mode::Server).len() > 1000);
+                    harness.discard_pending_data(Mode::Server);
+                    assert!(harness.pending_data(Mode::Server).is_empty());
+                }
+            }
+
+            #[test]
+            fn test_bytes_sent() {
+                let mut harness = <$harness_type>::default().unwrap();
+                assert_eq!(harness.bytes_sent(Mode::Client), 0);
+                harness.handshake().unwrap();
+
+                // the server sends its certificate chain
+                let client_handshake_bytes = harness.bytes_sent(Mode::Client);
+                let server_handshake_bytes = harness.bytes_sent(Mode::Server);
+                assert!(client_handshake_bytes > 0);
+                assert!(server_handshake_bytes > client_handshake_bytes);
+
+                // sent data is counted along with record overhead
+                harness.send(Mode::Client, &[0; 100]).unwrap();
+                assert!(harness.bytes_sent(Mode::Client) > client_handshake_bytes + 100);
+            }
+
+            #[test]
+            fn test_peak_unread_bytes() {
+                let crypto_config = CryptoConfig {
+                    cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+                    ec_group: ECGroup::SECP256R1,
+                };
+                let peaks = |handshake_type| {
+                    let mut harness = <$harness_type>::new(&crypto_config, handshake_type).unwrap();
+                    harness.handshake().unwrap();
+                    [Mode::Client, Mode::Server].map(|mode| {
+                        let peak = harness.peak_unread_bytes(mode);
+                        assert!(peak > 0 && peak <= harness.bytes_sent(mode));
+                        peak
+                    })
+                };
+                let [server_auth_client, server_auth_server] = peaks(HandshakeType::ServerAuth);
+                let [mutual_auth_client, mutual_auth_server] = peaks(HandshakeType::MutualAuth);
+
+                // the client's cert flight is larger than its ClientHello, and
+                // the server adds a CertificateRequest to its flight
+                assert!(mutual_auth_client > server_auth_client);
+                assert!(mutual_auth_server > server_auth_server);
+            }
+
+            #[test]
+            fn test_same_configs() {
+                let mut harness = <$harness_type>::default().unwrap();
+                let mut shared = harness.new_with_same_configs().unwrap();
+                harness.handshake().unwrap();
+                shared.handshake().unwrap();
+                assert!(shared.handshake_completed());
+                assert!(!harness.was_resumed());
+                assert!(!shared.was_resumed());
+                assert_eq!(shared.connection_info(), harness.connection_info());
+                for mode in [Mode::Client, Mode::Server] {
+                    assert_eq!(shared.records_sent(mode), harness.records_sent(mode));
+                }
+            }
+
+            #[test]
+            fn test_records_sent() {
+                let mut harness = <$harness_type>::default().unwrap();
+                harness.handshake().unwrap();
+                assert!(harness.negotiated_tls13());
+
+                // each library's exact counts are checked in its own tests
+                let client_records = harness.records_sent(Mode::Client);
+                let server_records = harness.records_sent(Mode::Server);
+
+                // application data is sent in a new record
+                harness.send(Mode::Client, &[0; 100]).unwrap();
+                assert_eq!(harness.records_sent(Mode::Client), client_records + 1);
+
+                // client auth adds the client's Certificate and
+                // CertificateVerify, and the server's CertificateRequest
+                let crypto_config = CryptoConfig {
+                    cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+                    ec_group: ECGroup::SECP256R1,
+                };
+                let mut mutual_auth =
+                    <$harness_type>::new(&crypto_config, HandshakeType::MutualAuth).unwrap();
+                mutual_auth.handshake().unwrap();
+                assert_eq!(mutual_auth.records_sent(Mode::Client), client_records + 2);
+                assert_eq!(mutual_auth.records_sent(Mode::Server), server_records + 1);
+            }
+
+            #[test]
+            fn test_fragmented_reads() {
+                let crypto_config = CryptoConfig {
+                    cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+                    ec_group: ECGroup::SECP256R1,
+                };
+                for seed in [1, 2, 42, 0xdead_beef, u64::MAX] {
+                    for handshake_type in [HandshakeType::ServerAuth, HandshakeType::MutualAuth] {
+                        let mut harness = <$harness_type>::new_with_buffer(
+                            &crypto_config,
+                            handshake_type,
+                            ConnectedBuffer::with_fragmented_reads(seed),
+                        )
+                        .unwrap();
+                        harness.handshake().unwrap();
+                        assert!(harness.handshake_completed(), "seed {seed}");
+
+                        let data = [7; 10_000];
+                        let mut received = [0; 10_000];
+                        harness.send(Mode::Client, &data).unwrap();
+                        harness.recv(Mode::Server, &mut received).unwrap();
+                        assert_eq!(data, received, "seed {seed}");
+                        harness.send(Mode::Server, &data).unwrap();
+                        harness.recv(Mode::Client, &mut received).unwrap();
+                        assert_eq!(data, received, "seed {seed}");
+                    }
+                }
+            }
+
+            #[test]
+            fn test_resumption() {
+                let crypto_config = CryptoConfig {
+                    cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+                    ec_group: ECGroup::SECP256R1,
+                };
+                let mut harness = <$harness_type>::new_with_session_tickets(
+                    &crypto_config,
+                    HandshakeType::ServerAuth,
+                )
+                .unwrap();
+                harness.handshake().unwrap();
+                // the client hasn't read the session tickets yet
+                assert!(harness.new_resumed().is_err());
+
+                // session tickets are sent after the handshake in TLS1.3
+                harness.send(Mode::Server, &[0]).unwrap();
+                harness.recv(Mode::Client, &mut [0]).unwrap();
+
+                assert!(!harness.was_resumed());
+
+                let mut resumed = harness.new_resumed().unwrap();
+                assert!(!resumed.handshake_completed());
+                resumed.handshake().unwrap();
+                assert!(resumed.handshake_completed());
+                assert!(resumed.was_resumed());
+            }
+
+            #[test]
+            fn test_resumption_key_exchange() {
+                // secp256r1, the group of the crypto config
+                const SECP256R1_ID: u16 = 0x0017;
+
+                let crypto_config = CryptoConfig {
+                    cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+                    ec_group: ECGroup::SECP256R1,
+                };
+                let mut harness = <$harness_type>::new_with_session_tickets(
+                    &crypto_config,
+                    HandshakeType::ServerAuth,
+                )
+                .unwrap();
+                harness.handshake().unwrap();
+                harness.send(Mode::Server, &[0]).unwrap();
+                harness.recv(Mode::Client, &mut [0]).unwrap();
+
+                // both libraries only offer and accept psk_dhe_ke, so resumed
+                // handshakes still agree on a fresh key with the server's
+                // key share, and keep forward secrecy
+                let mut resumed = harness.new_resumed().unwrap();
+                resumed.handshake_step(Mode::Client).unwrap();
+                resumed.handshake_step(Mode::Server).unwrap();
+                let server_hello = resumed.pending_data(Mode::Client);
+                assert_eq!(server_hello_group(&server_hello), Some(SECP256R1_ID));
+                resumed.handshake().unwrap();
+                assert!(resumed.was_resumed());
+            }
+
+            #[test]
+            fn test_client_auth() {
+                let crypto_config = CryptoConfig {
+                    cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+                    ec_group: ECGroup::SECP256R1,
+                };
+                for handshake_type in [HandshakeType::MutualAuth, HandshakeType::OptionalClientAuth] {
+                    let mut harness = <$harness_type>::new(&crypto_config, handshake_type).unwrap();
+                    harness.handshake().unwrap();
+                    assert!(harness.handshake_completed());
+                }
+            }
+
+            #[test]
+            fn test_mixed_sig_types() {
+                let crypto_config = CryptoConfig {
+                    cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+                    ec_group: ECGroup::SECP256R1,
+                };
+                for server in [SigType::Ecdsa, SigType::Rsa] {
+                    for client in [SigType::Ecdsa, SigType::Rsa] {
+                        let mut harness = <$harness_type>::new_with_certs(
+                            &crypto_config,
+                            HandshakeType::MutualAuth,
+                            &CertConfig::with_sig_types(server, client),
+                        )
+                        .unwrap();
+                        harness.handshake().unwrap();
+                        assert!(harness.handshake_completed(), "{server:?} {client:?}");
+                    }
+                }
+            }
+
+            #[test]
+            fn test_mixed_key_type_chains() {
+                let crypto_config = CryptoConfig {
+                    cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+                    ec_group: ECGroup::SECP256R1,
+                };
+                for ca in [SigType::Ecdsa, SigType::Rsa] {
+                    for leaf in [SigType::Ecdsa, SigType::Rsa] {
+                        let mut harness = <$harness_type>::new_with_certs(
+                            &crypto_config,
+                            HandshakeType::ServerAuth,
+                            &CertConfig::with_chain_sig_types(ca, leaf),
+                        )
+                        .unwrap();
+                        harness.handshake().unwrap();
+                        assert!(harness.handshake_completed(), "{ca:?} CA, {leaf:?} leaf");
+                    }
+                }
+            }
+
+            #[test]
+            fn test_rsa_key_sizes() {
+                let crypto_config = CryptoConfig {
+                    cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+                    ec_group: ECGroup::SECP256R1,
+                };
+                for bits in [2048, 3072, 4096] {
+                    let mut harness = <$harness_type>::new_with_certs(
+                        &crypto_config,
+                        HandshakeType::ServerAuth,
+                        &CertConfig::with_rsa_key_size(bits),
+                    )
+                    .unwrap();
+                    harness.handshake().unwrap();
+                    assert!(harness.handshake_completed(), "{bits}");
+                }
+            }
+
+            #[test]
+            fn test_pkcs12_identity() {
+                let crypto_config = CryptoConfig {
+                    cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+                    ec_group: ECGroup::SECP256R1,
+                };
+                let mut pem = <$harness_type>::default().unwrap();
+                let mut pkcs12 = <$harness_type>::new_with_certs(
+                    &crypto_config,
+                    HandshakeType::ServerAuth,
+                    &CertConfig::with_pkcs12(),
+                )
+                .unwrap();
+                pem.handshake().unwrap();
+                pkcs12.handshake().unwrap();
+                assert!(pkcs12.handshake_completed());
+                assert_eq!(pkcs12.get_negotiated_cipher_suite(), pem.get_negotiated_cipher_suite());
+                assert_eq!(pkcs12.round_trips(), pem.round_trips());
+            }
+
+            #[test]
+            fn test_large_trust_store() {
+                let crypto_config = CryptoConfig {
+                    cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+                    ec_group: ECGroup::SECP256R1,
+                };
+                for cert_config in [
+                    CertConfig::with_large_trust_store(),
+                    CertConfig::with_large_der_trust_store(),
+                ] {
+                    let mut harness = <$harness_type>::new_with_certs(
+                        &crypto_config,
+                        HandshakeType::ServerAuth,
+                        &cert_config,
+                    )
+                    .unwrap();
+                    harness.handshake().unwrap();
+                    assert!(harness.handshake_completed(), "{}", cert_config.ca_cert);
+                    assert_eq!(harness.last_alert(), None, "{}", cert_config.ca_cert);
+                }
+            }
+
+            #[test]
+            fn test_intermediate_order() {
+                for (order, valid) in [
+                    (IntermediateOrder::Ordered, true),
+                    (IntermediateOrder::Reversed, true),
+                    (IntermediateOrder::Missing, false),
+                ] {
+                    let crypto_config = CryptoConfig {
+                        cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+                        ec_group: ECGroup::SECP256R1,
+                    };
+                    let mut harness = <$harness_type>::new_with_certs(
+                        &crypto_config,
+                        HandshakeType::ServerAuth,
+                        &CertConfig::with_intermediates(order),
+                    )
+                    .unwrap();
+                    assert_eq!(harness.handshake().is_ok(), valid, "{order:?}");
+                    assert_eq!(harness.handshake_completed(), valid, "{order:?}");
+                    // the client can't build a path to a trusted CA
+                    let expected_alert = (!valid).then_some(AlertDescription::UnknownCa);
+                    assert_eq!(harness.last_alert(), expected_alert, "{order:?}");
+                }
+            }
+
+            #[test]
+            fn test_long_cert_chain() {
+                const CHAIN_LEN: usize = 5;
+
+                let crypto_config = CryptoConfig {
+                    cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+                    ec_group: ECGroup::SECP256R1,
+                };
+                let mut harness = <$harness_type>::new_with_certs(
+                    &crypto_config,
+                    HandshakeType::ServerAuth,
+                    &CertConfig::with_chain_len(CHAIN_LEN, IntermediateOrder::Ordered),
+                )
+                .unwrap();
+                harness.handshake().unwrap();
+                assert!(harness.handshake_completed());
+                // the server doesn't send the root CA
+                assert_eq!(
+                    harness.connection_info().peer_cert_chain_len,
+                    Some(CHAIN_LEN + 1)
+                );
+                let chain_bytes = harness.bytes_sent(Mode::Server);
+
+                let mut short = <$harness_type>::new_with_certs(
+                    &crypto_config,
+                    HandshakeType::ServerAuth,
+                    &CertConfig::with_chain_len(2, IntermediateOrder::Ordered),
+                )
+                .unwrap();
+                short.handshake().unwrap();
+                assert!(short.bytes_sent(Mode::Server) < chain_bytes);
+
+                let mut missing = <$harness_type>::new_with_certs(
+                    &crypto_config,
+                    HandshakeType::ServerAuth,
+                    &CertConfig::with_chain_len(CHAIN_LEN, IntermediateOrder::Missing),
+                )
+                .unwrap();
+                assert!(missing.handshake().is_err());
+                assert_eq!(missing.last_alert(), Some(AlertDescription::UnknownCa));
+            }
+
+            #[test]
+            fn test_heartbeat_never_negotiated() {
+                use CipherSuite::*;
+                use ECGroup::*;
+                // neither library implements heartbeats, so they can't be
+                // disabled, only checked for
+                const HEARTBEAT: u16 = 15;
+
+                for cipher_suite in [AES_128_GCM_SHA256, AES_256_GCM_SHA384] {
+                    for ec_group in [SECP256R1, X25519] {
+                        let crypto_config = CryptoConfig {
+                            cipher_suite: cipher_suite.clone(),
+                            ec_group,
+                        };
+                        if cfg!(feature = "fips") && !crypto_config.is_fips_approved() {
+                            continue;
+                        }
+                        for handshake_type in [HandshakeType::ServerAuth, HandshakeType::MutualAuth] {
+                            let mut harness =
+                                <$harness_type>::new(&crypto_config, handshake_type).unwrap();
+                            harness.handshake_step(Mode::Client).unwrap();
+                            let client_hello =
+                                hello_extensions(&harness.pending_data(Mode::Server)).unwrap();
+                            harness.handshake_step(Mode::Server).unwrap();
+                            let server_hello =
+                                hello_extensions(&harness.pending_data(Mode::Client)).unwrap();
+                            harness.handshake().unwrap();
+                            assert!(harness.handshake_completed());
+
+                            // a server can only negotiate extensions the client offers
+                            let context = format!("{crypto_config:?} {handshake_type:?}");
+                            assert!(!client_hello.contains(&HEARTBEAT), "{context}");
+                            assert!(!server_hello.contains(&HEARTBEAT), "{context}");
+                        }
+                    }
+                }
+            }
+
+            #[test]
+            fn test_different_crypto_config() {
+                use CipherSuite::*;
+                use ECGroup::*;
+
+                let (mut harness, mut crypto_config);
+                for cipher_suite in [AES_128_GCM_SHA256, AES_256_GCM_SHA384].iter() {
+                    for ec_group in [SECP256R1, X25519].iter() {
+                        crypto_config = CryptoConfig { cipher_suite: cipher_suite.clone(), ec_group: ec_group.clone() };
+                        if cfg!(feature = "fips") && !crypto_config.is_fips_approved() {
+                            continue;
+                        }
+                        let (client_log, server_log) = (KeyLog::default(), KeyLog::default());
+                        harness = <$harness_type>::new_with_key_logs(
+                            &crypto_config,
+                            HandshakeType::ServerAuth,
+                            &client_log,
+                            &server_log,
+                        )
+                        .unwrap();
+                        harness.handshake().unwrap();
+                        assert_eq!(cipher_suite, &harness.get_negotiated_cipher_suite());
+
+                        // each secret is as long as the PRF hash, hex-encoded
+                        let secret_len = match cipher_suite {
+                            AES_128_GCM_SHA256 => 2 * 32,
+                            AES_256_GCM_SHA384 => 2 * 48,
+                        };
+                        for label in ["CLIENT_TRAFFIC_SECRET_0", "SERVER_TRAFFIC_SECRET_0"] {
+                            let secret = client_log.secret(label).unwrap();
+                            assert_eq!(secret.len(), secret_len, "{label}");
+                            assert_eq!(Some(secret), server_log.secret(label), "{label}");
+                        }
+                    }
+                }
+            }
+        }
+    )*
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{RustlsHarness, S2NHarness, TlsBenchHarness};
+
+    #[test]
+    fn test_pkcs12_matches_pem() {
+        let pem = ServerIdentity::load(&CertConfig::default()).unwrap();
+        let pkcs12 = ServerIdentity::load(&CertConfig::with_pkcs12()).unwrap();
+        assert_eq!(pkcs12.cert_chain, pem.cert_chain);
+        assert!(!pkcs12.key.is_empty());
+
+        assert!(ServerIdentity::from_pkcs12(
+            &std::fs::read(SERVER_PKCS12_PATH).unwrap(),
+            "wrong password"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_der_trust_store_matches_pem() {
+        let pem = load_trust_store(LARGE_TRUST_STORE_PATH).unwrap();
+        let der = load_trust_store(LARGE_DER_TRUST_STORE_PATH).unwrap();
+        assert_eq!(pem.len(), 1000);
+        assert_eq!(der, pem);
+        assert_eq!(parse_pem_certs(&certs_to_pem(&der)).unwrap(), pem);
+
+        let bundle = std::fs::read(LARGE_DER_TRUST_STORE_PATH).unwrap();
+        assert!(parse_der_certs(&bundle[..bundle.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_preallocated_buffer_does_not_grow() {
+        let crypto_config = CryptoConfig {
+            cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+            ec_group: ECGroup::SECP256R1,
+        };
+        let capacities =
+            |buf: &ConnectedBuffer| (buf.recv.borrow().capacity(), buf.send.borrow().capacity());
+
+        // mutual auth sends the most handshake data
+        let mut harness = S2NHarness::new(&crypto_config, HandshakeType::MutualAuth).unwrap();
+        harness.handshake().unwrap();
+        let capacity = harness
+            .bytes_sent(Mode::Client)
+            .max(harness.bytes_sent(Mode::Server));
+
+        let buf = ConnectedBuffer::with_capacity(capacity);
+        let preallocated = capacities(&buf);
+        assert!(preallocated.0 >= capacity && preallocated.1 >= capacity);
+        let mut harness =
+            S2NHarness::new_with_buffer(&crypto_config, HandshakeType::MutualAuth, buf.clone())
+                .unwrap();
+        harness.handshake().unwrap();
+        assert!(harness.handshake_completed());
+        assert_eq!(capacities(&buf), preallocated);
+    }
+
+    #[test]
+    fn test_asymmetric_buffer_does_not_grow() {
+        const REQUEST_LEN: usize = 100;
+        const RESPONSE_LEN: usize = 1_000_000;
+        // room for record overhead and the handshake data sent to the client
+        const RECV_CAPACITY: usize = RESPONSE_LEN + 64 * 1024;
+
+        let crypto_config = CryptoConfig {
+            cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+            ec_group: ECGroup::SECP256R1,
+        };
+        // the client is the struct, so its requests go to the send buffer
+        let buf = ConnectedBuffer::with_capacities(4096, RECV_CAPACITY);
+        let recv_capacity = buf.recv.borrow().capacity();
+        assert!(recv_capacity >= RECV_CAPACITY);
+        assert!(buf.send.borrow().capacity() < RECV_CAPACITY);
+
+        let mut harness =
+            S2NHarness::new_with_buffer(&crypto_config, HandshakeType::ServerAuth, buf.clone())
+                .unwrap();
+        harness.handshake().unwrap();
+
+        let request = [1; REQUEST_LEN];
+        let response: Vec<u8> = (0..RESPONSE_LEN).map(|i| i as u8).collect();
+        let mut received_request = [0; REQUEST_LEN];
+        let mut received_response = vec![0; RESPONSE_LEN];
+        harness.send(Mode::Client, &request).unwrap();
+        harness.recv(Mode::Server, &mut received_request).unwrap();
+        harness.send(Mode::Server, &response).unwrap();
+        harness.recv(Mode::Client, &mut received_response).unwrap();
+        assert_eq!(received_request, request);
+        assert_eq!(received_response, response);
+        assert_eq!(buf.recv.borrow().capacity(), recv_capacity);
+    }
+
+    #[test]
+    fn test_read_to_bytes_cached() {
+        let path = std::env::temp_dir().join("s2n-tls-bench-cached-file");
+        let path = path.to_str().unwrap();
+        std::fs::write(path, "first").unwrap();
+        let first = read_to_bytes(path);
+        assert_eq!(&*first, b"first");
+
+        // later reads share the cached contents without touching the file
+        std::fs::write(path, "second").unwrap();
+        for _ in 0..10 {
+            assert!(Arc::ptr_eq(&read_to_bytes(path), &first));
+        }
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(&*read_to_bytes(path), b"first");
+    }
+
+    #[test]
+    fn test_pem_round_trip() {
+        let identity = ServerIdentity::load(&CertConfig::default()).unwrap();
+        let (cert_chain, key) = identity.to_pem();
+        assert_eq!(
+            ServerIdentity::from_pem(&cert_chain, &key).unwrap(),
+            identity
+        );
+    }
 
     test_tls_bench_harnesses! {
         s2n_tls: S2NHarness,
@@ -1,18 +1,34 @@
 // Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
 // SPDX-License-Identifier: Apache-2.0
 
+#[cfg(feature = "alloc-counter")]
+pub mod alloc_counter;
 pub mod harness;
 pub mod rustls;
 pub mod s2n_tls;
+#[cfg(feature = "slow-alloc")]
+pub mod slow_alloc;
+pub mod types;
 pub use crate::{
-    harness::{CipherSuite, CryptoConfig, ECGroup, TlsBenchHarness},
-    rustls::RustlsHarness,
-    s2n_tls::S2NHarness,
+    harness::{
+        AlertDescription, CertConfig, CipherSuite, ClientHelloMalformation, ConnectionInfo,
+        CryptoConfig, ECGroup, HandshakeTimeout, HandshakeType, IntermediateOrder, KeyLog,
+        ServerIdentity, SigType, TlsBenchHarness,
+    },
+    rustls::{AcceptAnyServerCert, RustlsHarness},
+    s2n_tls::{
+        CallCounts, EventLoopStats, S2NHarness, TicketKey, TlsStream, TrustStoreSource,
+        WildcardHostNameHandler, MAX_HANDSHAKE_MESSAGE_LEN,
+    },
 };
 
 const SERVER_KEY_PATH: &str = "certs/server-key.pem";
 const SERVER_CERT_CHAIN_PATH: &str = "certs/fullchain.pem";
 const CA_CERT_PATH: &str = "certs/ca-cert.pem";
+const CLIENT_KEY_PATH: &str = "certs/client-key.pem";
+const CLIENT_CERT_PATH: &str = "certs/client-cert.pem";
+const SERVER_PKCS12_PATH: &str = "certs/server.p12";
+const SERVER_PKCS12_PASSWORD: &str = "bench-password";
 
 #[cfg(test)]
 mod tests {
@@ -23,5 +39,8 @@ mod tests {
         assert!(Path::new(crate::SERVER_KEY_PATH).exists());
         assert!(Path::new(crate::SERVER_CERT_CHAIN_PATH).exists());
         assert!(Path::new(crate::CA_CERT_PATH).exists());
+        assert!(Path::new(crate::CLIENT_KEY_PATH).exists());
+        assert!(Path::new(crate::CLIENT_CERT_PATH).exists());
+        assert!(Path::new(crate::SERVER_PKCS12_PATH).exists());
     }
 }
@@ -1,56 +1,359 @@
 // Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{
-    harness::{
-        read_to_bytes, CipherSuite, ConnectedBuffer, CryptoConfig, ECGroup, Mode, TlsBenchHarness,
-    },
-    CA_CERT_PATH, SERVER_CERT_CHAIN_PATH, SERVER_KEY_PATH,
+use crate::harness::{
+    is_hello_retry_request, load_trust_store, read_to_bytes, server_hello_group, AlertDescription,
+    CertConfig, CipherSuite, ConnectedBuffer, ConnectionInfo, CryptoConfig, ECGroup, HandshakeType,
+    KeyLog, Mode, ServerIdentity, TlsBenchHarness,
 };
 use rustls::{
     cipher_suite::{TLS13_AES_128_GCM_SHA256, TLS13_AES_256_GCM_SHA384},
+    client::{
+        ClientSessionMemoryCache, ClientSessionStore, Resumption, ServerCertVerified,
+        ServerCertVerifier, Tls12ClientSessionValue, Tls13ClientSessionValue,
+    },
     kx_group::{SECP256R1, X25519},
+    server::{AllowAnyAnonymousOrAuthenticatedClient, AllowAnyAuthenticatedClient, NoClientAuth},
     version::TLS13,
-    Certificate, ClientConfig, ClientConnection, PrivateKey,
+    Certificate, CertificateError, ClientConfig, ClientConnection, ConnectionCommon, NamedGroup,
+    PrivateKey,
     ProtocolVersion::TLSv1_3,
-    RootCertStore, ServerConfig, ServerConnection, ServerName,
+    RootCertStore, ServerConfig, ServerConnection, ServerName, SupportedCipherSuite,
+    SupportedKxGroup,
 };
 use rustls_pemfile::{certs, pkcs8_private_keys};
-use std::{error::Error, io::BufReader, sync::Arc};
+use std::{
+    error::Error,
+    io::{BufReader, ErrorKind, Read, Write},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::SystemTime,
+};
 
 pub struct RustlsHarness {
     client_buf: ConnectedBuffer,
     server_buf: ConnectedBuffer,
+    client_config: Arc<ClientConfig>,
+    server_config: Arc<ServerConfig>,
+    // the session store of `client_config`, which rustls doesn't expose
+    session_store: Arc<CountingSessionStore>,
     client_conn: ClientConnection,
     server_conn: ServerConnection,
+    // rustls 0.21 doesn't report the negotiated group, so it's read from
+    // the ServerHello received by the client
+    ec_group: Option<ECGroup>,
+    // nor whether the server sent a HelloRetryRequest
+    hello_retry_requested: bool,
+    handshake_step_count: usize,
+    last_alert: Option<AlertDescription>,
 }
 
 impl RustlsHarness {
-    fn get_root_cert_store() -> Result<RootCertStore, Box<dyn Error>> {
-        let root_cert =
-            Certificate(certs(&mut BufReader::new(&*read_to_bytes(CA_CERT_PATH)))?.remove(0));
+    fn get_root_cert_store(path: &str) -> Result<RootCertStore, Box<dyn Error>> {
         let mut root_certs = RootCertStore::empty();
-        root_certs.add(&root_cert)?;
+        for root_cert in load_trust_store(path)? {
+            root_certs.add(&Certificate(root_cert))?;
+        }
         Ok(root_certs)
     }
 
-    fn get_cert_chain() -> Result<Vec<Certificate>, Box<dyn Error>> {
-        let chain = certs(&mut BufReader::new(&*read_to_bytes(SERVER_CERT_CHAIN_PATH)))?;
+    fn get_cert_chain(path: &str) -> Result<Vec<Certificate>, Box<dyn Error>> {
+        let chain = certs(&mut BufReader::new(&*read_to_bytes(path)))?;
         Ok(chain
             .iter()
             .map(|bytes| Certificate(bytes.to_vec()))
             .collect())
     }
 
-    fn get_server_key() -> Result<PrivateKey, Box<dyn Error>> {
+    fn get_key(path: &str) -> Result<PrivateKey, Box<dyn Error>> {
         Ok(PrivateKey(
-            pkcs8_private_keys(&mut BufReader::new(&*read_to_bytes(SERVER_KEY_PATH)))?.remove(0),
+            pkcs8_private_keys(&mut BufReader::new(&*read_to_bytes(path)))?.remove(0),
         ))
     }
 
+    fn get_cipher_suite(cipher_suite: &CipherSuite) -> SupportedCipherSuite {
+        match cipher_suite {
+            CipherSuite::AES_128_GCM_SHA256 => TLS13_AES_128_GCM_SHA256,
+            CipherSuite::AES_256_GCM_SHA384 => TLS13_AES_256_GCM_SHA384,
+        }
+    }
+
+    /// Create client and server configs, where the server uses the certs in
+    /// `cert_config`
+    fn create_configs(
+        crypto_config: &CryptoConfig,
+        handshake_type: HandshakeType,
+        cert_config: &CertConfig,
+    ) -> Result<(ClientConfig, ServerConfig), Box<dyn Error>> {
+        let cipher_suites = [Self::get_cipher_suite(&crypto_config.cipher_suite)];
+        Self::create_configs_with_cipher_suites(
+            &cipher_suites,
+            &cipher_suites,
+            &[crypto_config.ec_group.clone()],
+            &[crypto_config.ec_group.clone()],
+            handshake_type,
+            cert_config,
+        )
+    }
+
+    /// Build the client and server configs used by [`TlsBenchHarness::new`],
+    /// without initializing connections
+    pub fn build_configs(
+        crypto_config: &CryptoConfig,
+        handshake_type: HandshakeType,
+    ) -> Result<(Arc<ClientConfig>, Arc<ServerConfig>), Box<dyn Error>> {
+        let (client_config, server_config) =
+            Self::create_configs(crypto_config, handshake_type, &CertConfig::default())?;
+        Ok((Arc::new(client_config), Arc::new(server_config)))
+    }
+
+    fn get_kx_groups(ec_groups: &[ECGroup]) -> Vec<&'static SupportedKxGroup> {
+        ec_groups
+            .iter()
+            .map(|ec_group| match ec_group {
+                ECGroup::SECP256R1 => &SECP256R1,
+                ECGroup::X25519 => &X25519,
+            })
+            .collect()
+    }
+
+    /// Create client and server configs, where each side supports cipher
+    /// suites and groups in the given order of preference
+    fn create_configs_with_cipher_suites(
+        client_cipher_suites: &[SupportedCipherSuite],
+        server_cipher_suites: &[SupportedCipherSuite],
+        client_ec_groups: &[ECGroup],
+        server_ec_groups: &[ECGroup],
+        handshake_type: HandshakeType,
+        cert_config: &CertConfig,
+    ) -> Result<(ClientConfig, ServerConfig), Box<dyn Error>> {
+        let client_builder = ClientConfig::builder()
+            .with_cipher_suites(client_cipher_suites)
+            .with_kx_groups(&Self::get_kx_groups(client_ec_groups))
+            .with_protocol_versions(&[&TLS13])?
+            .with_root_certificates(Self::get_root_cert_store(&cert_config.ca_cert)?);
+        let client_config = match handshake_type {
+            HandshakeType::MutualAuth => client_builder.with_client_auth_cert(
+                Self::get_cert_chain(&cert_config.client_cert)?,
+                Self::get_key(&cert_config.client_key)?,
+            )?,
+            HandshakeType::ServerAuth | HandshakeType::OptionalClientAuth => {
+                client_builder.with_no_client_auth()
+            }
+        };
+
+        let client_cert_verifier = match handshake_type {
+            HandshakeType::ServerAuth => NoClientAuth::boxed(),
+            HandshakeType::MutualAuth => AllowAnyAuthenticatedClient::new(
+                Self::get_root_cert_store(&cert_config.client_ca_cert)?,
+            )
+            .boxed(),
+            HandshakeType::OptionalClientAuth => AllowAnyAnonymousOrAuthenticatedClient::new(
+                Self::get_root_cert_store(&cert_config.client_ca_cert)?,
+            )
+            .boxed(),
+        };
+        let server_identity = ServerIdentity::load(cert_config)?;
+        let server_config = ServerConfig::builder()
+            .with_cipher_suites(server_cipher_suites)
+            .with_kx_groups(&Self::get_kx_groups(server_ec_groups))
+            .with_protocol_versions(&[&TLS13])?
+            .with_client_cert_verifier(client_cert_verifier)
+            .with_single_cert(
+                server_identity
+                    .cert_chain
+                    .into_iter()
+                    .map(Certificate)
+                    .collect(),
+                PrivateKey(server_identity.key),
+            )?;
+
+        Ok((client_config, server_config))
+    }
+
+    /// Initialize connections where the client and server support cipher
+    /// suites in different orders of preference
+    ///
+    /// rustls servers follow the client's order unless `honor_server_order`
+    /// is set
+    pub fn new_with_cipher_order(
+        client_order: &[CipherSuite],
+        server_order: &[CipherSuite],
+        honor_server_order: bool,
+    ) -> Result<Self, Box<dyn Error>> {
+        let (client_config, mut server_config) = Self::create_configs_with_cipher_suites(
+            &client_order
+                .iter()
+                .map(Self::get_cipher_suite)
+                .collect::<Vec<_>>(),
+            &server_order
+                .iter()
+                .map(Self::get_cipher_suite)
+                .collect::<Vec<_>>(),
+            &[ECGroup::SECP256R1],
+            &[ECGroup::SECP256R1],
+            HandshakeType::ServerAuth,
+            &CertConfig::default(),
+        )?;
+        server_config.ignore_client_order = honor_server_order;
+
+        Self::from_new_configs(ConnectedBuffer::new(), client_config, server_config)
+    }
+
+    /// Initialize connections where each side supports groups in the given
+    /// order of preference
+    ///
+    /// The client only sends a key share for its first group. If the server
+    /// doesn't support it, the server sends a HelloRetryRequest for its most
+    /// preferred group that the client supports.
+    pub fn new_with_kx_groups(
+        client_ec_groups: &[ECGroup],
+        server_ec_groups: &[ECGroup],
+    ) -> Result<Self, Box<dyn Error>> {
+        let cipher_suites = [TLS13_AES_128_GCM_SHA256];
+        let (client_config, server_config) = Self::create_configs_with_cipher_suites(
+            &cipher_suites,
+            &cipher_suites,
+            client_ec_groups,
+            server_ec_groups,
+            HandshakeType::ServerAuth,
+            &CertConfig::default(),
+        )?;
+
+        Self::from_new_configs(ConnectedBuffer::new(), client_config, server_config)
+    }
+
+    /// Initialize connections where the client validates the server's
+    /// certificate chain with `verifier` instead of the built-in webpki
+    /// verifier
+    ///
+    /// Handshake signatures are still verified by rustls unless `verifier`
+    /// overrides them
+    pub fn new_with_server_cert_verifier(
+        crypto_config: &CryptoConfig,
+        verifier: Arc<dyn ServerCertVerifier>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let cipher_suites = [Self::get_cipher_suite(&crypto_config.cipher_suite)];
+        let (mut client_config, server_config) = Self::create_configs_with_cipher_suites(
+            &cipher_suites,
+            &cipher_suites,
+            &[crypto_config.ec_group.clone()],
+            &[crypto_config.ec_group.clone()],
+            HandshakeType::ServerAuth,
+            &CertConfig::default(),
+        )?;
+        client_config.dangerous().set_certificate_verifier(verifier);
+
+        Self::from_new_configs(ConnectedBuffer::new(), client_config, server_config)
+    }
+
+    /// Set up connections with new configs, where the client stores session
+    /// tickets in a new [`CountingSessionStore`]
+    fn from_new_configs(
+        client_buf: ConnectedBuffer,
+        mut client_config: ClientConfig,
+        server_config: ServerConfig,
+    ) -> Result<Self, Box<dyn Error>> {
+        let session_store = Arc::new(CountingSessionStore::default());
+        client_config.resumption = Resumption::store(session_store.clone());
+        Self::from_configs(
+            client_buf,
+            Arc::new(client_config),
+            Arc::new(server_config),
+            session_store,
+        )
+    }
+
+    /// Set up connections with existing configs
+    ///
+    /// Rustls clients store received session tickets in their config, so
+    /// connections sharing a config resume previous sessions
+    fn from_configs(
+        client_buf: ConnectedBuffer,
+        client_config: Arc<ClientConfig>,
+        server_config: Arc<ServerConfig>,
+        session_store: Arc<CountingSessionStore>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let server_buf = client_buf.clone_inverse();
+
+        let client_conn =
+            ClientConnection::new(client_config.clone(), ServerName::try_from("localhost")?)?;
+        let server_conn = ServerConnection::new(server_config.clone())?;
+
+        Ok(Self {
+            client_buf,
+            server_buf,
+            client_config,
+            server_config,
+            session_store,
+            client_conn,
+            server_conn,
+            ec_group: None,
+            hello_retry_requested: false,
+            handshake_step_count: 0,
+            last_alert: None,
+        })
+    }
+
+    /// Read decrypted data, reading and processing more incoming records
+    /// whenever the decrypted data runs out
+    ///
+    /// Rustls limits how much decrypted data it buffers, so records can't all
+    /// be processed up front for large transfers
+    fn recv_conn<T>(
+        conn: &mut ConnectionCommon<T>,
+        buf: &mut ConnectedBuffer,
+        data: &mut [u8],
+    ) -> Result<(), Box<dyn Error>> {
+        let mut received = 0;
+        while received < data.len() {
+            let len = match conn.reader().read(&mut data[received..]) {
+                Ok(len) => len,
+                Err(err) if err.kind() == ErrorKind::WouldBlock => 0,
+                Err(err) => return Err(err.into()),
+            };
+            received += len;
+
+            if len == 0 {
+                match conn.read_tls(buf) {
+                    Ok(_) => {
+                        conn.process_new_packets()?;
+                    }
+                    Err(err) if err.kind() == ErrorKind::WouldBlock => {
+                        return Err("not enough data sent to receive".into())
+                    }
+                    Err(err) => return Err(err.into()),
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// Read all incoming data, process it, and write it out
     /// Works for handshaking and data transfer
     fn process_conn(&mut self, mode: Mode) -> Result<(), std::io::Error> {
+        let handshaking = match mode {
+            Mode::Client => self.client_conn.is_handshaking(),
+            Mode::Server => self.server_conn.is_handshaking(),
+        };
+        if handshaking {
+            self.handshake_step_count += 1;
+            // the first ServerHello is parsed before the client processes
+            // it. A HelloRetryRequest selects the same group as the
+            // ServerHello that follows it, so both are known from then on.
+            if mode == Mode::Client && self.ec_group.is_none() {
+                let (group, hello_retry_requested) = self.client_buf.inspect_unread(|received| {
+                    (
+                        server_hello_group(received),
+                        is_hello_retry_request(received),
+                    )
+                });
+                self.ec_group = group.map(ec_group_from_id);
+                self.hello_retry_requested |= hello_retry_requested;
+            }
+        }
         match match mode {
             Mode::Client => self.client_conn.complete_io(&mut self.client_buf),
             Mode::Server => self.server_conn.complete_io(&mut self.server_buf),
@@ -60,6 +363,13 @@ impl RustlsHarness {
                 if err.kind() == std::io::ErrorKind::WouldBlock {
                     Ok(())
                 } else {
+                    // rustls errors are wrapped in the io error
+                    if let Some(err) = err
+                        .get_ref()
+                        .and_then(|err| err.downcast_ref::<rustls::Error>())
+                    {
+                        self.last_alert = error_alert(err);
+                    }
                     Err(err)
                 }
             }
@@ -67,48 +377,236 @@ impl RustlsHarness {
     }
 }
 
-impl TlsBenchHarness for RustlsHarness {
-    fn new(crypto_config: &CryptoConfig) -> Result<Self, Box<dyn Error>> {
-        let client_buf = ConnectedBuffer::new();
-        let server_buf = client_buf.clone_inverse();
+/// Client session store that counts the TLS1.3 session tickets it holds,
+/// since rustls doesn't report whether a client has a ticket to resume
+struct CountingSessionStore {
+    cache: ClientSessionMemoryCache,
+    tls13_tickets: AtomicUsize,
+}
 
-        let cipher_suite = match crypto_config.cipher_suite {
-            CipherSuite::AES_128_GCM_SHA256 => TLS13_AES_128_GCM_SHA256,
-            CipherSuite::AES_256_GCM_SHA384 => TLS13_AES_256_GCM_SHA384,
-        };
+impl CountingSessionStore {
+    /// Get the number of TLS1.3 tickets received and not yet used to resume
+    fn tls13_tickets(&self) -> usize {
+        self.tls13_tickets.load(Ordering::Relaxed)
+    }
+}
 
-        let kx_group = match crypto_config.ec_group {
-            ECGroup::SECP256R1 => &SECP256R1,
-            ECGroup::X25519 => &X25519,
+impl Default for CountingSessionStore {
+    fn default() -> Self {
+        Self {
+            // the size of the default rustls client session cache
+            cache: ClientSessionMemoryCache::new(256),
+            tls13_tickets: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl ClientSessionStore for CountingSessionStore {
+    fn set_kx_hint(&self, server_name: &ServerName, group: NamedGroup) {
+        self.cache.set_kx_hint(server_name, group)
+    }
+
+    fn kx_hint(&self, server_name: &ServerName) -> Option<NamedGroup> {
+        self.cache.kx_hint(server_name)
+    }
+
+    fn set_tls12_session(&self, server_name: &ServerName, value: Tls12ClientSessionValue) {
+        self.cache.set_tls12_session(server_name, value)
+    }
+
+    fn tls12_session(&self, server_name: &ServerName) -> Option<Tls12ClientSessionValue> {
+        self.cache.tls12_session(server_name)
+    }
+
+    fn remove_tls12_session(&self, server_name: &ServerName) {
+        self.cache.remove_tls12_session(server_name)
+    }
+
+    fn insert_tls13_ticket(&self, server_name: &ServerName, value: Tls13ClientSessionValue) {
+        self.tls13_tickets.fetch_add(1, Ordering::Relaxed);
+        self.cache.insert_tls13_ticket(server_name, value)
+    }
+
+    fn take_tls13_ticket(&self, server_name: &ServerName) -> Option<Tls13ClientSessionValue> {
+        let ticket = self.cache.take_tls13_ticket(server_name);
+        if ticket.is_some() {
+            self.tls13_tickets.fetch_sub(1, Ordering::Relaxed);
+        }
+        ticket
+    }
+}
+
+/// Server certificate verifier that accepts any certificate chain, to
+/// measure the overhead of replacing the built-in verifier
+pub struct AcceptAnyServerCert;
+
+impl ServerCertVerifier for AcceptAnyServerCert {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &Certificate,
+        _intermediates: &[Certificate],
+        _server_name: &ServerName,
+        _scts: &mut dyn Iterator<Item = &[u8]>,
+        _ocsp_response: &[u8],
+        _now: SystemTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+}
+
+impl rustls::KeyLog for KeyLog {
+    fn log(&self, label: &str, client_random: &[u8], secret: &[u8]) {
+        let hex = |bytes: &[u8]| bytes.iter().map(|b| format!("{b:02x}")).collect::<String>();
+        self.log_line(format!("{label} {} {}", hex(client_random), hex(secret)));
+    }
+}
+
+/// Get whether the DER-encoded certificate `cert` has the same issuer and
+/// subject
+///
+/// Returns `None` if `cert` isn't a well-formed certificate.
+fn is_self_issued(cert: &[u8]) -> Option<bool> {
+    // split the DER element at the start of `data` into the whole element,
+    // its contents, and the data after it
+    fn split_element(data: &[u8]) -> Option<(&[u8], &[u8], &[u8])> {
+        let first_len_byte = *data.get(1)? as usize;
+        let (header_len, len) = if first_len_byte < 0x80 {
+            (2, first_len_byte)
+        } else {
+            let len_bytes = data.get(2..2 + (first_len_byte & 0x7f))?;
+            let len = len_bytes
+                .iter()
+                .fold(0usize, |len, byte| (len << 8) | *byte as usize);
+            (2 + len_bytes.len(), len)
         };
+        let end = header_len.checked_add(len)?;
+        let element = data.get(..end)?;
+        Some((element, &element[header_len..], &data[end..]))
+    }
+    const VERSION_TAG: u8 = 0xa0;
 
-        let client_config = Arc::new(
-            ClientConfig::builder()
-                .with_cipher_suites(&[cipher_suite])
-                .with_kx_groups(&[kx_group])
-                .with_protocol_versions(&[&TLS13])?
-                .with_root_certificates(Self::get_root_cert_store()?)
-                .with_no_client_auth(),
-        );
+    let (_, cert, _) = split_element(cert)?;
+    let (_, tbs_cert, _) = split_element(cert)?;
+    let mut fields = tbs_cert;
+    if fields.first() == Some(&VERSION_TAG) {
+        fields = split_element(fields)?.2;
+    }
+    // skip the serial number and signature algorithm
+    let (_, _, fields) = split_element(fields)?;
+    let (_, _, fields) = split_element(fields)?;
+    let (issuer, _, fields) = split_element(fields)?;
+    // skip the validity period
+    let (_, _, fields) = split_element(fields)?;
+    let (subject, _, _) = split_element(fields)?;
+    Some(issuer == subject)
+}
 
-        let server_config = Arc::new(
-            ServerConfig::builder()
-                .with_cipher_suites(&[cipher_suite])
-                .with_kx_groups(&[kx_group])
-                .with_protocol_versions(&[&TLS13])?
-                .with_no_client_auth()
-                .with_single_cert(Self::get_cert_chain()?, Self::get_server_key()?)?,
-        );
+/// Map the id of a group negotiated by rustls back to an [`ECGroup`]
+fn ec_group_from_id(id: u16) -> ECGroup {
+    match NamedGroup::from(id) {
+        NamedGroup::secp256r1 => ECGroup::SECP256R1,
+        NamedGroup::X25519 => ECGroup::X25519,
+        group => panic!("rustls negotiated unmapped group {group:?}"),
+    }
+}
 
-        let client_conn = ClientConnection::new(client_config, ServerName::try_from("localhost")?)?;
-        let server_conn = ServerConnection::new(server_config)?;
+/// Get the alert received or sent by rustls for a handshake error
+fn error_alert(err: &rustls::Error) -> Option<AlertDescription> {
+    use rustls::Error::*;
+    let alert = match err {
+        AlertReceived(alert) => AlertDescription::from(alert.get_u8()),
+        InappropriateMessage { .. } | InappropriateHandshakeMessage { .. } => {
+            AlertDescription::UnexpectedMessage
+        }
+        InvalidMessage(_) => AlertDescription::DecodeError,
+        DecryptError => AlertDescription::BadRecordMac,
+        NoCertificatesPresented => AlertDescription::CertificateRequired,
+        PeerIncompatible(_) => AlertDescription::HandshakeFailure,
+        PeerMisbehaved(_) => AlertDescription::IllegalParameter,
+        InvalidCertificate(err) => match err {
+            CertificateError::UnknownIssuer => AlertDescription::UnknownCa,
+            CertificateError::Expired => AlertDescription::CertificateExpired,
+            CertificateError::BadSignature => AlertDescription::DecryptError,
+            _ => AlertDescription::BadCertificate,
+        },
+        _ => return None,
+    };
+    Some(alert)
+}
 
-        Ok(Self {
-            client_buf,
-            server_buf,
-            client_conn,
-            server_conn,
-        })
+impl TlsBenchHarness for RustlsHarness {
+    fn new_with_certs(
+        crypto_config: &CryptoConfig,
+        handshake_type: HandshakeType,
+        cert_config: &CertConfig,
+    ) -> Result<Self, Box<dyn Error>> {
+        let (client_config, server_config) =
+            Self::create_configs(crypto_config, handshake_type, cert_config)?;
+        Self::from_new_configs(ConnectedBuffer::new(), client_config, server_config)
+    }
+
+    fn new_with_session_tickets(
+        crypto_config: &CryptoConfig,
+        handshake_type: HandshakeType,
+    ) -> Result<Self, Box<dyn Error>> {
+        // rustls servers issue session tickets by default
+        Self::new(crypto_config, handshake_type)
+    }
+
+    fn new_with_buffer(
+        crypto_config: &CryptoConfig,
+        handshake_type: HandshakeType,
+        client_buf: ConnectedBuffer,
+    ) -> Result<Self, Box<dyn Error>> {
+        let (client_config, server_config) =
+            Self::create_configs(crypto_config, handshake_type, &CertConfig::default())?;
+        Self::from_new_configs(client_buf, client_config, server_config)
+    }
+
+    fn new_with_alpn(
+        crypto_config: &CryptoConfig,
+        client_protocols: &[&[u8]],
+        server_protocols: &[&[u8]],
+    ) -> Result<Self, Box<dyn Error>> {
+        let cipher_suites = [Self::get_cipher_suite(&crypto_config.cipher_suite)];
+        let (mut client_config, mut server_config) = Self::create_configs_with_cipher_suites(
+            &cipher_suites,
+            &cipher_suites,
+            &[crypto_config.ec_group.clone()],
+            &[crypto_config.ec_group.clone()],
+            HandshakeType::ServerAuth,
+            &CertConfig::default(),
+        )?;
+        client_config.alpn_protocols = client_protocols.iter().map(|p| p.to_vec()).collect();
+        server_config.alpn_protocols = server_protocols.iter().map(|p| p.to_vec()).collect();
+
+        Self::from_new_configs(ConnectedBuffer::new(), client_config, server_config)
+    }
+
+    fn new_with_key_logs(
+        crypto_config: &CryptoConfig,
+        handshake_type: HandshakeType,
+        client_log: &KeyLog,
+        server_log: &KeyLog,
+    ) -> Result<Self, Box<dyn Error>> {
+        let cipher_suites = [Self::get_cipher_suite(&crypto_config.cipher_suite)];
+        let (mut client_config, mut server_config) = Self::create_configs_with_cipher_suites(
+            &cipher_suites,
+            &cipher_suites,
+            &[crypto_config.ec_group.clone()],
+            &[crypto_config.ec_group.clone()],
+            handshake_type,
+            &CertConfig::default(),
+        )?;
+        client_config.key_log = Arc::new(client_log.clone());
+        server_config.key_log = Arc::new(server_log.clone());
+
+        Self::from_new_configs(ConnectedBuffer::new(), client_config, server_config)
+    }
+
+    fn take_connected_buffer(self) -> ConnectedBuffer {
+        drop(self.server_buf);
+        self.client_buf.recycle()
     }
 
     fn handshake(&mut self) -> Result<(), Box<dyn Error>> {
@@ -119,6 +617,14 @@ impl TlsBenchHarness for RustlsHarness {
         Ok(())
     }
 
+    fn handshake_step(&mut self, mode: Mode) -> Result<(), Box<dyn Error>> {
+        Ok(self.process_conn(mode)?)
+    }
+
+    fn handshake_step_count(&self) -> usize {
+        self.handshake_step_count
+    }
+
     fn handshake_completed(&self) -> bool {
         !self.client_conn.is_handshaking() && !self.server_conn.is_handshaking()
     }
@@ -137,4 +643,344 @@ impl TlsBenchHarness for RustlsHarness {
             .expect("Handshake not completed")
             == TLSv1_3
     }
+
+    fn hello_retry_requested(&self) -> bool {
+        self.hello_retry_requested
+    }
+
+    /// rustls 0.21 always uses ring, which doesn't report its version
+    fn crypto_backend() -> String {
+        "ring".to_owned()
+    }
+
+    fn connection_info(&self) -> ConnectionInfo {
+        ConnectionInfo {
+            cipher_suite: self.get_negotiated_cipher_suite(),
+            tls13: self.negotiated_tls13(),
+            ec_group: self
+                .ec_group
+                .clone()
+                .expect("client hasn't received a ServerHello"),
+            signature_scheme: None,
+            alpn: self.client_conn.alpn_protocol().map(<[u8]>::to_vec),
+            resumed: self.was_resumed(),
+            // rustls only reports the certs the server sent, which end with
+            // the trust anchor if it was sent, since test anchors are
+            // self-issued
+            peer_cert_chain_len: self.client_conn.peer_certificates().map(|certs| {
+                let anchor_sent = certs
+                    .last()
+                    .map_or(true, |cert| is_self_issued(&cert.0).unwrap_or(true));
+                certs.len() + usize::from(!anchor_sent)
+            }),
+        }
+    }
+
+    fn send(&mut self, sender: Mode, data: &[u8]) -> Result<(), Box<dyn Error>> {
+        // rustls limits how much data it buffers, so it may only accept part
+        // of the data until encrypted records are written out to the buffer
+        let mut sent = 0;
+        while sent < data.len() {
+            sent += match sender {
+                Mode::Client => self.client_conn.writer().write(&data[sent..])?,
+                Mode::Server => self.server_conn.writer().write(&data[sent..])?,
+            };
+            self.process_conn(sender)?;
+        }
+        Ok(())
+    }
+
+    fn recv(&mut self, receiver: Mode, data: &mut [u8]) -> Result<(), Box<dyn Error>> {
+        let result = match receiver {
+            Mode::Client => Self::recv_conn(&mut *self.client_conn, &mut self.client_buf, data),
+            Mode::Server => Self::recv_conn(&mut *self.server_conn, &mut self.server_buf, data),
+        };
+        if let Some(err) = result
+            .as_ref()
+            .err()
+            .and_then(|err| err.downcast_ref::<rustls::Error>())
+        {
+            self.last_alert = error_alert(err);
+        }
+        result
+    }
+
+    fn shutdown(&mut self) -> Result<(), Box<dyn Error>> {
+        self.client_conn.send_close_notify();
+        self.client_conn.write_tls(&mut self.client_buf)?;
+        self.server_conn.read_tls(&mut self.server_buf)?;
+        let server_state = self.server_conn.process_new_packets()?;
+
+        self.server_conn.send_close_notify();
+        self.server_conn.write_tls(&mut self.server_buf)?;
+        self.client_conn.read_tls(&mut self.client_buf)?;
+        let client_state = self.client_conn.process_new_packets()?;
+
+        if !(server_state.peer_has_closed() && client_state.peer_has_closed()) {
+            return Err("connections did not exchange close_notify alerts".into());
+        }
+        Ok(())
+    }
+
+    fn was_resumed(&self) -> bool {
+        // rustls servers only report resumption data if they accepted the
+        // client's ticket
+        self.server_conn.received_resumption_data().is_some()
+    }
+
+    fn new_resumed(&self) -> Result<Self, Box<dyn Error>> {
+        if self.session_store.tls13_tickets() == 0 {
+            return Err("no session ticket received".into());
+        }
+        self.new_with_same_configs()
+    }
+
+    fn new_with_same_configs(&self) -> Result<Self, Box<dyn Error>> {
+        // configs are shared by cloning the Arc
+        Self::from_configs(
+            ConnectedBuffer::new(),
+            self.client_config.clone(),
+            self.server_config.clone(),
+            self.session_store.clone(),
+        )
+    }
+
+    fn round_trips(&self) -> usize {
+        self.client_buf.sent_flights()
+    }
+
+    fn bytes_sent(&self, sender: Mode) -> usize {
+        match sender {
+            Mode::Client => self.client_buf.sent_bytes(),
+            Mode::Server => self.server_buf.sent_bytes(),
+        }
+    }
+
+    fn records_sent(&self, sender: Mode) -> usize {
+        match sender {
+            Mode::Client => self.client_buf.sent_records(),
+            Mode::Server => self.server_buf.sent_records(),
+        }
+    }
+
+    fn peak_unread_bytes(&self, sender: Mode) -> usize {
+        match sender {
+            Mode::Client => self.client_buf.peak_unread(),
+            Mode::Server => self.server_buf.peak_unread(),
+        }
+    }
+
+    fn pending_data(&self, receiver: Mode) -> Vec<u8> {
+        match receiver {
+            Mode::Client => self.client_buf.peek_unread(),
+            Mode::Server => self.server_buf.peek_unread(),
+        }
+    }
+
+    fn discard_pending_data(&mut self, receiver: Mode) {
+        match receiver {
+            Mode::Client => self.client_buf.discard_unread(),
+            Mode::Server => self.server_buf.discard_unread(),
+        }
+    }
+
+    fn is_drained(&self) -> bool {
+        self.client_buf.is_drained() && self.server_buf.is_drained()
+    }
+
+    fn inject_pending_data(&mut self, receiver: Mode, data: &[u8]) {
+        match receiver {
+            Mode::Client => self.client_buf.inject(data),
+            Mode::Server => self.server_buf.inject(data),
+        }
+    }
+
+    fn corrupt_pending_data(&mut self, receiver: Mode, index: usize) {
+        match receiver {
+            Mode::Client => self.client_buf.corrupt_unread(index),
+            Mode::Server => self.server_buf.corrupt_unread(index),
+        }
+    }
+
+    fn last_alert(&self) -> Option<AlertDescription> {
+        self.last_alert
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_configs() {
+        let crypto_config = CryptoConfig {
+            cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+            ec_group: ECGroup::SECP256R1,
+        };
+        let (client_config, _) =
+            RustlsHarness::build_configs(&crypto_config, HandshakeType::ServerAuth).unwrap();
+        let (mutual_client_config, mutual_server_config) =
+            RustlsHarness::build_configs(&crypto_config, HandshakeType::MutualAuth).unwrap();
+
+        let mut harness = RustlsHarness::from_configs(
+            ConnectedBuffer::new(),
+            mutual_client_config,
+            mutual_server_config.clone(),
+            Arc::default(),
+        )
+        .unwrap();
+        harness.handshake().unwrap();
+        assert!(harness.handshake_completed());
+
+        // the server rejects a client without a cert
+        let mut harness = RustlsHarness::from_configs(
+            ConnectedBuffer::new(),
+            client_config,
+            mutual_server_config,
+            Arc::default(),
+        )
+        .unwrap();
+        assert!(harness.handshake().is_err());
+        assert!(!harness.handshake_completed());
+    }
+
+    #[test]
+    fn test_cipher_order() {
+        use CipherSuite::*;
+
+        let client_order = [AES_128_GCM_SHA256, AES_256_GCM_SHA384];
+        let server_order = [AES_256_GCM_SHA384, AES_128_GCM_SHA256];
+        for (honor_server_order, expected) in
+            [(true, AES_256_GCM_SHA384), (false, AES_128_GCM_SHA256)]
+        {
+            let mut harness = RustlsHarness::new_with_cipher_order(
+                &client_order,
+                &server_order,
+                honor_server_order,
+            )
+            .unwrap();
+            harness.handshake().unwrap();
+            assert_eq!(harness.get_negotiated_cipher_suite(), expected);
+        }
+    }
+
+    #[test]
+    fn test_custom_server_cert_verifier() {
+        struct RejectAllServerCerts;
+        impl ServerCertVerifier for RejectAllServerCerts {
+            fn verify_server_cert(
+                &self,
+                _end_entity: &Certificate,
+                _intermediates: &[Certificate],
+                _server_name: &ServerName,
+                _scts: &mut dyn Iterator<Item = &[u8]>,
+                _ocsp_response: &[u8],
+                _now: SystemTime,
+            ) -> Result<ServerCertVerified, rustls::Error> {
+                Err(CertificateError::ApplicationVerificationFailure.into())
+            }
+        }
+
+        let crypto_config = CryptoConfig {
+            cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+            ec_group: ECGroup::SECP256R1,
+        };
+
+        let mut harness = RustlsHarness::new_with_server_cert_verifier(
+            &crypto_config,
+            Arc::new(AcceptAnyServerCert),
+        )
+        .unwrap();
+        harness.handshake().unwrap();
+        assert!(harness.handshake_completed());
+
+        let mut harness = RustlsHarness::new_with_server_cert_verifier(
+            &crypto_config,
+            Arc::new(RejectAllServerCerts),
+        )
+        .unwrap();
+        assert!(harness.handshake().is_err());
+        assert!(!harness.handshake_completed());
+        assert_eq!(harness.last_alert(), Some(AlertDescription::BadCertificate));
+    }
+
+    #[test]
+    fn test_negotiated_group() {
+        use ECGroup::*;
+
+        for (client_groups, server_groups, expected) in [
+            (vec![SECP256R1], vec![SECP256R1], SECP256R1),
+            (vec![X25519], vec![X25519], X25519),
+            // the client's key share is used if the server supports it
+            (vec![X25519, SECP256R1], vec![SECP256R1, X25519], X25519),
+        ] {
+            let mut harness =
+                RustlsHarness::new_with_kx_groups(&client_groups, &server_groups).unwrap();
+            harness.handshake().unwrap();
+            assert_eq!(harness.connection_info().ec_group, expected);
+            assert!(!harness.hello_retry_requested());
+        }
+    }
+
+    #[test]
+    fn test_hello_retry_for_missing_key_share() {
+        use ECGroup::*;
+
+        let mut no_retry = RustlsHarness::new_with_kx_groups(&[SECP256R1], &[SECP256R1]).unwrap();
+        no_retry.handshake().unwrap();
+        assert!(!no_retry.hello_retry_requested());
+
+        // the server prefers secp256r1, but the client's only key share is
+        // for x25519
+        let mut retry =
+            RustlsHarness::new_with_kx_groups(&[X25519, SECP256R1], &[SECP256R1]).unwrap();
+        // the retry adds a round trip, which doesn't fit in one `handshake`
+        retry.handshake_with_timeout(2).unwrap();
+        assert!(retry.handshake_completed());
+        assert!(retry.hello_retry_requested());
+        assert_eq!(retry.connection_info().ec_group, SECP256R1);
+        assert_eq!(retry.round_trips(), no_retry.round_trips() + 1);
+        assert_eq!(
+            retry.handshake_step_count(),
+            no_retry.handshake_step_count() + 2
+        );
+
+        // a supported share avoids the retry, even if the server prefers
+        // another group
+        let mut harness =
+            RustlsHarness::new_with_kx_groups(&[X25519, SECP256R1], &[SECP256R1, X25519]).unwrap();
+        harness.handshake().unwrap();
+        assert!(!harness.hello_retry_requested());
+        assert_eq!(harness.round_trips(), no_retry.round_trips());
+    }
+
+    #[test]
+    fn test_records_sent() {
+        let crypto_config = CryptoConfig {
+            cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+            ec_group: ECGroup::SECP256R1,
+        };
+        // each handshake message is sent in its own record, and both sides
+        // send a ChangeCipherSpec for middlebox compatibility. The server
+        // also sends its session tickets once it receives the client's
+        // Finished.
+        for (handshake_type, client_records, server_records) in [
+            (HandshakeType::ServerAuth, 3, 6),
+            (HandshakeType::MutualAuth, 5, 7),
+        ] {
+            let mut harness = RustlsHarness::new(&crypto_config, handshake_type).unwrap();
+            harness.handshake().unwrap();
+            let tickets = harness.server_config.send_tls13_tickets;
+            assert_eq!(
+                harness.records_sent(Mode::Client),
+                client_records,
+                "{handshake_type:?}"
+            );
+            assert_eq!(
+                harness.records_sent(Mode::Server),
+                server_records + tickets,
+                "{handshake_type:?}"
+            );
+        }
+    }
 }
@@ -1,41 +1,113 @@
 // Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{
-    harness::{read_to_bytes, CipherSuite, CryptoConfig, ECGroup, Mode, TlsBenchHarness},
-    CA_CERT_PATH, SERVER_CERT_CHAIN_PATH, SERVER_KEY_PATH,
+use crate::harness::{
+    certs_to_pem, load_trust_store, read_to_bytes, AlertDescription, CertConfig, CipherSuite,
+    ConnectedBuffer, ConnectionInfo, CryptoConfig, ECGroup, HandshakeTimeout, HandshakeType,
+    KeyLog, Mode, ServerIdentity, TlsBenchHarness,
 };
 use s2n_tls::{
-    callbacks::VerifyHostNameCallback,
+    callbacks::{
+        SessionCache, SessionTicket, SessionTicketCallback, VerifyHostNameCallback, WallClock,
+    },
     config::{Builder, Config},
     connection::Connection,
-    enums::{Blinding, Version},
+    enums::{Blinding, ClientAuthType, MaxFragmentLength, Version},
     security::Policy,
 };
 use std::{
-    cell::UnsafeCell,
-    collections::VecDeque,
+    collections::{HashMap, VecDeque},
     error::Error,
-    ffi::c_void,
-    io::{Read, Write},
+    ffi::{c_char, c_void, CStr},
+    io::{ErrorKind, IoSlice, Read, Write},
     os::raw::c_int,
+    path::Path,
     pin::Pin,
-    task::Poll::Ready,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    task::{
+        Poll::{Pending, Ready},
+        Wake, Waker,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
+/// Security policy offering hybrid post-quantum groups for TLS1.3 key
+/// exchange, along with classical groups to fall back to
+///
+/// Hybrid groups are only negotiated if the libcrypto supports them, and are
+/// otherwise silently skipped
+pub const PQ_SECURITY_POLICY: &str = "PQ-TLS-1-2-2023-04-07";
+/// Security policy with the same classical groups as [`PQ_SECURITY_POLICY`]
+/// but no hybrid post-quantum groups
+pub const CLASSICAL_SECURITY_POLICY: &str = "default_tls13";
+
 pub struct S2NHarness {
-    // UnsafeCell is needed b/c client and server share *mut to IO buffers
     // Pin<Box<T>> is to ensure long-term *mut to IO buffers remain valid
-    client_to_server_buf: Pin<Box<UnsafeCell<VecDeque<u8>>>>,
-    server_to_client_buf: Pin<Box<UnsafeCell<VecDeque<u8>>>>,
+    client_buf: Pin<Box<ConnectedBuffer>>,
+    server_buf: Pin<Box<ConnectedBuffer>>,
     client_config: Config,
     server_config: Config,
     client_conn: Connection,
     server_conn: Connection,
     client_handshake_completed: bool,
     server_handshake_completed: bool,
+    // data read while draining a full buffer during a send, which is
+    // returned by the next recv
+    client_drained: VecDeque<u8>,
+    server_drained: VecDeque<u8>,
+    session_ticket_storage: SessionTicketStorage,
+    negotiate_polls: usize,
+    handshake_step_count: usize,
+    last_alert: Option<AlertDescription>,
+    // contexts of the key log callbacks of the configs, which must outlive
+    // the configs, so they're shared by every harness using the configs
+    key_log_contexts: Vec<Arc<KeyLog>>,
+}
+
+/// Number of calls made into and by s2n-tls, summed over both connections
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CallCounts {
+    pub poll_negotiate: usize,
+    pub recv_cb: usize,
+    pub send_cb: usize,
+}
+
+/// Transitions made while driving a handshake with
+/// [`S2NHarness::handshake_with_event_loop`], summed over both connections
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EventLoopStats {
+    /// Number of times a connection was polled
+    pub polls: usize,
+    /// Number of polls that returned Pending
+    pub pending: usize,
+    /// Number of times a connection's waker was woken
+    pub wakeups: usize,
+}
+
+/// Waker that records that its connection should be polled again
+#[derive(Default)]
+struct EventLoopWaker {
+    woken: AtomicBool,
+    wakeups: AtomicUsize,
+}
+impl Wake for EventLoopWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.woken.store(true, Ordering::Relaxed);
+        self.wakeups.fetch_add(1, Ordering::Relaxed);
+    }
 }
 
+/// Largest handshake message s2n-tls accepts, to bound the memory a peer can
+/// make it allocate. s2n-tls fixes this limit at compile time.
+pub const MAX_HANDSHAKE_MESSAGE_LEN: usize = 64 * 1024;
+
 /// Custom callback for verifying hostnames. Rustls requires checking hostnames,
 /// so this is to make a fair comparison
 struct HostNameHandler<'a> {
@@ -47,88 +119,1041 @@ impl VerifyHostNameCallback for HostNameHandler<'_> {
     }
 }
 
+/// Verifies hostnames with wildcard matching, counting how many names it is
+/// asked to verify
+///
+/// s2n-tls calls the callback for each name in the server cert until one
+/// matches, so this is more realistic than the equality check in
+/// `HostNameHandler` for certs with many names
+#[derive(Clone)]
+pub struct WildcardHostNameHandler {
+    expected_server_name: &'static str,
+    calls: Arc<AtomicUsize>,
+}
+impl WildcardHostNameHandler {
+    pub fn new(expected_server_name: &'static str) -> Self {
+        WildcardHostNameHandler {
+            expected_server_name,
+            calls: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Number of names verified so far
+    pub fn calls(&self) -> usize {
+        self.calls.load(Ordering::Relaxed)
+    }
+}
+impl VerifyHostNameCallback for WildcardHostNameHandler {
+    fn verify_host_name(&self, hostname: &str) -> bool {
+        self.calls.fetch_add(1, Ordering::Relaxed);
+        match hostname.strip_prefix("*.") {
+            // wildcards only match the leftmost label
+            Some(suffix) => self
+                .expected_server_name
+                .split_once('.')
+                .map_or(false, |(_, rest)| rest.eq_ignore_ascii_case(suffix)),
+            None => self.expected_server_name.eq_ignore_ascii_case(hostname),
+        }
+    }
+}
+
+/// Where an s2n-tls client loads the CAs it trusts from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrustStoreSource {
+    /// Only the CA, added to the trust store from PEM in memory, like a
+    /// pinned bundle
+    Pem,
+    /// Only the CA, loaded by libcrypto from the CA file
+    Location,
+    /// The system trust store, along with the CA, since the bench CA isn't
+    /// installed in the system trust store
+    System,
+}
+
+/// Key that servers encrypt session tickets with
+///
+/// Servers using the same key can resume sessions from each other's
+/// tickets, like a load-balanced fleet sharing ticket keys
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TicketKey {
+    pub name: Vec<u8>,
+    pub key: Vec<u8>,
+}
+
+impl Default for TicketKey {
+    fn default() -> Self {
+        Self {
+            name: b"bench key".to_vec(),
+            key: vec![0; 16],
+        }
+    }
+}
+
+/// Wall clock stopped at a fixed time, so that certificate validity can be
+/// checked at a chosen time instead of the real time
+struct FixedClock(SystemTime);
+impl WallClock for FixedClock {
+    fn get_time_since_epoch(&self) -> Duration {
+        self.0.duration_since(UNIX_EPOCH).unwrap()
+    }
+}
+
+/// Stores all session tickets received by the client, so that later
+/// connections can resume the session
+#[derive(Clone, Default)]
+struct SessionTicketStorage(Arc<Mutex<Vec<Vec<u8>>>>);
+impl SessionTicketCallback for SessionTicketStorage {
+    fn on_session_ticket(&self, _connection: &mut Connection, session_ticket: &SessionTicket) {
+        let mut ticket = vec![0; session_ticket.len().unwrap()];
+        session_ticket.data(&mut ticket).unwrap();
+        self.0.lock().unwrap().push(ticket);
+    }
+}
+
+/// Server-side cache of TLS1.2 sessions, keyed by session ID
+#[derive(Clone, Default)]
+struct SessionIdCache(Arc<Mutex<HashMap<Vec<u8>, Vec<u8>>>>);
+impl SessionCache for SessionIdCache {
+    fn store(&self, key: &[u8], value: &[u8], _ttl: Duration) {
+        self.0.lock().unwrap().insert(key.to_vec(), value.to_vec());
+    }
+
+    fn retrieve(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.0.lock().unwrap().get(key).cloned()
+    }
+
+    fn delete(&self, key: &[u8]) {
+        self.0.lock().unwrap().remove(key);
+    }
+}
+
 impl S2NHarness {
     /// Unsafe callback for custom IO C API
     ///
     /// s2n-tls IO is usually used with file descriptors to a TCP socket, but we
     /// reduce overhead and outside noise with a local buffer for benchmarking
     unsafe extern "C" fn send_cb(context: *mut c_void, data: *const u8, len: u32) -> c_int {
-        let context = &mut *(context as *mut VecDeque<u8>);
+        let context = &mut *(context as *mut ConnectedBuffer);
         let data = core::slice::from_raw_parts(data, len as _);
-        context.write(data).unwrap() as _
+        match context.write(data) {
+            Ok(len) => len as _,
+            // bounded ConnectedBuffers return WouldBlock when they are full
+            Err(err) if err.kind() == ErrorKind::WouldBlock => {
+                errno::set_errno(errno::Errno(libc::EWOULDBLOCK));
+                -1
+            }
+            Err(_) => -1,
+        }
     }
 
     /// Unsafe callback for custom IO C API
     unsafe extern "C" fn recv_cb(context: *mut c_void, data: *mut u8, len: u32) -> c_int {
-        let context = &mut *(context as *mut VecDeque<u8>);
+        let context = &mut *(context as *mut ConnectedBuffer);
         let data = core::slice::from_raw_parts_mut(data, len as _);
-        context.flush().unwrap();
-        let len = context.read(data).unwrap();
-        if len == 0 {
-            errno::set_errno(errno::Errno(libc::EWOULDBLOCK));
-            -1
-        } else {
-            len as _
+        match context.read(data) {
+            Ok(len) => len as _,
+            // ConnectedBuffer returns WouldBlock when there is no data to read
+            Err(_) => {
+                errno::set_errno(errno::Errno(libc::EWOULDBLOCK));
+                -1
+            }
         }
     }
 
-    fn create_config(mode: Mode, crypto_config: &CryptoConfig) -> Result<Config, Box<dyn Error>> {
-        let security_policy = match (&crypto_config.cipher_suite, &crypto_config.ec_group) {
-            (CipherSuite::AES_128_GCM_SHA256, ECGroup::SECP256R1) => "20230317",
-            (CipherSuite::AES_256_GCM_SHA384, ECGroup::SECP256R1) => "20190802",
-            (CipherSuite::AES_128_GCM_SHA256, ECGroup::X25519) => "default_tls13",
-            (CipherSuite::AES_256_GCM_SHA384, ECGroup::X25519) => "20190801",
+    /// Unsafe callback for the key log C API
+    unsafe extern "C" fn key_log_cb(
+        context: *mut c_void,
+        _conn: *mut s2n_tls::ffi::s2n_connection,
+        logline: *mut u8,
+        len: usize,
+    ) -> c_int {
+        let context = &*(context as *const KeyLog);
+        let line = core::slice::from_raw_parts(logline, len);
+        context.log_line(String::from_utf8_lossy(line).into_owned());
+        0
+    }
+
+    /// Log the secrets derived by connections using `builder` to `key_log`
+    ///
+    /// Returns the context of the callback, which must be kept alive as long
+    /// as the config built from `builder`.
+    fn set_key_log(builder: &mut Builder, key_log: &KeyLog) -> Result<Arc<KeyLog>, Box<dyn Error>> {
+        let context = Arc::new(key_log.clone());
+        unsafe {
+            builder.set_key_log_callback(
+                Some(Self::key_log_cb),
+                Arc::as_ptr(&context) as *mut c_void,
+            )?
+        };
+        Ok(context)
+    }
+
+    /// Build the client and server configs used by [`TlsBenchHarness::new`],
+    /// without initializing connections
+    pub fn build_configs(
+        crypto_config: &CryptoConfig,
+        handshake_type: HandshakeType,
+    ) -> Result<(Config, Config), Box<dyn Error>> {
+        let session_ticket_storage = SessionTicketStorage::default();
+        Ok((
+            Self::create_config(
+                Mode::Client,
+                crypto_config,
+                handshake_type,
+                &session_ticket_storage,
+            )?,
+            Self::create_config(
+                Mode::Server,
+                crypto_config,
+                handshake_type,
+                &session_ticket_storage,
+            )?,
+        ))
+    }
+
+    fn create_config(
+        mode: Mode,
+        crypto_config: &CryptoConfig,
+        handshake_type: HandshakeType,
+        session_ticket_storage: &SessionTicketStorage,
+    ) -> Result<Config, Box<dyn Error>> {
+        let builder = Self::create_builder(
+            mode,
+            crypto_config,
+            handshake_type,
+            &CertConfig::default(),
+            session_ticket_storage,
+        )?;
+        Ok(builder.build()?)
+    }
+
+    fn create_builder(
+        mode: Mode,
+        crypto_config: &CryptoConfig,
+        handshake_type: HandshakeType,
+        cert_config: &CertConfig,
+        session_ticket_storage: &SessionTicketStorage,
+    ) -> Result<Builder, Box<dyn Error>> {
+        let mut builder = Self::create_builder_without_host_callback(
+            mode,
+            crypto_config,
+            handshake_type,
+            cert_config,
+            session_ticket_storage,
+        )?;
+        builder.set_verify_host_callback(HostNameHandler {
+            expected_server_name: "localhost",
+        })?;
+        Ok(builder)
+    }
+
+    /// Without a callback, s2n-tls verifies hostnames against the server name
+    /// set on the connection
+    fn create_builder_without_host_callback(
+        mode: Mode,
+        crypto_config: &CryptoConfig,
+        handshake_type: HandshakeType,
+        cert_config: &CertConfig,
+        session_ticket_storage: &SessionTicketStorage,
+    ) -> Result<Builder, Box<dyn Error>> {
+        let security_policy = if cfg!(feature = "fips") {
+            match (&crypto_config.cipher_suite, &crypto_config.ec_group) {
+                (CipherSuite::AES_128_GCM_SHA256, ECGroup::SECP256R1) => "20230317",
+                // rfc9151 is the only FIPS policy preferring AES_256_GCM_SHA384
+                // in TLS1.3, but it only offers secp384r1 for key exchange
+                (CipherSuite::AES_256_GCM_SHA384, ECGroup::SECP256R1) => {
+                    return Err("no FIPS policy offers AES_256_GCM_SHA384 with SECP256R1".into())
+                }
+                (_, ECGroup::X25519) => return Err("X25519 is not FIPS-approved".into()),
+            }
+        } else {
+            match (&crypto_config.cipher_suite, &crypto_config.ec_group) {
+                (CipherSuite::AES_128_GCM_SHA256, ECGroup::SECP256R1) => "20230317",
+                (CipherSuite::AES_256_GCM_SHA384, ECGroup::SECP256R1) => "20190802",
+                (CipherSuite::AES_128_GCM_SHA256, ECGroup::X25519) => "default_tls13",
+                (CipherSuite::AES_256_GCM_SHA384, ECGroup::X25519) => "20190801",
+            }
         };
 
         let mut builder = Builder::new();
         builder.set_security_policy(&Policy::from_version(security_policy)?)?;
 
+        let client_auth_type = match handshake_type {
+            HandshakeType::ServerAuth => ClientAuthType::None,
+            HandshakeType::MutualAuth => ClientAuthType::Required,
+            HandshakeType::OptionalClientAuth => ClientAuthType::Optional,
+        };
+        builder.set_client_auth_type(client_auth_type)?;
+
         match mode {
-            Mode::Server => builder.load_pem(
-                read_to_bytes(SERVER_CERT_CHAIN_PATH).as_slice(),
-                read_to_bytes(SERVER_KEY_PATH).as_slice(),
-            )?,
+            Mode::Server => {
+                // s2n-tls only loads PEM, so a PKCS#12 identity is re-encoded
+                let (cert_chain, key) = ServerIdentity::load(cert_config)?.to_pem();
+                builder
+                    .load_pem(&cert_chain, &key)?
+                    .trust_pem(&read_to_bytes(&cert_config.client_ca_cert))?
+            }
             Mode::Client => builder
-                .trust_pem(read_to_bytes(CA_CERT_PATH).as_slice())?
-                .set_verify_host_callback(HostNameHandler {
-                    expected_server_name: "localhost",
-                })?,
+                .trust_pem(&Self::trusted_cas_pem(&cert_config.ca_cert)?)?
+                .set_session_ticket_callback(session_ticket_storage.clone())?,
         };
 
-        Ok(builder.build()?)
+        if mode == Mode::Client && handshake_type == HandshakeType::MutualAuth {
+            builder.load_pem(
+                &read_to_bytes(&cert_config.client_cert),
+                &read_to_bytes(&cert_config.client_key),
+            )?;
+        }
+
+        Ok(builder)
+    }
+
+    /// Read the trust store at `path` as PEM, since s2n-tls only loads PEM
+    /// trust stores
+    fn trusted_cas_pem(path: &str) -> Result<Arc<[u8]>, Box<dyn Error>> {
+        if path.ends_with(".der") {
+            Ok(certs_to_pem(&load_trust_store(path)?).into())
+        } else {
+            Ok(read_to_bytes(path))
+        }
+    }
+
+    /// Have a server issue session tickets encrypted with `ticket_key`
+    ///
+    /// Servers only issue tickets if a constructor adds a key, so that full
+    /// handshakes aren't followed by a ticket that nothing resumes.
+    fn add_ticket_key(builder: &mut Builder, ticket_key: &TicketKey) -> Result<(), Box<dyn Error>> {
+        builder.add_session_ticket_key(&ticket_key.name, &ticket_key.key, SystemTime::now())?;
+        Ok(())
+    }
+
+    /// Initialize connections whose configs are built from the same builders
+    /// as [`TlsBenchHarness::new_with_certs`], after `builder_hook` has
+    /// customized the builder for each mode (pre-handshake)
+    fn new_with_builder_hook(
+        crypto_config: &CryptoConfig,
+        handshake_type: HandshakeType,
+        cert_config: &CertConfig,
+        mut builder_hook: impl FnMut(Mode, &mut Builder) -> Result<(), Box<dyn Error>>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let session_ticket_storage = SessionTicketStorage::default();
+        let mut create_config = |mode| -> Result<Config, Box<dyn Error>> {
+            let mut builder = Self::create_builder(
+                mode,
+                crypto_config,
+                handshake_type,
+                cert_config,
+                &session_ticket_storage,
+            )?;
+            builder_hook(mode, &mut builder)?;
+            Ok(builder.build()?)
+        };
+        let client_config = create_config(Mode::Client)?;
+        let server_config = create_config(Mode::Server)?;
+
+        Self::from_configs(
+            ConnectedBuffer::new(),
+            client_config,
+            server_config,
+            session_ticket_storage,
+        )
+    }
+
+    /// Initialize connections where the server sends `ticket_count` session
+    /// tickets after the handshake
+    pub fn new_with_ticket_count(
+        crypto_config: &CryptoConfig,
+        ticket_count: u8,
+    ) -> Result<Self, Box<dyn Error>> {
+        Self::new_with_builder_hook(
+            crypto_config,
+            HandshakeType::ServerAuth,
+            &CertConfig::default(),
+            |mode, builder| {
+                if mode == Mode::Server {
+                    Self::add_ticket_key(builder, &TicketKey::default())?;
+                    builder.set_initial_ticket_count(ticket_count)?;
+                }
+                Ok(())
+            },
+        )
+    }
+
+    /// Initialize connections where the server encrypts session tickets
+    /// with `ticket_key` instead of the default key
+    pub fn new_with_ticket_key(
+        crypto_config: &CryptoConfig,
+        ticket_key: &TicketKey,
+    ) -> Result<Self, Box<dyn Error>> {
+        Self::new_with_builder_hook(
+            crypto_config,
+            HandshakeType::ServerAuth,
+            &CertConfig::default(),
+            |mode, builder| {
+                if mode == Mode::Server {
+                    Self::add_ticket_key(builder, ticket_key)?;
+                }
+                Ok(())
+            },
+        )
+    }
+
+    /// Get the last session ticket received by the client, serialized so it
+    /// can be resumed by another harness with [`Self::set_session_ticket`]
+    pub fn last_session_ticket(&self) -> Option<Vec<u8>> {
+        self.session_ticket_storage
+            .0
+            .lock()
+            .unwrap()
+            .last()
+            .cloned()
+    }
+
+    /// Have the client try to resume the session in `ticket`, which is lost
+    /// on [`Self::reset`]
+    pub fn set_session_ticket(&mut self, ticket: &[u8]) -> Result<(), Box<dyn Error>> {
+        self.client_conn.set_session_ticket(ticket)?;
+        Ok(())
+    }
+
+    /// Initialize connections where the server uses the certs in
+    /// `cert_config` and the client verifies hostnames with `host_name_handler`
+    pub fn new_with_host_name_handler<T: 'static + VerifyHostNameCallback>(
+        crypto_config: &CryptoConfig,
+        cert_config: &CertConfig,
+        host_name_handler: T,
+    ) -> Result<Self, Box<dyn Error>> {
+        let mut host_name_handler = Some(host_name_handler);
+        Self::new_with_builder_hook(
+            crypto_config,
+            HandshakeType::ServerAuth,
+            cert_config,
+            |mode, builder| {
+                if let (Mode::Client, Some(handler)) = (mode, host_name_handler.take()) {
+                    builder.set_verify_host_callback(handler)?;
+                }
+                Ok(())
+            },
+        )
+    }
+
+    /// Initialize connections where the server uses the certs in
+    /// `cert_config` and the client verifies hostnames with s2n-tls's
+    /// built-in matching instead of a callback into Rust
+    ///
+    /// The server name is set on the client connection, so it's lost on
+    /// [`Self::reset`].
+    pub fn new_with_builtin_host_verification(
+        crypto_config: &CryptoConfig,
+        cert_config: &CertConfig,
+    ) -> Result<Self, Box<dyn Error>> {
+        let session_ticket_storage = SessionTicketStorage::default();
+        let client_builder = Self::create_builder_without_host_callback(
+            Mode::Client,
+            crypto_config,
+            HandshakeType::ServerAuth,
+            cert_config,
+            &session_ticket_storage,
+        )?;
+        let server_builder = Self::create_builder(
+            Mode::Server,
+            crypto_config,
+            HandshakeType::ServerAuth,
+            cert_config,
+            &session_ticket_storage,
+        )?;
+
+        let mut harness = Self::from_configs(
+            ConnectedBuffer::new(),
+            client_builder.build()?,
+            server_builder.build()?,
+            session_ticket_storage,
+        )?;
+        harness.client_conn.set_server_name("localhost")?;
+        Ok(harness)
+    }
+
+    /// Initialize TLS1.2 connections where the server caches sessions by
+    /// session ID instead of issuing session tickets
+    pub fn new_with_session_cache() -> Result<Self, Box<dyn Error>> {
+        // TLS1.2 is the highest version supported by this policy, which
+        // unlike the default policy has ECDSA cipher suites for the test certs
+        let security_policy = Policy::from_version("20190214_gcm")?;
+        let crypto_config = CryptoConfig {
+            cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+            ec_group: ECGroup::SECP256R1,
+        };
+        Self::new_with_builder_hook(
+            &crypto_config,
+            HandshakeType::ServerAuth,
+            &CertConfig::default(),
+            |mode, builder| {
+                builder.set_security_policy(&security_policy)?;
+                if mode == Mode::Server {
+                    // cache entries are encrypted with the ticket keys, which
+                    // s2n-tls drops when tickets are disabled without a cache
+                    Self::add_ticket_key(builder, &TicketKey::default())?;
+                    builder.set_session_cache(SessionIdCache::default())?;
+                }
+                builder.enable_session_tickets(false)?;
+                Ok(())
+            },
+        )
+    }
+
+    /// Initialize connections where the client and server use the named
+    /// security policies instead of ones chosen from a `CryptoConfig`
+    pub fn new_with_security_policies(
+        client_policy: &str,
+        server_policy: &str,
+    ) -> Result<Self, Box<dyn Error>> {
+        let crypto_config = CryptoConfig {
+            cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+            ec_group: ECGroup::SECP256R1,
+        };
+        Self::new_with_builder_hook(
+            &crypto_config,
+            HandshakeType::ServerAuth,
+            &CertConfig::default(),
+            |mode, builder| {
+                let policy = match mode {
+                    Mode::Client => client_policy,
+                    Mode::Server => server_policy,
+                };
+                builder.set_security_policy(&Policy::from_version(policy)?)?;
+                Ok(())
+            },
+        )
+    }
+
+    /// Get the hybrid post-quantum group negotiated by the handshake, or
+    /// `None` if the key exchange was classical
+    pub fn kem_group(&self) -> Option<&str> {
+        match self.client_conn.kem_group_name().unwrap() {
+            "NONE" => None,
+            group => Some(group),
+        }
+    }
+
+    /// Initialize `count` harnesses that all resume the last session ticket
+    /// received by the client, like a connection pool resuming from one
+    /// shared session store
+    ///
+    /// The harnesses share this harness's configs and session ticket storage,
+    /// so tickets they receive are added to the same store. s2n-tls servers
+    /// don't limit how often a ticket is used, so every resumption succeeds.
+    pub fn new_resumed_pool(&self, count: usize) -> Result<Vec<Self>, Box<dyn Error>> {
+        let ticket = self
+            .last_session_ticket()
+            .ok_or("no session ticket received")?;
+        (0..count)
+            .map(|_| -> Result<Self, Box<dyn Error>> {
+                let mut harness = self.share_configs()?;
+                harness.client_conn.set_session_ticket(&ticket)?;
+                Ok(harness)
+            })
+            .collect()
+    }
+
+    /// Initialize connections that resume the TLS1.2 session of this harness
+    /// by session ID, using the same configs (pre-handshake)
+    pub fn new_resumed_by_session_id(&mut self) -> Result<Self, Box<dyn Error>> {
+        let session = self.client_conn.session()?;
+        let mut harness = self.share_configs()?;
+        harness.client_conn.set_session_ticket(&session)?;
+        Ok(harness)
+    }
+
+    /// Initialize connections using the same configs and session ticket
+    /// storage as this harness (pre-handshake)
+    fn share_configs(&self) -> Result<Self, Box<dyn Error>> {
+        // cloning a Config only increments its reference count
+        let mut harness = Self::from_configs(
+            ConnectedBuffer::new(),
+            self.client_config.clone(),
+            self.server_config.clone(),
+            self.session_ticket_storage.clone(),
+        )?;
+        harness.key_log_contexts = self.key_log_contexts.clone();
+        Ok(harness)
+    }
+
+    /// Initialize connections that apply `blinding` to handshake errors,
+    /// instead of the default self-service blinding
+    ///
+    /// With built-in blinding, s2n-tls sleeps for 10 to 30 seconds in the
+    /// call that fails, so a failed `handshake` only returns after the delay
+    pub fn new_with_blinding(
+        crypto_config: &CryptoConfig,
+        cert_config: &CertConfig,
+        blinding: Blinding,
+    ) -> Result<Self, Box<dyn Error>> {
+        let mut harness =
+            Self::new_with_certs(crypto_config, HandshakeType::ServerAuth, cert_config)?;
+        harness.client_conn.set_blinding(blinding)?;
+        harness.server_conn.set_blinding(blinding)?;
+        Ok(harness)
+    }
+
+    /// Initialize connections where the client asks the server to limit the
+    /// plaintext of each record to `max_fragment_length`, and the server
+    /// accepts
+    pub fn new_with_max_fragment_length(
+        crypto_config: &CryptoConfig,
+        max_fragment_length: MaxFragmentLength,
+    ) -> Result<Self, Box<dyn Error>> {
+        Self::new_with_builder_hook(
+            crypto_config,
+            HandshakeType::ServerAuth,
+            &CertConfig::default(),
+            |mode, builder| {
+                match mode {
+                    Mode::Client => builder.send_max_fragment_length(max_fragment_length)?,
+                    Mode::Server => builder.accept_max_fragment_length()?,
+                };
+                Ok(())
+            },
+        )
+    }
+
+    /// Initialize connections that buffer up to `send_buffer_size` bytes of
+    /// records before writing them, so that one write can carry several
+    /// records
+    pub fn new_with_send_buffer_size(
+        crypto_config: &CryptoConfig,
+        send_buffer_size: u32,
+    ) -> Result<Self, Box<dyn Error>> {
+        Self::new_with_builder_hook(
+            crypto_config,
+            HandshakeType::ServerAuth,
+            &CertConfig::default(),
+            |_, builder| {
+                builder.set_send_buffer_size(send_buffer_size)?;
+                Ok(())
+            },
+        )
+    }
+
+    /// Initialize connections where the client sends the extensions a browser
+    /// would: SNI, ALPN, and status requests for OCSP and signed certificate
+    /// timestamps, in addition to the ones s2n-tls always sends
+    ///
+    /// s2n-tls clients only send a key share for their most preferred group,
+    /// and don't support certificate compression, so neither can be added.
+    /// The server name is set on the client connection, so it's lost on
+    /// [`Self::reset`].
+    pub fn new_with_browser_extensions(
+        crypto_config: &CryptoConfig,
+    ) -> Result<Self, Box<dyn Error>> {
+        const PROTOCOLS: [&[u8]; 2] = [b"h2", b"http/1.1"];
+
+        let mut harness = Self::new_with_builder_hook(
+            crypto_config,
+            HandshakeType::ServerAuth,
+            &CertConfig::default(),
+            |mode, builder| {
+                builder.set_application_protocol_preference(PROTOCOLS)?;
+                if mode == Mode::Client {
+                    builder.enable_ocsp()?.enable_certificate_transparency()?;
+                }
+                Ok(())
+            },
+        )?;
+        harness.client_conn.set_server_name("localhost")?;
+        Ok(harness)
+    }
+
+    /// Send application data from several application writes at once, so
+    /// that s2n-tls coalesces it into as few records as possible instead of
+    /// sending at least one record per write
+    /// Must be called after handshake has completed
+    pub fn send_vectored(&mut self, sender: Mode, data: &[&[u8]]) -> Result<(), Box<dyn Error>> {
+        let receiver = match sender {
+            Mode::Client => Mode::Server,
+            Mode::Server => Mode::Client,
+        };
+
+        let mut remaining = data.to_vec();
+        while !remaining.is_empty() {
+            let bufs: Vec<IoSlice> = remaining.iter().map(|buf| IoSlice::new(buf)).collect();
+            match self.conn_mut(sender).poll_sendv(&bufs) {
+                // empty buffers are dropped without sending anything
+                Ready(Ok(0)) if remaining.iter().any(|buf| !buf.is_empty()) => {
+                    return Err("connection closed before all data was sent".into())
+                }
+                Ready(len) => {
+                    // drop the buffers that were sent in full, and the sent
+                    // part of the first one that wasn't
+                    let mut len = len?;
+                    while let Some(first) = remaining.first_mut() {
+                        if len < first.len() {
+                            *first = &first[len..];
+                            break;
+                        }
+                        len -= first.len();
+                        remaining.remove(0);
+                    }
+                }
+                Pending => self.drain(receiver)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Hold back the records written by `sender` until [`Self::flush`], like
+    /// a socket that buffers writes, or pass them straight to the peer
+    ///
+    /// s2n-tls always writes the records for a send before returning, so
+    /// this defers flushing in the transport instead. The buffers must be
+    /// unbounded, since the peer can't drain records that are held back.
+    pub fn set_deferred_flush(&mut self, sender: Mode, deferred: bool) {
+        match sender {
+            Mode::Client => self.client_buf.set_deferred_flush(deferred),
+            Mode::Server => self.server_buf.set_deferred_flush(deferred),
+        }
+    }
+
+    /// Make the records held back for `sender` readable by the peer
+    pub fn flush(&mut self, sender: Mode) -> Result<(), Box<dyn Error>> {
+        match sender {
+            Mode::Client => self.client_buf.flush()?,
+            Mode::Server => self.server_buf.flush()?,
+        }
+        Ok(())
+    }
+
+    /// Get the number of flushes by `sender` that made records readable by
+    /// the peer, and the number of writes to the transport
+    pub fn flush_and_write_calls(&self, sender: Mode) -> (usize, usize) {
+        let buf = match sender {
+            Mode::Client => &self.client_buf,
+            Mode::Server => &self.server_buf,
+        };
+        (buf.flush_calls(), buf.write_calls())
+    }
+
+    /// Initialize connections that share an external PSK, so the handshake
+    /// authenticates with the PSK instead of certificates and signatures
+    ///
+    /// s2n-tls only supports the psk_dhe_ke mode, so the handshake still
+    /// performs key agreement with `crypto_config.ec_group`
+    pub fn new_with_psk(
+        crypto_config: &CryptoConfig,
+        identity: &[u8],
+        secret: &[u8],
+    ) -> Result<Self, Box<dyn Error>> {
+        let mut harness = Self::new(crypto_config, HandshakeType::ServerAuth)?;
+        harness.client_conn.append_psk(identity, secret)?;
+        harness.server_conn.append_psk(identity, secret)?;
+        Ok(harness)
+    }
+
+    /// Initialize connections where the client validates the server's
+    /// certificates as of `validation_time` instead of the current time
+    pub fn new_with_validation_time(
+        crypto_config: &CryptoConfig,
+        validation_time: SystemTime,
+    ) -> Result<Self, Box<dyn Error>> {
+        Self::new_with_clocks(
+            crypto_config,
+            HandshakeType::ServerAuth,
+            Some(validation_time),
+            None,
+        )
+    }
+
+    /// Initialize connections where the client's and server's clocks are
+    /// fixed at `client_time` and `server_time`, or use the current time if
+    /// `None`, to simulate clock skew between them
+    ///
+    /// s2n-tls uses the wall clock of the config for validation, so each side
+    /// validates its peer's certificates as of its own clock.
+    pub fn new_with_clocks(
+        crypto_config: &CryptoConfig,
+        handshake_type: HandshakeType,
+        client_time: Option<SystemTime>,
+        server_time: Option<SystemTime>,
+    ) -> Result<Self, Box<dyn Error>> {
+        Self::new_with_builder_hook(
+            crypto_config,
+            handshake_type,
+            &CertConfig::default(),
+            |mode, builder| {
+                let time = match mode {
+                    Mode::Client => client_time,
+                    Mode::Server => server_time,
+                };
+                if let Some(time) = time {
+                    builder.set_wall_clock(FixedClock(time))?;
+                }
+                Ok(())
+            },
+        )
+    }
+
+    /// Initialize connections where the client loads the CAs it trusts from
+    /// `trust_store_source`
+    pub fn new_with_trust_store_source(
+        crypto_config: &CryptoConfig,
+        trust_store_source: TrustStoreSource,
+    ) -> Result<Self, Box<dyn Error>> {
+        let cert_config = CertConfig::default();
+        Self::new_with_builder_hook(
+            crypto_config,
+            HandshakeType::ServerAuth,
+            &cert_config,
+            |mode, builder| {
+                if mode == Mode::Server {
+                    return Ok(());
+                }
+                // system certs are otherwise loaded when the config is built
+                match trust_store_source {
+                    TrustStoreSource::Pem => {
+                        builder
+                            .with_system_certs(false)?
+                            .wipe_trust_store()?
+                            .trust_pem(&Self::trusted_cas_pem(&cert_config.ca_cert)?)?;
+                    }
+                    TrustStoreSource::Location => {
+                        builder
+                            .with_system_certs(false)?
+                            .wipe_trust_store()?
+                            .trust_location(Some(Path::new(&cert_config.ca_cert)), None)?;
+                    }
+                    TrustStoreSource::System => {
+                        builder.with_system_certs(true)?;
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+
+    /// Get the longest delay that either connection still requires before
+    /// it can be closed, which is only nonzero after an error under
+    /// self-service blinding
+    pub fn remaining_blinding_delay(&self) -> Result<Duration, Box<dyn Error>> {
+        Ok(self
+            .client_conn
+            .remaining_blinding_delay()?
+            .max(self.server_conn.remaining_blinding_delay()?))
+    }
+
+    /// Get number of session tickets received by the client and their total
+    /// size in bytes
+    pub fn stored_tickets(&self) -> (usize, usize) {
+        let tickets = self.session_ticket_storage.0.lock().unwrap();
+        (tickets.len(), tickets.iter().map(Vec::len).sum())
+    }
+
+    /// Wipe both connections so they can run a new handshake, reusing their
+    /// memory like a connection pool would
+    ///
+    /// The IO buffers are cleared and all per-connection state is lost,
+    /// including settings made by constructors other than [`Self::new`]
+    pub fn reset(&mut self) -> Result<(), Box<dyn Error>> {
+        self.client_conn.wipe()?;
+        self.server_conn.wipe()?;
+        self.reinit_conns()
+    }
+
+    /// Replace both connections with new connections using the same configs,
+    /// for comparison with [`Self::reset`]
+    pub fn reset_with_new_connections(&mut self) -> Result<(), Box<dyn Error>> {
+        self.client_conn = Connection::new_client();
+        self.server_conn = Connection::new_server();
+        self.reinit_conns()
+    }
+
+    /// Clear all state of previous handshakes and set up the connections
+    fn reinit_conns(&mut self) -> Result<(), Box<dyn Error>> {
+        self.client_buf.reset();
+        self.server_buf.reset();
+        self.client_handshake_completed = false;
+        self.server_handshake_completed = false;
+        self.client_drained.clear();
+        self.server_drained.clear();
+        self.negotiate_polls = 0;
+        self.handshake_step_count = 0;
+        self.last_alert = None;
+        self.init_conn(Mode::Client)?;
+        self.init_conn(Mode::Server)
+    }
+
+    /// Set up connections with existing configs and custom IO
+    fn from_configs(
+        client_buf: ConnectedBuffer,
+        client_config: Config,
+        server_config: Config,
+        session_ticket_storage: SessionTicketStorage,
+    ) -> Result<Self, Box<dyn Error>> {
+        let server_buf = client_buf.clone_inverse();
+
+        let mut harness = Self {
+            client_buf: Box::pin(client_buf),
+            server_buf: Box::pin(server_buf),
+            client_config,
+            server_config,
+            client_conn: Connection::new_client(),
+            server_conn: Connection::new_server(),
+            client_handshake_completed: false,
+            server_handshake_completed: false,
+            client_drained: VecDeque::new(),
+            server_drained: VecDeque::new(),
+            session_ticket_storage,
+            negotiate_polls: 0,
+            handshake_step_count: 0,
+            last_alert: None,
+            key_log_contexts: Vec::new(),
+        };
+
+        harness.init_conn(Mode::Client)?;
+        harness.init_conn(Mode::Server)?;
+
+        Ok(harness)
     }
 
     /// Set up connections with config and custom IO
     fn init_conn(&mut self, mode: Mode) -> Result<(), Box<dyn Error>> {
-        let client_to_server_ptr = self.client_to_server_buf.get() as *mut c_void;
-        let server_to_client_ptr = self.server_to_client_buf.get() as *mut c_void;
-        let (read_ptr, write_ptr, config, conn) = match mode {
+        let (buf, config, conn) = match mode {
             Mode::Client => (
-                server_to_client_ptr,
-                client_to_server_ptr,
+                &mut self.client_buf,
                 &self.client_config,
                 &mut self.client_conn,
             ),
             Mode::Server => (
-                client_to_server_ptr,
-                server_to_client_ptr,
+                &mut self.server_buf,
                 &self.server_config,
                 &mut self.server_conn,
             ),
         };
+        let buf_ptr = &mut **buf as *mut ConnectedBuffer as *mut c_void;
 
         conn.set_blinding(Blinding::SelfService)?
             .set_config(config.clone())?
             .set_send_callback(Some(Self::send_cb))?
             .set_receive_callback(Some(Self::recv_cb))?;
         unsafe {
-            conn.set_send_context(write_ptr)?
-                .set_receive_context(read_ptr)?;
+            conn.set_send_context(buf_ptr)?
+                .set_receive_context(buf_ptr)?;
         }
 
         Ok(())
     }
 
+    /// Get number of calls made so far, including during the handshake
+    pub fn call_counts(&self) -> CallCounts {
+        CallCounts {
+            poll_negotiate: self.negotiate_polls,
+            recv_cb: self.client_buf.read_calls() + self.server_buf.read_calls(),
+            send_cb: self.client_buf.write_calls() + self.server_buf.write_calls(),
+        }
+    }
+
+    /// Drive the handshake like a readiness-based event loop, where a
+    /// connection is only polled after its waker is woken
+    ///
+    /// The client is polled first. Each connection's waker is woken when its
+    /// peer writes data to it, as a reactor would on socket readiness, or by
+    /// s2n-tls itself when an async callback completes. Returns
+    /// [`HandshakeTimeout`] if the handshake needs more than `max_polls`
+    /// polls, or an error if no connection is woken before it completes.
+    pub fn handshake_with_event_loop(
+        &mut self,
+        max_polls: usize,
+    ) -> Result<EventLoopStats, Box<dyn Error>> {
+        let client_waker = Arc::new(EventLoopWaker::default());
+        let server_waker = Arc::new(EventLoopWaker::default());
+        self.client_conn
+            .set_waker(Some(&Waker::from(client_waker.clone())))?;
+        self.server_conn
+            .set_waker(Some(&Waker::from(server_waker.clone())))?;
+        let waker = |mode| match mode {
+            Mode::Client => &client_waker,
+            Mode::Server => &server_waker,
+        };
+
+        let mut stats = EventLoopStats {
+            polls: 0,
+            pending: 0,
+            wakeups: 0,
+        };
+        let mut ready = VecDeque::from([Mode::Client]);
+        while let Some(mode) = ready.pop_front() {
+            if stats.polls == max_polls {
+                return Err(HandshakeTimeout {
+                    max_steps: max_polls,
+                }
+                .into());
+            }
+            let peer = match mode {
+                Mode::Client => Mode::Server,
+                Mode::Server => Mode::Client,
+            };
+            let unread_by_peer = self.pending_data(peer).len();
+
+            stats.polls += 1;
+            self.handshake_conn(mode)?;
+            let handshake_completed = match mode {
+                Mode::Client => self.client_handshake_completed,
+                Mode::Server => self.server_handshake_completed,
+            };
+            if !handshake_completed {
+                stats.pending += 1;
+            }
+
+            if self.pending_data(peer).len() > unread_by_peer {
+                waker(peer).wake_by_ref();
+            }
+            for mode in [Mode::Client, Mode::Server] {
+                if waker(mode).woken.swap(false, Ordering::Relaxed) && !ready.contains(&mode) {
+                    ready.push_back(mode);
+                }
+            }
+        }
+
+        stats.wakeups = client_waker.wakeups.load(Ordering::Relaxed)
+            + server_waker.wakeups.load(Ordering::Relaxed);
+        if !self.handshake_completed() {
+            return Err("no connection woken before the handshake completed".into());
+        }
+        Ok(stats)
+    }
+
+    /// Wrap one connection in a [`TlsStream`] to send and receive
+    /// application data through [`Read`] and [`Write`]
+    ///
+    /// Must be called after the handshake has completed
+    pub fn stream(&mut self, mode: Mode) -> TlsStream<'_> {
+        TlsStream {
+            harness: self,
+            mode,
+        }
+    }
+
+    fn conn_mut(&mut self, mode: Mode) -> &mut Connection {
+        match mode {
+            Mode::Client => &mut self.client_conn,
+            Mode::Server => &mut self.server_conn,
+        }
+    }
+
+    /// Read all data currently available to one connection, freeing space
+    /// in its buffer for the peer to send more
+    fn drain(&mut self, receiver: Mode) -> Result<(), s2n_tls::error::Error> {
+        let (conn, drained) = match receiver {
+            Mode::Client => (&mut self.client_conn, &mut self.client_drained),
+            Mode::Server => (&mut self.server_conn, &mut self.server_drained),
+        };
+        let mut buf = [0; 4096];
+        loop {
+            match conn.poll_recv(&mut buf) {
+                Ready(Ok(0)) | Pending => return Ok(()),
+                Ready(len) => drained.extend(&buf[..len?]),
+            }
+        }
+    }
+
     /// Handshake step for one connection
     fn handshake_conn(&mut self, mode: Mode) -> Result<(), Box<dyn Error>> {
         let (conn, handshake_completed) = match mode {
@@ -136,8 +1161,18 @@ impl S2NHarness {
             Mode::Server => (&mut self.server_conn, &mut self.server_handshake_completed),
         };
 
+        self.negotiate_polls += 1;
+        if !*handshake_completed {
+            self.handshake_step_count += 1;
+        }
         if let Ready(res) = conn.poll_negotiate() {
-            res?;
+            if let Err(err) = res {
+                self.last_alert = conn
+                    .alert()
+                    .map(AlertDescription::from)
+                    .or(error_alert(&err));
+                return Err(err.into());
+            }
             *handshake_completed = true;
         } else {
             *handshake_completed = false;
@@ -146,31 +1181,204 @@ impl S2NHarness {
     }
 }
 
+/// One connection of an [`S2NHarness`], used as an IO object
+///
+/// Like [`TlsBenchHarness::send`], writes that would block pump the peer,
+/// which reads its pending data into a side buffer that later reads through
+/// the peer's stream return first. Reads return WouldBlock when no data has
+/// been sent.
+pub struct TlsStream<'a> {
+    harness: &'a mut S2NHarness,
+    mode: Mode,
+}
+
+impl TlsStream<'_> {
+    fn peer(&self) -> Mode {
+        match self.mode {
+            Mode::Client => Mode::Server,
+            Mode::Server => Mode::Client,
+        }
+    }
+}
+
+impl Read for TlsStream<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let (conn, drained) = match self.mode {
+            Mode::Client => (
+                &mut self.harness.client_conn,
+                &mut self.harness.client_drained,
+            ),
+            Mode::Server => (
+                &mut self.harness.server_conn,
+                &mut self.harness.server_drained,
+            ),
+        };
+        if !drained.is_empty() {
+            return drained.read(buf);
+        }
+        match conn.poll_recv(buf) {
+            Ready(len) => Ok(len?),
+            Pending => Err(std::io::ErrorKind::WouldBlock.into()),
+        }
+    }
+}
+
+impl Write for TlsStream<'_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        loop {
+            match self.harness.conn_mut(self.mode).poll_send(buf) {
+                Ready(len) => return Ok(len?),
+                Pending => self.harness.drain(self.peer())?,
+            }
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        loop {
+            match self.harness.conn_mut(self.mode).poll_flush() {
+                Ready(res) => return res.map(|_| ()).map_err(Into::into),
+                Pending => self.harness.drain(self.peer())?,
+            }
+        }
+    }
+}
+
+/// Get the alert that RFC 8446 requires for a local handshake error
+///
+/// s2n-tls sends a generic close_notify alert for most errors, and only after
+/// the blinding delay, so the alert is derived from the error instead
+fn error_alert(err: &s2n_tls::error::Error) -> Option<AlertDescription> {
+    let alert = match err.name() {
+        "S2N_ERR_BAD_MESSAGE" => AlertDescription::UnexpectedMessage,
+        "S2N_ERR_DECRYPT" => AlertDescription::BadRecordMac,
+        "S2N_ERR_CIPHER_NOT_SUPPORTED" => AlertDescription::HandshakeFailure,
+        // s2n-tls reports all certificate validation failures, including
+        // host name mismatches, as untrusted certificates
+        "S2N_ERR_CERT_UNTRUSTED" => AlertDescription::UnknownCa,
+        "S2N_ERR_CERT_EXPIRED" => AlertDescription::CertificateExpired,
+        "S2N_ERR_CERT_INVALID" | "S2N_ERR_CERT_TYPE_UNSUPPORTED" => {
+            AlertDescription::BadCertificate
+        }
+        "S2N_ERR_PROTOCOL_DOWNGRADE_DETECTED" => AlertDescription::IllegalParameter,
+        "S2N_ERR_PROTOCOL_VERSION_UNSUPPORTED" => AlertDescription::ProtocolVersion,
+        "S2N_ERR_MISSING_EXTENSION" => AlertDescription::MissingExtension,
+        _ => return None,
+    };
+    Some(alert)
+}
+
 impl TlsBenchHarness for S2NHarness {
-    fn new(crypto_config: &CryptoConfig) -> Result<Self, Box<dyn Error>> {
-        let client_to_server_buf = Box::pin(UnsafeCell::new(VecDeque::new()));
-        let server_to_client_buf = Box::pin(UnsafeCell::new(VecDeque::new()));
+    fn new_with_certs(
+        crypto_config: &CryptoConfig,
+        handshake_type: HandshakeType,
+        cert_config: &CertConfig,
+    ) -> Result<Self, Box<dyn Error>> {
+        Self::new_with_builder_hook(crypto_config, handshake_type, cert_config, |_, _| Ok(()))
+    }
 
-        let client_config = Self::create_config(Mode::Client, crypto_config)?;
-        let server_config = Self::create_config(Mode::Server, crypto_config)?;
+    fn new_with_session_tickets(
+        crypto_config: &CryptoConfig,
+        handshake_type: HandshakeType,
+    ) -> Result<Self, Box<dyn Error>> {
+        Self::new_with_builder_hook(
+            crypto_config,
+            handshake_type,
+            &CertConfig::default(),
+            |mode, builder| {
+                if mode == Mode::Server {
+                    Self::add_ticket_key(builder, &TicketKey::default())?;
+                }
+                Ok(())
+            },
+        )
+    }
 
-        let mut harness = Self {
-            client_to_server_buf,
-            server_to_client_buf,
+    fn new_with_buffer(
+        crypto_config: &CryptoConfig,
+        handshake_type: HandshakeType,
+        client_buf: ConnectedBuffer,
+    ) -> Result<Self, Box<dyn Error>> {
+        let session_ticket_storage = SessionTicketStorage::default();
+        let client_config = Self::create_config(
+            Mode::Client,
+            crypto_config,
+            handshake_type,
+            &session_ticket_storage,
+        )?;
+        let server_config = Self::create_config(
+            Mode::Server,
+            crypto_config,
+            handshake_type,
+            &session_ticket_storage,
+        )?;
+
+        Self::from_configs(
+            client_buf,
             client_config,
             server_config,
-            client_conn: Connection::new_client(),
-            server_conn: Connection::new_server(),
-            client_handshake_completed: false,
-            server_handshake_completed: false,
-        };
+            session_ticket_storage,
+        )
+    }
 
-        harness.init_conn(Mode::Client)?;
-        harness.init_conn(Mode::Server)?;
+    fn new_with_alpn(
+        crypto_config: &CryptoConfig,
+        client_protocols: &[&[u8]],
+        server_protocols: &[&[u8]],
+    ) -> Result<Self, Box<dyn Error>> {
+        Self::new_with_builder_hook(
+            crypto_config,
+            HandshakeType::ServerAuth,
+            &CertConfig::default(),
+            |mode, builder| {
+                let protocols = match mode {
+                    Mode::Client => client_protocols,
+                    Mode::Server => server_protocols,
+                };
+                builder.set_application_protocol_preference(protocols)?;
+                Ok(())
+            },
+        )
+    }
 
+    fn new_with_key_logs(
+        crypto_config: &CryptoConfig,
+        handshake_type: HandshakeType,
+        client_log: &KeyLog,
+        server_log: &KeyLog,
+    ) -> Result<Self, Box<dyn Error>> {
+        let mut key_log_contexts = Vec::new();
+        let mut harness = Self::new_with_builder_hook(
+            crypto_config,
+            handshake_type,
+            &CertConfig::default(),
+            |mode, builder| {
+                let key_log = match mode {
+                    Mode::Client => client_log,
+                    Mode::Server => server_log,
+                };
+                key_log_contexts.push(Self::set_key_log(builder, key_log)?);
+                Ok(())
+            },
+        )?;
+        harness.key_log_contexts = key_log_contexts;
         Ok(harness)
     }
 
+    fn take_connected_buffer(self) -> ConnectedBuffer {
+        // the connections hold pointers to the buffers, so drop them first
+        let Self {
+            client_conn,
+            server_conn,
+            client_buf,
+            server_buf,
+            ..
+        } = self;
+        drop(client_conn);
+        drop(server_conn);
+        drop(server_buf);
+        Pin::into_inner(client_buf).recycle()
+    }
+
     fn handshake(&mut self) -> Result<(), Box<dyn Error>> {
         for _ in 0..2 {
             self.handshake_conn(Mode::Client)?;
@@ -179,6 +1387,14 @@ impl TlsBenchHarness for S2NHarness {
         Ok(())
     }
 
+    fn handshake_step(&mut self, mode: Mode) -> Result<(), Box<dyn Error>> {
+        self.handshake_conn(mode)
+    }
+
+    fn handshake_step_count(&self) -> usize {
+        self.handshake_step_count
+    }
+
     fn handshake_completed(&self) -> bool {
         self.client_handshake_completed && self.server_handshake_completed
     }
@@ -194,4 +1410,1175 @@ impl TlsBenchHarness for S2NHarness {
     fn negotiated_tls13(&self) -> bool {
         self.client_conn.actual_protocol_version().unwrap() == Version::TLS13
     }
+
+    fn hello_retry_requested(&self) -> bool {
+        self.client_conn
+            .handshake_type()
+            .unwrap()
+            .contains("HELLO_RETRY_REQUEST")
+    }
+
+    /// s2n-tls is built against whichever libcrypto openssl-sys finds, which
+    /// can be selected with `OPENSSL_DIR`, or against the libcrypto of a
+    /// prebuilt s2n-tls selected with `S2N_TLS_DIR`
+    fn crypto_backend() -> String {
+        extern "C" {
+            fn OpenSSL_version(version_type: c_int) -> *const c_char;
+        }
+        const OPENSSL_VERSION: c_int = 0;
+
+        // SAFETY: the version is a static null-terminated string
+        unsafe { CStr::from_ptr(OpenSSL_version(OPENSSL_VERSION)) }
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    fn connection_info(&self) -> ConnectionInfo {
+        let ec_group = match self.client_conn.selected_curve().unwrap() {
+            "secp256r1" => ECGroup::SECP256R1,
+            "x25519" => ECGroup::X25519,
+            _ => panic!("Unknown group"),
+        };
+        let signature_scheme = format!(
+            "{:?}+{:?}",
+            self.client_conn.selected_signature_algorithm().unwrap(),
+            self.client_conn.selected_hash_algorithm().unwrap()
+        );
+        ConnectionInfo {
+            cipher_suite: self.get_negotiated_cipher_suite(),
+            tls13: self.negotiated_tls13(),
+            ec_group,
+            signature_scheme: Some(signature_scheme),
+            alpn: self.client_conn.application_protocol().map(<[u8]>::to_vec),
+            resumed: self.was_resumed(),
+            peer_cert_chain_len: self.client_conn.peer_cert_chain_len().ok(),
+        }
+    }
+
+    fn send(&mut self, sender: Mode, data: &[u8]) -> Result<(), Box<dyn Error>> {
+        let receiver = match sender {
+            Mode::Client => Mode::Server,
+            Mode::Server => Mode::Client,
+        };
+
+        // s2n-tls may only accept part of the data if the buffer fills up,
+        // so keep sending the remainder until all of it has been accepted
+        let mut sent = 0;
+        while sent < data.len() {
+            match self.conn_mut(sender).poll_send(&data[sent..]) {
+                Ready(Ok(0)) => return Err("connection closed before all data was sent".into()),
+                Ready(len) => sent += len?,
+                Pending => self.drain(receiver)?,
+            }
+        }
+        Ok(())
+    }
+
+    fn recv(&mut self, receiver: Mode, data: &mut [u8]) -> Result<(), Box<dyn Error>> {
+        let (conn, drained) = match receiver {
+            Mode::Client => (&mut self.client_conn, &mut self.client_drained),
+            Mode::Server => (&mut self.server_conn, &mut self.server_drained),
+        };
+        let mut received = drained.read(data)?;
+        while received < data.len() {
+            match conn.poll_recv(&mut data[received..]) {
+                Ready(Ok(0)) => return Err("connection closed before all data was received".into()),
+                Ready(Ok(len)) => received += len,
+                Ready(Err(err)) => {
+                    self.last_alert = error_alert(&err);
+                    return Err(err.into());
+                }
+                Pending => return Err("not enough data sent to receive".into()),
+            }
+        }
+        Ok(())
+    }
+
+    fn shutdown(&mut self) -> Result<(), Box<dyn Error>> {
+        fn poll_closed(conn: &mut Connection) -> Result<bool, Box<dyn Error>> {
+            match conn.poll_shutdown() {
+                Ready(Ok(_)) => Ok(true),
+                Ready(Err(err)) => Err(err.into()),
+                Pending => Ok(false),
+            }
+        }
+
+        // the client blocks until the server answers its close_notify
+        let client_closed = poll_closed(&mut self.client_conn)?;
+        let server_closed = poll_closed(&mut self.server_conn)?;
+        let client_closed = client_closed || poll_closed(&mut self.client_conn)?;
+        if !(client_closed && server_closed) {
+            return Err("connections did not exchange close_notify alerts".into());
+        }
+        Ok(())
+    }
+
+    fn was_resumed(&self) -> bool {
+        self.client_conn.resumed()
+    }
+
+    fn new_resumed(&self) -> Result<Self, Box<dyn Error>> {
+        let ticket = self
+            .last_session_ticket()
+            .ok_or("no session ticket received")?;
+
+        let mut harness = self.share_configs()?;
+        harness.client_conn.set_session_ticket(&ticket)?;
+        Ok(harness)
+    }
+
+    fn new_with_same_configs(&self) -> Result<Self, Box<dyn Error>> {
+        self.share_configs()
+    }
+
+    fn round_trips(&self) -> usize {
+        self.client_buf.sent_flights()
+    }
+
+    fn bytes_sent(&self, sender: Mode) -> usize {
+        match sender {
+            Mode::Client => self.client_buf.sent_bytes(),
+            Mode::Server => self.server_buf.sent_bytes(),
+        }
+    }
+
+    fn records_sent(&self, sender: Mode) -> usize {
+        match sender {
+            Mode::Client => self.client_buf.sent_records(),
+            Mode::Server => self.server_buf.sent_records(),
+        }
+    }
+
+    fn peak_unread_bytes(&self, sender: Mode) -> usize {
+        match sender {
+            Mode::Client => self.client_buf.peak_unread(),
+            Mode::Server => self.server_buf.peak_unread(),
+        }
+    }
+
+    fn pending_data(&self, receiver: Mode) -> Vec<u8> {
+        match receiver {
+            Mode::Client => self.client_buf.peek_unread(),
+            Mode::Server => self.server_buf.peek_unread(),
+        }
+    }
+
+    fn discard_pending_data(&mut self, receiver: Mode) {
+        match receiver {
+            Mode::Client => self.client_buf.discard_unread(),
+            Mode::Server => self.server_buf.discard_unread(),
+        }
+    }
+
+    fn is_drained(&self) -> bool {
+        self.client_buf.is_drained() && self.server_buf.is_drained()
+    }
+
+    fn inject_pending_data(&mut self, receiver: Mode, data: &[u8]) {
+        match receiver {
+            Mode::Client => self.client_buf.inject(data),
+            Mode::Server => self.server_buf.inject(data),
+        }
+    }
+
+    fn corrupt_pending_data(&mut self, receiver: Mode, index: usize) {
+        match receiver {
+            Mode::Client => self.client_buf.corrupt_unread(index),
+            Mode::Server => self.server_buf.corrupt_unread(index),
+        }
+    }
+
+    fn last_alert(&self) -> Option<AlertDescription> {
+        self.last_alert
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::harness::{
+        handshake_header_record, hide_extended_master_secret, hide_tls13_support, read_transcript,
+        record_lengths, wire_format_summary,
+    };
+    use crate::harness::{IntermediateOrder, ServerIdentity, SigType};
+    use s2n_tls::enums::SignatureAlgorithm;
+
+    const CRYPTO_CONFIG: CryptoConfig = CryptoConfig {
+        cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+        ec_group: ECGroup::SECP256R1,
+    };
+    /// Calls to `handshake` needed by handshakes with more than two round
+    /// trips, like full TLS1.2 handshakes and HelloRetryRequests
+    const MAX_HANDSHAKES: usize = 2;
+
+    #[test]
+    fn test_send_larger_than_buffer() {
+        // large enough to hold each handshake flight
+        const CAPACITY: usize = 4096;
+
+        let mut harness = S2NHarness::new_with_buffer(
+            &CRYPTO_CONFIG,
+            HandshakeType::ServerAuth,
+            ConnectedBuffer::bounded(CAPACITY),
+        )
+        .unwrap();
+        harness.handshake().unwrap();
+        assert!(harness.handshake_completed());
+
+        let data: Vec<u8> = (0..10 * CAPACITY).map(|i| i as u8).collect();
+        let mut received = vec![0; data.len()];
+        harness.send(Mode::Client, &data).unwrap();
+        harness.recv(Mode::Server, &mut received).unwrap();
+        assert_eq!(data, received);
+    }
+
+    #[test]
+    fn test_tls_stream_copy() {
+        const CAPACITY: usize = 4096;
+
+        let mut harness = S2NHarness::new_with_buffer(
+            &CRYPTO_CONFIG,
+            HandshakeType::ServerAuth,
+            ConnectedBuffer::bounded(CAPACITY),
+        )
+        .unwrap();
+        harness.handshake().unwrap();
+
+        // the data doesn't fit in the buffer, so copying pumps the server
+        let data: Vec<u8> = (0..10 * CAPACITY).map(|i| i as u8).collect();
+        let copied =
+            std::io::copy(&mut data.as_slice(), &mut harness.stream(Mode::Client)).unwrap();
+        assert_eq!(copied, data.len() as u64);
+        harness.stream(Mode::Client).flush().unwrap();
+
+        let mut received = vec![0; data.len()];
+        let mut server = harness.stream(Mode::Server);
+        server.read_exact(&mut received).unwrap();
+        assert_eq!(data, received);
+        let err = server.read(&mut received).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::WouldBlock);
+    }
+
+    #[test]
+    fn test_psk_handshake() {
+        const IDENTITY: &[u8] = b"bench identity";
+
+        let mut cert_harness = S2NHarness::new(&CRYPTO_CONFIG, HandshakeType::ServerAuth).unwrap();
+        cert_harness.handshake().unwrap();
+        assert_eq!(
+            cert_harness.client_conn.negotiated_psk_identity().unwrap(),
+            None
+        );
+
+        let mut harness = S2NHarness::new_with_psk(&CRYPTO_CONFIG, IDENTITY, &[1; 32]).unwrap();
+        harness.handshake().unwrap();
+        assert!(harness.handshake_completed());
+        for conn in [&harness.client_conn, &harness.server_conn] {
+            assert_eq!(
+                conn.negotiated_psk_identity().unwrap().as_deref(),
+                Some(IDENTITY)
+            );
+        }
+        // the server doesn't send its certificate chain or a signature
+        assert!(harness.bytes_sent(Mode::Server) < cert_harness.bytes_sent(Mode::Server));
+    }
+
+    #[test]
+    fn test_browser_extensions() {
+        let mut minimal = S2NHarness::new(&CRYPTO_CONFIG, HandshakeType::ServerAuth).unwrap();
+        let mut browser = S2NHarness::new_with_browser_extensions(&CRYPTO_CONFIG).unwrap();
+        // the first step only sends the ClientHello
+        minimal.handshake_step(Mode::Client).unwrap();
+        browser.handshake_step(Mode::Client).unwrap();
+        assert!(browser.bytes_sent(Mode::Client) > minimal.bytes_sent(Mode::Client));
+
+        browser.handshake().unwrap();
+        assert!(browser.handshake_completed());
+        assert_eq!(browser.connection_info().alpn, Some(b"h2".to_vec()));
+        assert_eq!(browser.server_conn.server_name(), Some("localhost"));
+    }
+
+    #[test]
+    fn test_deferred_flush() {
+        let mut harness = S2NHarness::new(&CRYPTO_CONFIG, HandshakeType::ServerAuth).unwrap();
+        harness.handshake().unwrap();
+
+        harness.set_deferred_flush(Mode::Client, true);
+        let writes_before = harness.flush_and_write_calls(Mode::Client).1;
+        for _ in 0..3 {
+            harness.send(Mode::Client, &[1; 100]).unwrap();
+        }
+        // the records were written, but the server can't read them yet
+        assert_eq!(
+            harness.flush_and_write_calls(Mode::Client),
+            (0, writes_before + 3)
+        );
+        assert!(harness.pending_data(Mode::Server).is_empty());
+
+        harness.flush(Mode::Client).unwrap();
+        assert_eq!(harness.flush_and_write_calls(Mode::Client).0, 1);
+        let mut received = [0; 300];
+        harness.recv(Mode::Server, &mut received).unwrap();
+        assert_eq!(received, [1; 300]);
+    }
+
+    #[test]
+    fn test_send_vectored() {
+        const WRITE_LEN: usize = 1000;
+        const WRITES: usize = 100;
+        const SEND_BUFFER_SIZE: u32 = 128 * 1024;
+
+        let data: Vec<u8> = (0..WRITE_LEN * WRITES).map(|i| i as u8).collect();
+        let writes: Vec<&[u8]> = data.chunks(WRITE_LEN).collect();
+        let mut write_calls = Vec::new();
+        for batched in [false, true] {
+            let mut harness = if batched {
+                S2NHarness::new_with_send_buffer_size(&CRYPTO_CONFIG, SEND_BUFFER_SIZE)
+            } else {
+                S2NHarness::new(&CRYPTO_CONFIG, HandshakeType::ServerAuth)
+            }
+            .unwrap();
+            harness.handshake().unwrap();
+
+            let before = harness.call_counts().send_cb;
+            if batched {
+                harness.send_vectored(Mode::Client, &writes).unwrap();
+            } else {
+                for write in &writes {
+                    harness.send(Mode::Client, write).unwrap();
+                }
+            }
+            write_calls.push(harness.call_counts().send_cb - before);
+
+            let mut received = vec![0; data.len()];
+            harness.recv(Mode::Server, &mut received).unwrap();
+            assert_eq!(data, received);
+        }
+        // one record and write per application write, versus a few full
+        // records in a single write
+        assert_eq!(write_calls[0], WRITES);
+        assert_eq!(write_calls[1], 1);
+    }
+
+    #[test]
+    fn test_trust_store_sources() {
+        for source in [
+            TrustStoreSource::Pem,
+            TrustStoreSource::Location,
+            TrustStoreSource::System,
+        ] {
+            let mut harness =
+                S2NHarness::new_with_trust_store_source(&CRYPTO_CONFIG, source).unwrap();
+            harness.handshake().unwrap();
+            assert!(harness.handshake_completed(), "{source:?}");
+        }
+    }
+
+    #[test]
+    fn test_cert_validation_time() {
+        const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+        // the default certs are valid from 2023-06-09 to 2202-11-14
+        let not_before = UNIX_EPOCH + Duration::from_secs(1_686_331_502);
+        let not_after = UNIX_EPOCH + Duration::from_secs(7_348_641_902);
+
+        for validation_time in [not_before + DAY, not_after - DAY] {
+            let mut harness =
+                S2NHarness::new_with_validation_time(&CRYPTO_CONFIG, validation_time).unwrap();
+            harness.handshake().unwrap();
+            assert!(harness.handshake_completed());
+        }
+
+        // s2n-tls reports not-yet-valid certs as untrusted
+        for (validation_time, expected_error) in [
+            (not_after + DAY, "S2N_ERR_CERT_EXPIRED"),
+            (not_before - DAY, "S2N_ERR_CERT_UNTRUSTED"),
+        ] {
+            let mut harness =
+                S2NHarness::new_with_validation_time(&CRYPTO_CONFIG, validation_time).unwrap();
+            let err = harness.handshake().unwrap_err();
+            let err = err.downcast_ref::<s2n_tls::error::Error>().unwrap();
+            assert_eq!(err.name(), expected_error);
+        }
+    }
+
+    #[test]
+    fn test_build_configs() {
+        let (client_config, _) =
+            S2NHarness::build_configs(&CRYPTO_CONFIG, HandshakeType::ServerAuth).unwrap();
+        let (mutual_client_config, mutual_server_config) =
+            S2NHarness::build_configs(&CRYPTO_CONFIG, HandshakeType::MutualAuth).unwrap();
+
+        let mut harness = S2NHarness::from_configs(
+            ConnectedBuffer::new(),
+            mutual_client_config,
+            mutual_server_config.clone(),
+            SessionTicketStorage::default(),
+        )
+        .unwrap();
+        harness.handshake().unwrap();
+        assert!(harness.handshake_completed());
+
+        // the server rejects a client without a cert
+        let mut harness = S2NHarness::from_configs(
+            ConnectedBuffer::new(),
+            client_config,
+            mutual_server_config,
+            SessionTicketStorage::default(),
+        )
+        .unwrap();
+        assert!(harness.handshake().is_err());
+        assert!(!harness.handshake_completed());
+    }
+
+    #[test]
+    fn test_clock_skew() {
+        const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+        // all the default certs expire at the same time
+        let not_after = UNIX_EPOCH + Duration::from_secs(7_348_641_902);
+        let now = SystemTime::now();
+
+        // each side only rejects its peer's cert if its own clock is past it
+        for (client_time, server_time, rejected_by) in [
+            (not_after + DAY, now, Some(Mode::Client)),
+            (now, not_after + DAY, Some(Mode::Server)),
+            (not_after - DAY, now, None),
+        ] {
+            let mut harness = S2NHarness::new_with_clocks(
+                &CRYPTO_CONFIG,
+                HandshakeType::MutualAuth,
+                Some(client_time),
+                Some(server_time),
+            )
+            .unwrap();
+            let mut result = Ok(());
+            for mode in [Mode::Client, Mode::Server, Mode::Client, Mode::Server] {
+                if let Err(err) = harness.handshake_step(mode) {
+                    result = Err((mode, err));
+                    break;
+                }
+            }
+            match rejected_by {
+                Some(rejected_by) => {
+                    let (mode, err) = result.unwrap_err();
+                    assert_eq!(mode, rejected_by);
+                    let err = err.downcast_ref::<s2n_tls::error::Error>().unwrap();
+                    assert_eq!(err.name(), "S2N_ERR_CERT_EXPIRED");
+                }
+                None => {
+                    assert!(result.is_ok());
+                    assert!(harness.handshake_completed());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut harness = S2NHarness::new(&CRYPTO_CONFIG, HandshakeType::MutualAuth).unwrap();
+        harness.handshake().unwrap();
+        let round_trips = harness.round_trips();
+
+        for wipe in [true, false] {
+            if wipe {
+                harness.reset().unwrap();
+            } else {
+                harness.reset_with_new_connections().unwrap();
+            }
+            assert!(!harness.handshake_completed());
+            assert!(harness.pending_data(Mode::Client).is_empty());
+            assert!(harness.pending_data(Mode::Server).is_empty());
+            assert!(!harness.server_conn.client_cert_used());
+
+            harness.handshake().unwrap();
+            assert!(harness.handshake_completed());
+            assert!(harness.server_conn.client_cert_used());
+            // a full handshake, not resumed from the previous session
+            assert!(!harness.was_resumed());
+            assert_eq!(harness.round_trips(), round_trips);
+        }
+    }
+
+    #[test]
+    fn test_optional_client_auth_without_cert() {
+        let mut harness =
+            S2NHarness::new(&CRYPTO_CONFIG, HandshakeType::OptionalClientAuth).unwrap();
+        harness.handshake().unwrap();
+        assert!(harness.handshake_completed());
+        assert!(!harness.server_conn.client_cert_used());
+    }
+
+    #[test]
+    fn test_optional_client_auth_with_cert() {
+        // client presents a cert to a server that only requests one
+        let storage = SessionTicketStorage::default();
+        let client_config = S2NHarness::create_config(
+            Mode::Client,
+            &CRYPTO_CONFIG,
+            HandshakeType::MutualAuth,
+            &storage,
+        )
+        .unwrap();
+        let server_config = S2NHarness::create_config(
+            Mode::Server,
+            &CRYPTO_CONFIG,
+            HandshakeType::OptionalClientAuth,
+            &storage,
+        )
+        .unwrap();
+
+        let mut harness = S2NHarness::from_configs(
+            ConnectedBuffer::new(),
+            client_config,
+            server_config,
+            storage,
+        )
+        .unwrap();
+        harness.handshake().unwrap();
+        assert!(harness.handshake_completed());
+        assert!(harness.server_conn.client_cert_used());
+    }
+
+    #[test]
+    fn test_call_counts_byte_by_byte() {
+        let mut all_at_once = S2NHarness::default().unwrap();
+        all_at_once.handshake().unwrap();
+
+        let mut byte_by_byte = S2NHarness::new_with_buffer(
+            &CRYPTO_CONFIG,
+            HandshakeType::ServerAuth,
+            ConnectedBuffer::with_max_read_len(1),
+        )
+        .unwrap();
+        byte_by_byte.handshake().unwrap();
+        assert!(byte_by_byte.handshake_completed());
+
+        let (expected, actual) = (all_at_once.call_counts(), byte_by_byte.call_counts());
+        assert_eq!(expected.poll_negotiate, actual.poll_negotiate);
+        assert_eq!(expected.send_cb, actual.send_cb);
+        assert!(expected.recv_cb < actual.recv_cb);
+    }
+
+    #[test]
+    fn test_chunked_arrival_pending_polls() {
+        const CHUNK_LEN: usize = 64;
+        // bound the number of polls in case the handshake makes no progress
+        const MAX_ROUNDS: usize = 1000;
+
+        let mut all_at_once = S2NHarness::default().unwrap();
+        all_at_once.handshake().unwrap();
+
+        let mut chunked = S2NHarness::new_with_buffer(
+            &CRYPTO_CONFIG,
+            HandshakeType::ServerAuth,
+            ConnectedBuffer::with_chunked_arrival(CHUNK_LEN),
+        )
+        .unwrap();
+        // each poll can only read one chunk before the read would block
+        chunked.handshake().unwrap();
+        assert!(!chunked.handshake_completed());
+        chunked.handshake_with_timeout(MAX_ROUNDS).unwrap();
+
+        let server_flight_len = all_at_once.bytes_sent(Mode::Server);
+        let (expected, actual) = (all_at_once.call_counts(), chunked.call_counts());
+        assert!(actual.poll_negotiate > expected.poll_negotiate);
+        assert!(actual.poll_negotiate >= server_flight_len / CHUNK_LEN);
+    }
+
+    #[test]
+    fn test_resume_with_last_of_many_tickets() {
+        const TICKET_COUNT: u8 = 5;
+
+        let mut harness = S2NHarness::new_with_ticket_count(&CRYPTO_CONFIG, TICKET_COUNT).unwrap();
+        harness.handshake().unwrap();
+        harness.send(Mode::Server, &[0]).unwrap();
+        harness.recv(Mode::Client, &mut [0]).unwrap();
+
+        let (count, bytes) = harness.stored_tickets();
+        assert_eq!(count, TICKET_COUNT as usize);
+        assert!(bytes > 0);
+
+        let mut resumed = harness.new_resumed().unwrap();
+        resumed.handshake().unwrap();
+        assert!(resumed.handshake_completed());
+        assert!(resumed.client_conn.resumed());
+    }
+
+    #[test]
+    fn test_resume_with_shared_ticket_key() {
+        let ticket_key = TicketKey {
+            name: b"fleet key".to_vec(),
+            key: vec![7; 32],
+        };
+        let mut harness = S2NHarness::new_with_ticket_key(&CRYPTO_CONFIG, &ticket_key).unwrap();
+        harness.handshake().unwrap();
+        // TLS1.3 tickets are sent after the handshake
+        harness.send(Mode::Server, &[0]).unwrap();
+        harness.recv(Mode::Client, &mut [0]).unwrap();
+        let ticket = harness.last_session_ticket().unwrap();
+
+        // a separately constructed server with the same key resumes the session
+        let mut resumed = S2NHarness::new_with_ticket_key(&CRYPTO_CONFIG, &ticket_key).unwrap();
+        resumed.set_session_ticket(&ticket).unwrap();
+        resumed.handshake().unwrap();
+        assert!(resumed.was_resumed());
+
+        // but a server with a different key falls back to a full handshake
+        let mut full =
+            S2NHarness::new_with_session_tickets(&CRYPTO_CONFIG, HandshakeType::ServerAuth)
+                .unwrap();
+        full.set_session_ticket(&ticket).unwrap();
+        full.handshake().unwrap();
+        assert!(full.handshake_completed());
+        assert!(!full.was_resumed());
+    }
+
+    #[test]
+    fn test_only_resumable_servers_issue_tickets() {
+        let mut harness = S2NHarness::default().unwrap();
+        harness.handshake().unwrap();
+        assert!(harness.is_drained());
+        assert_eq!(harness.stored_tickets().0, 0);
+
+        let mut harness =
+            S2NHarness::new_with_session_tickets(&CRYPTO_CONFIG, HandshakeType::ServerAuth)
+                .unwrap();
+        harness.handshake().unwrap();
+        assert!(!harness.is_drained());
+        harness.send(Mode::Server, &[0]).unwrap();
+        harness.recv(Mode::Client, &mut [0]).unwrap();
+        assert_eq!(harness.stored_tickets().0, 1);
+    }
+
+    #[test]
+    fn test_resumed_pool() {
+        let mut harness =
+            S2NHarness::new_with_session_tickets(&CRYPTO_CONFIG, HandshakeType::ServerAuth)
+                .unwrap();
+        harness.handshake().unwrap();
+        harness.send(Mode::Server, &[0]).unwrap();
+        harness.recv(Mode::Client, &mut [0]).unwrap();
+        let (stored, _) = harness.stored_tickets();
+
+        let pool = harness.new_resumed_pool(8).unwrap();
+        assert_eq!(pool.len(), 8);
+        for mut resumed in pool {
+            resumed.handshake().unwrap();
+            assert!(resumed.handshake_completed());
+            assert!(resumed.was_resumed());
+        }
+        // tickets for the resumed sessions were never read by the clients
+        assert_eq!(harness.stored_tickets().0, stored);
+    }
+
+    #[test]
+    fn test_resume_by_session_id() {
+        let mut harness = S2NHarness::new_with_session_cache().unwrap();
+        harness.handshake_with_timeout(MAX_HANDSHAKES).unwrap();
+        assert!(!harness.negotiated_tls13());
+        assert!(!harness.client_conn.resumed());
+        assert_eq!(harness.stored_tickets().0, 0);
+
+        let mut resumed = harness.new_resumed_by_session_id().unwrap();
+        resumed.handshake().unwrap();
+        assert!(resumed.handshake_completed());
+        assert!(resumed.client_conn.resumed());
+        assert!(resumed.server_conn.resumed());
+        // the client completes a full TLS1.2 handshake by reading the
+        // server's Finished, but the server completes an abbreviated one by
+        // reading the client's
+        assert_eq!(harness.handshake_step_count(), 5);
+        assert_eq!(resumed.handshake_step_count(), 4);
+        // the abbreviated handshake omits the server certificate
+        assert!(resumed.bytes_sent(Mode::Server) < harness.bytes_sent(Mode::Server));
+    }
+
+    #[test]
+    fn test_tls12_master_secret_matches() {
+        let (client_log, server_log) = (KeyLog::default(), KeyLog::default());
+        let session_ticket_storage = SessionTicketStorage::default();
+        let mut key_log_contexts = Vec::new();
+        let mut create_config = |mode, key_log| {
+            let mut builder = S2NHarness::create_builder(
+                mode,
+                &CRYPTO_CONFIG,
+                HandshakeType::ServerAuth,
+                &CertConfig::default(),
+                &session_ticket_storage,
+            )
+            .unwrap();
+            // TLS1.2 is the highest version supported by this policy, which
+            // unlike the default policy has ECDSA cipher suites
+            builder
+                .set_security_policy(&Policy::from_version("20190214_gcm").unwrap())
+                .unwrap();
+            key_log_contexts.push(S2NHarness::set_key_log(&mut builder, key_log).unwrap());
+            builder.build().unwrap()
+        };
+        let client_config = create_config(Mode::Client, &client_log);
+        let server_config = create_config(Mode::Server, &server_log);
+        let mut harness = S2NHarness::from_configs(
+            ConnectedBuffer::new(),
+            client_config,
+            server_config,
+            session_ticket_storage,
+        )
+        .unwrap();
+        harness.key_log_contexts = key_log_contexts;
+        harness.handshake_with_timeout(MAX_HANDSHAKES).unwrap();
+        assert!(!harness.negotiated_tls13());
+
+        // the TLS1.2 master secret is always 48 bytes, whatever the PRF hash
+        let master_secret = client_log.secret("CLIENT_RANDOM").unwrap();
+        assert_eq!(master_secret.len(), 2 * 48);
+        assert_eq!(Some(master_secret), server_log.secret("CLIENT_RANDOM"));
+    }
+
+    #[test]
+    fn test_resumption_requires_extended_master_secret() {
+        // s2n-tls always offers extended master secrets in TLS1.2, and has no
+        // option to require them in full handshakes
+        let mut harness = S2NHarness::new_with_session_cache().unwrap();
+        harness.handshake().unwrap();
+        assert!(!harness.negotiated_tls13());
+
+        let mut resumed = harness.new_resumed_by_session_id().unwrap();
+        resumed.handshake().unwrap();
+        assert!(resumed.client_conn.resumed());
+
+        // the session used an extended master secret, so the server must
+        // abort a resumption that doesn't offer one (RFC 7627 section 5.3)
+        let session = harness.client_conn.session().unwrap();
+        let mut resumed = S2NHarness::from_configs(
+            ConnectedBuffer::with_write_interceptor(hide_extended_master_secret),
+            harness.client_config.clone(),
+            harness.server_config.clone(),
+            SessionTicketStorage::default(),
+        )
+        .unwrap();
+        resumed.client_conn.set_session_ticket(&session).unwrap();
+        let err = resumed.handshake().unwrap_err();
+        let err = err.downcast_ref::<s2n_tls::error::Error>().unwrap();
+        assert_eq!(err.name(), "S2N_ERR_MISSING_EXTENSION");
+        assert!(!resumed.handshake_completed());
+    }
+
+    #[test]
+    fn test_downgrade_detected() {
+        // CRYPTO_CONFIG uses a security policy supporting both TLS1.2 and TLS1.3
+        let crypto_config = CRYPTO_CONFIG;
+
+        let mut harness = S2NHarness::new(&crypto_config, HandshakeType::ServerAuth).unwrap();
+        harness.handshake().unwrap();
+        assert!(harness.negotiated_tls13());
+
+        // the server negotiates TLS1.2 with the rewritten ClientHello, and the
+        // client detects the downgrade from the server random
+        let mut harness = S2NHarness::new_with_buffer(
+            &crypto_config,
+            HandshakeType::ServerAuth,
+            ConnectedBuffer::with_write_interceptor(hide_tls13_support),
+        )
+        .unwrap();
+        assert!(harness.handshake().is_err());
+        assert!(!harness.handshake_completed());
+        assert_eq!(
+            harness.last_alert(),
+            Some(AlertDescription::IllegalParameter)
+        );
+    }
+
+    #[test]
+    fn test_verify_host_name_calls() {
+        for count in [1, 10, 100] {
+            let handler = WildcardHostNameHandler::new("localhost");
+            let mut harness = S2NHarness::new_with_host_name_handler(
+                &CRYPTO_CONFIG,
+                &CertConfig::with_san_count(count),
+                handler.clone(),
+            )
+            .unwrap();
+            harness.handshake().unwrap();
+            assert!(harness.handshake_completed());
+            // only the last name matches
+            assert_eq!(handler.calls(), count);
+        }
+    }
+
+    #[test]
+    fn test_verify_host_name_called_once_per_handshake() {
+        let handler = WildcardHostNameHandler::new("localhost");
+        let mut harness = S2NHarness::new_with_host_name_handler(
+            &CRYPTO_CONFIG,
+            &CertConfig::with_san_count(1),
+            handler.clone(),
+        )
+        .unwrap();
+        for handshakes in 1..=3 {
+            harness.handshake().unwrap();
+            assert!(harness.handshake_completed());
+            assert_eq!(handler.calls(), handshakes);
+            // the callback is set on the config, so it survives the reset
+            harness.reset().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_builtin_host_verification() {
+        let mut harness = S2NHarness::new_with_builtin_host_verification(
+            &CRYPTO_CONFIG,
+            &CertConfig::with_san_count(1),
+        )
+        .unwrap();
+        harness.handshake().unwrap();
+        assert!(harness.handshake_completed());
+
+        // without the server name, no hostname can match
+        harness.reset().unwrap();
+        assert!(harness.handshake().is_err());
+    }
+
+    #[test]
+    fn test_cert_signature_not_allowed_in_handshake() {
+        // DER encoding of the sha256WithRSAEncryption OID
+        const RSA_PKCS1_SHA256_OID: &[u8] = &[
+            0x06, 0x09, 0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x0b,
+        ];
+
+        // the ECDSA server cert is signed by the RSA CA with rsa_pkcs1_sha256,
+        // which TLS1.3 only allows in signature_algorithms_cert
+        let identity = ServerIdentity::load(&CertConfig::default()).unwrap();
+        let leaf = &identity.cert_chain[0];
+        assert!(leaf
+            .windows(RSA_PKCS1_SHA256_OID.len())
+            .any(|window| window == RSA_PKCS1_SHA256_OID));
+
+        let mut harness = S2NHarness::default().unwrap();
+        harness.handshake().unwrap();
+        assert!(harness.handshake_completed());
+        assert!(harness.negotiated_tls13());
+        assert_eq!(
+            harness.client_conn.selected_signature_algorithm().unwrap(),
+            SignatureAlgorithm::ECDSA
+        );
+    }
+
+    #[test]
+    fn test_wildcard_host_name_matching() {
+        let handler = WildcardHostNameHandler::new("www.example.com");
+        assert!(handler.verify_host_name("www.example.com"));
+        assert!(handler.verify_host_name("*.example.com"));
+        assert!(handler.verify_host_name("*.EXAMPLE.com"));
+        assert!(!handler.verify_host_name("*.www.example.com"));
+        assert!(!handler.verify_host_name("*.com"));
+        assert!(!handler.verify_host_name("example.com"));
+        assert_eq!(handler.calls(), 6);
+    }
+
+    #[cfg(feature = "fips")]
+    #[test]
+    fn test_fips_rejects_unapproved_config() {
+        for crypto_config in [
+            CryptoConfig {
+                cipher_suite: CipherSuite::AES_128_GCM_SHA256,
+                ec_group: ECGroup::X25519,
+            },
+            CryptoConfig {
+                cipher_suite: CipherSuite::AES_256_GCM_SHA384,
+                ec_group: ECGroup::SECP256R1,
+            },
+        ] {
+            assert!(!crypto_config.is_fips_approved());
+            assert!(S2NHarness::new(&crypto_config, HandshakeType::ServerAuth).is_err());
+        }
+    }
+
+    #[test]
+    fn test_record_transcript() {
+        const HANDSHAKE_RECORD: u8 = 22;
+        const CLIENT_HELLO: u8 = 1;
+
+        let path = std::env::temp_dir().join("s2n-tls-bench-transcript");
+        let mut harness = S2NHarness::new_with_buffer(
+            &CRYPTO_CONFIG,
+            HandshakeType::ServerAuth,
+            ConnectedBuffer::recording(&path).unwrap(),
+        )
+        .unwrap();
+        harness.handshake().unwrap();
+        assert!(harness.handshake_completed());
+
+        let transcript = read_transcript(&path).unwrap();
+        let (sender, first_write) = &transcript[0];
+        assert_eq!(*sender, Mode::Client);
+        assert_eq!(first_write[0], HANDSHAKE_RECORD);
+        assert_eq!(first_write[5], CLIENT_HELLO);
+
+        // all data written by each side is recorded
+        let recorded_len = |mode| -> usize {
+            transcript
+                .iter()
+                .filter(|(sender, _)| *sender == mode)
+                .map(|(_, data)| data.len())
+                .sum()
+        };
+        assert_eq!(recorded_len(Mode::Client), harness.bytes_sent(Mode::Client));
+        assert_eq!(recorded_len(Mode::Server), harness.bytes_sent(Mode::Server));
+    }
+
+    /// Compare the wire format of a default handshake against the summary in
+    /// `tests/golden`, to catch changes made by s2n-tls upgrades
+    ///
+    /// Run with `UPDATE_GOLDEN=1` to record the summary after an intended
+    /// change.
+    #[test]
+    fn test_golden_wire_format() {
+        const GOLDEN_PATH: &str = "tests/golden/s2n-tls-handshake.txt";
+
+        let path = std::env::temp_dir().join("s2n-tls-bench-golden-transcript");
+        let mut harness = S2NHarness::new_with_buffer(
+            &CRYPTO_CONFIG,
+            HandshakeType::ServerAuth,
+            ConnectedBuffer::recording(&path).unwrap(),
+        )
+        .unwrap();
+        harness.handshake().unwrap();
+        let summary = wire_format_summary(&read_transcript(&path).unwrap());
+
+        let golden_path = Path::new(GOLDEN_PATH);
+        if std::env::var_os("UPDATE_GOLDEN").is_some() {
+            std::fs::create_dir_all(golden_path.parent().unwrap()).unwrap();
+            std::fs::write(golden_path, &summary).unwrap();
+            return;
+        }
+        let golden = std::fs::read_to_string(golden_path).unwrap_or_else(|err| {
+            panic!("can't read {GOLDEN_PATH} ({err}), run with UPDATE_GOLDEN=1 to record it")
+        });
+        assert!(
+            summary == golden,
+            "wire format differs from {GOLDEN_PATH}, rerun with UPDATE_GOLDEN=1 if \
+            intended:\n{summary}"
+        );
+    }
+
+    #[test]
+    fn test_max_fragment_length() {
+        // TLS1.3 records may be up to 256 bytes larger than their plaintext
+        const MAX_EXPANSION: usize = 256;
+        const MAX_FRAGMENT_LENGTH: usize = 512;
+        const DATA_LEN: usize = 8 * MAX_FRAGMENT_LENGTH;
+
+        let mut harness =
+            S2NHarness::new_with_max_fragment_length(&CRYPTO_CONFIG, MaxFragmentLength::Bytes512)
+                .unwrap();
+        harness.handshake().unwrap();
+        harness.send(Mode::Server, &[0; DATA_LEN]).unwrap();
+        let lengths = record_lengths(&harness.pending_data(Mode::Client));
+        assert!(lengths.len() >= DATA_LEN / MAX_FRAGMENT_LENGTH);
+        assert!(lengths
+            .iter()
+            .all(|len| *len <= MAX_FRAGMENT_LENGTH + MAX_EXPANSION));
+
+        // without the limit, the data fits in one record
+        let mut harness = S2NHarness::default().unwrap();
+        harness.handshake().unwrap();
+        harness.send(Mode::Server, &[0; DATA_LEN]).unwrap();
+        assert_eq!(record_lengths(&harness.pending_data(Mode::Client)).len(), 1);
+    }
+
+    #[test]
+    fn test_pq_client_falls_back_to_classical_server() {
+        let mut classical = S2NHarness::new_with_security_policies(
+            CLASSICAL_SECURITY_POLICY,
+            CLASSICAL_SECURITY_POLICY,
+        )
+        .unwrap();
+        classical.handshake().unwrap();
+
+        let mut fallback =
+            S2NHarness::new_with_security_policies(PQ_SECURITY_POLICY, CLASSICAL_SECURITY_POLICY)
+                .unwrap();
+        fallback.handshake().unwrap();
+        assert!(fallback.handshake_completed());
+        assert!(fallback.negotiated_tls13());
+        assert_eq!(fallback.kem_group(), None);
+        // the server picks the same classical group it would for a
+        // classical client, without a retry
+        assert_eq!(
+            fallback.connection_info().ec_group,
+            classical.connection_info().ec_group
+        );
+        assert_eq!(fallback.round_trips(), classical.round_trips());
+    }
+
+    #[test]
+    fn test_hello_retry_request() {
+        // both policies support secp256r1, but default_tls13 prefers x25519,
+        // which 20230317 doesn't support
+        let mut no_retry = S2NHarness::new_with_security_policies("20230317", "20230317").unwrap();
+        no_retry.handshake().unwrap();
+        assert!(!no_retry.hello_retry_requested());
+
+        let mut retry =
+            S2NHarness::new_with_security_policies("default_tls13", "20230317").unwrap();
+        retry.handshake_with_timeout(MAX_HANDSHAKES).unwrap();
+        assert!(retry.handshake_completed());
+        assert!(retry.hello_retry_requested());
+        assert_eq!(retry.connection_info().ec_group, ECGroup::SECP256R1);
+        assert_eq!(retry.round_trips(), no_retry.round_trips() + 1);
+        assert_eq!(
+            retry.handshake_step_count(),
+            no_retry.handshake_step_count() + 2
+        );
+    }
+
+    #[test]
+    fn test_server_cipher_order() {
+        // TLS1.3 suites are ordered AES_128_GCM_SHA256 first by default_tls13
+        // and AES_256_GCM_SHA384 first by 20190801. s2n-tls servers always
+        // follow their own order.
+        for (client_policy, server_policy, expected) in [
+            ("default_tls13", "20190801", CipherSuite::AES_256_GCM_SHA384),
+            ("20190801", "default_tls13", CipherSuite::AES_128_GCM_SHA256),
+        ] {
+            let mut harness =
+                S2NHarness::new_with_security_policies(client_policy, server_policy).unwrap();
+            harness.handshake().unwrap();
+            assert_eq!(harness.get_negotiated_cipher_suite(), expected);
+        }
+    }
+
+    #[test]
+    fn test_self_service_blinding_delay() {
+        // s2n-tls picks a delay between 10 and 30 seconds, which has
+        // started counting down by the time it's checked
+        const MIN_DELAY: Duration = Duration::from_secs(9);
+        const MAX_DELAY: Duration = Duration::from_secs(30);
+
+        let mut harness = S2NHarness::new_with_blinding(
+            &CRYPTO_CONFIG,
+            &CertConfig::default(),
+            Blinding::SelfService,
+        )
+        .unwrap();
+        harness.handshake().unwrap();
+        assert_eq!(harness.remaining_blinding_delay().unwrap(), Duration::ZERO);
+
+        // the client can't verify the server's chain, so the error is
+        // returned immediately with the delay left to the caller
+        let mut harness = S2NHarness::new_with_blinding(
+            &CRYPTO_CONFIG,
+            &CertConfig::with_intermediates(IntermediateOrder::Missing),
+            Blinding::SelfService,
+        )
+        .unwrap();
+        assert!(harness.handshake().is_err());
+        let delay = harness.remaining_blinding_delay().unwrap();
+        assert!(delay > MIN_DELAY && delay <= MAX_DELAY, "{delay:?}");
+    }
+
+    #[test]
+    fn test_mixed_sig_type_algorithms() {
+        // TLS1.3 only allows RSA-PSS signatures for RSA keys
+        let expected = |sig_type| match sig_type {
+            SigType::Ecdsa => SignatureAlgorithm::ECDSA,
+            SigType::Rsa => SignatureAlgorithm::RSA_PSS_RSAE,
+        };
+        for server in [SigType::Ecdsa, SigType::Rsa] {
+            for client in [SigType::Ecdsa, SigType::Rsa] {
+                let mut harness = S2NHarness::new_with_certs(
+                    &CRYPTO_CONFIG,
+                    HandshakeType::MutualAuth,
+                    &CertConfig::with_sig_types(server, client),
+                )
+                .unwrap();
+                harness.handshake().unwrap();
+                assert!(harness.negotiated_tls13());
+                for conn in [&harness.client_conn, &harness.server_conn] {
+                    assert_eq!(
+                        conn.selected_signature_algorithm().unwrap(),
+                        expected(server)
+                    );
+                    assert_eq!(
+                        conn.selected_client_signature_algorithm().unwrap(),
+                        Some(expected(client))
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_event_loop_transitions() {
+        const MAX_POLLS: usize = 100;
+
+        let mut harness = S2NHarness::default().unwrap();
+        let stats = harness.handshake_with_event_loop(MAX_POLLS).unwrap();
+        assert!(harness.handshake_completed());
+
+        // in a 1-RTT handshake, the client waits once for the server's
+        // flight and the server waits once for the client's Finished
+        assert_eq!(stats.pending, 2);
+        // every poll after the client's first one was triggered by a wakeup
+        assert_eq!(stats.wakeups, stats.polls - 1);
+        assert_eq!(harness.call_counts().poll_negotiate, stats.polls);
+    }
+
+    #[test]
+    fn test_oversized_handshake_message() {
+        const CERTIFICATE: u8 = 11;
+
+        for (message_len, rejected) in [
+            (MAX_HANDSHAKE_MESSAGE_LEN, false),
+            (MAX_HANDSHAKE_MESSAGE_LEN + 1, true),
+        ] {
+            let mut harness = S2NHarness::default().unwrap();
+            // client sends its ClientHello, then receives the header of a
+            // certificate message from the server in place of its real flight
+            harness.handshake_conn(Mode::Client).unwrap();
+            harness
+                .client_buf
+                .inject(&handshake_header_record(CERTIFICATE, message_len));
+
+            let result = harness.handshake_conn(Mode::Client);
+            if rejected {
+                let err = result.unwrap_err();
+                let err = err.downcast_ref::<s2n_tls::error::Error>().unwrap();
+                assert_eq!(err.name(), "S2N_ERR_BAD_MESSAGE");
+                assert_eq!(
+                    harness.last_alert(),
+                    Some(AlertDescription::UnexpectedMessage)
+                );
+            } else {
+                // a message at the limit is accepted, and the client waits
+                // for the rest of it
+                result.unwrap();
+                assert!(!harness.handshake_completed());
+            }
+        }
+    }
+
+    #[test]
+    fn test_records_sent() {
+        // each handshake message is sent in its own record, and both sides
+        // send a ChangeCipherSpec for middlebox compatibility. The default
+        // harness doesn't issue session tickets.
+        for (handshake_type, client_records, server_records) in [
+            (HandshakeType::ServerAuth, 3, 6),
+            (HandshakeType::MutualAuth, 5, 7),
+        ] {
+            let mut harness = S2NHarness::new(&CRYPTO_CONFIG, handshake_type).unwrap();
+            harness.handshake().unwrap();
+            assert!(harness.negotiated_tls13());
+            assert_eq!(
+                harness.records_sent(Mode::Client),
+                client_records,
+                "{handshake_type:?}"
+            );
+            assert_eq!(
+                harness.records_sent(Mode::Server),
+                server_records,
+                "{handshake_type:?}"
+            );
+        }
+    }
 }
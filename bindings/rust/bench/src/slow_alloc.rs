@@ -0,0 +1,80 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Global allocator that delays every allocation, to simulate platforms where
+//! allocation is expensive
+//!
+//! Comparing benches run with and without this allocator shows which
+//! operations are bound by allocation. With the `alloc-counter` feature also
+//! enabled, [`crate::alloc_counter::CountingAllocator`] counts allocations and
+//! delays them with this allocator.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    hint::spin_loop,
+    time::{Duration, Instant},
+};
+
+/// Delay added to every allocation and reallocation
+pub const ALLOCATION_DELAY: Duration = Duration::from_micros(1);
+
+pub struct SlowAllocator;
+
+impl SlowAllocator {
+    /// Busy wait, since sleeping is much less precise than the delay
+    fn delay() {
+        let start = Instant::now();
+        while start.elapsed() < ALLOCATION_DELAY {
+            spin_loop();
+        }
+    }
+}
+
+unsafe impl GlobalAlloc for SlowAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        Self::delay();
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        Self::delay();
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[cfg(not(feature = "alloc-counter"))]
+#[global_allocator]
+static ALLOCATOR: SlowAllocator = SlowAllocator;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{RustlsHarness, S2NHarness, TlsBenchHarness};
+    use std::hint::black_box;
+
+    #[test]
+    fn test_allocations_delayed() {
+        const ALLOCATIONS: u32 = 100;
+
+        let start = Instant::now();
+        for i in 0..ALLOCATIONS {
+            drop(black_box(Box::new(i)));
+        }
+        assert!(start.elapsed() >= ALLOCATIONS * ALLOCATION_DELAY);
+    }
+
+    #[test]
+    fn test_handshake_with_slow_allocator() {
+        let mut harness = S2NHarness::default().unwrap();
+        harness.handshake().unwrap();
+        assert!(harness.handshake_completed());
+
+        let mut harness = RustlsHarness::default().unwrap();
+        harness.handshake().unwrap();
+        assert!(harness.handshake_completed());
+    }
+}
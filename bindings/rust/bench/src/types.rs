@@ -0,0 +1,107 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Plain data types describing connections, shared by all harnesses
+//!
+//! This module only depends on `core`, so that embedded configuration code
+//! can reuse it in `no_std` contexts. `no-std-check` builds it under
+//! `#![no_std]`.
+
+// these parameters were the only ones readily usable for all three libaries:
+// s2n-tls, rustls, and openssl
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CipherSuite {
+    AES_128_GCM_SHA256,
+    AES_256_GCM_SHA384,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ECGroup {
+    SECP256R1,
+    X25519,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HandshakeType {
+    /// Only the server presents a certificate
+    ServerAuth,
+    /// Server requires a client certificate, which the client presents
+    MutualAuth,
+    /// Server requests but doesn't require a client certificate, and the
+    /// client declines to present one
+    OptionalClientAuth,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct CryptoConfig {
+    pub cipher_suite: CipherSuite,
+    pub ec_group: ECGroup,
+}
+
+impl CryptoConfig {
+    /// Whether all parameters are FIPS-approved
+    /// X25519 is not an approved key exchange group, and no FIPS policy of
+    /// s2n-tls prefers AES_256_GCM_SHA384 while offering secp256r1
+    pub fn is_fips_approved(&self) -> bool {
+        self.ec_group != ECGroup::X25519 && self.cipher_suite != CipherSuite::AES_256_GCM_SHA384
+    }
+}
+
+/// Key type of a certificate, which determines the signature algorithm used
+/// in the handshake
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SigType {
+    Ecdsa,
+    Rsa,
+}
+
+/// Order of the intermediate certs in the chain sent by the server
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntermediateOrder {
+    /// Each cert is followed by its issuer
+    Ordered,
+    /// Intermediates are in reverse order, so the client must build the path
+    Reversed,
+    /// The intermediate that issued the leaf is omitted, so the path can't
+    /// be built
+    Missing,
+}
+
+/// Alerts that can cause a handshake to fail, see RFC 8446 section 6
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertDescription {
+    UnexpectedMessage,
+    BadRecordMac,
+    HandshakeFailure,
+    BadCertificate,
+    CertificateExpired,
+    IllegalParameter,
+    UnknownCa,
+    DecodeError,
+    DecryptError,
+    ProtocolVersion,
+    MissingExtension,
+    CertificateRequired,
+    Other(u8),
+}
+
+impl From<u8> for AlertDescription {
+    fn from(code: u8) -> Self {
+        match code {
+            10 => Self::UnexpectedMessage,
+            20 => Self::BadRecordMac,
+            40 => Self::HandshakeFailure,
+            42 => Self::BadCertificate,
+            45 => Self::CertificateExpired,
+            47 => Self::IllegalParameter,
+            48 => Self::UnknownCa,
+            50 => Self::DecodeError,
+            51 => Self::DecryptError,
+            70 => Self::ProtocolVersion,
+            109 => Self::MissingExtension,
+            116 => Self::CertificateRequired,
+            code => Self::Other(code),
+        }
+    }
+}
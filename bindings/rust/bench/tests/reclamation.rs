@@ -0,0 +1,70 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Checks that connection state is freed when harnesses are dropped
+//!
+//! Resident set size is process-wide, so this is its own test binary to keep
+//! other tests from running concurrently and skewing the measurements.
+
+use bench::{harness::Mode, RustlsHarness, S2NHarness, TlsBenchHarness};
+use std::{error::Error, fs::read_to_string};
+
+/// Harnesses created and dropped before taking the baseline, so that lazily
+/// initialized library state and allocator caches aren't counted as leaks
+const WARMUP_CONNECTIONS: usize = 100;
+const CONNECTIONS: usize = 1000;
+/// Each connection holds tens of KB, so leaking connection state would grow
+/// RSS by far more than this over `CONNECTIONS`
+const MAX_RSS_GROWTH: usize = 4_000_000;
+
+/// Get the resident set size of this process in bytes
+fn resident_set_size() -> Result<usize, Box<dyn Error>> {
+    // the second field of statm is the number of resident pages
+    let statm = read_to_string("/proc/self/statm")?;
+    let pages: usize = statm
+        .split_whitespace()
+        .nth(1)
+        .ok_or("invalid statm")?
+        .parse()?;
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+    Ok(pages * page_size)
+}
+
+/// Create, handshake, transfer data over, and drop `count` harnesses
+fn drop_connections<T: TlsBenchHarness>(count: usize) -> Result<(), Box<dyn Error>> {
+    let mut received = [0; 1000];
+    for _ in 0..count {
+        let mut harness = T::default()?;
+        harness.handshake()?;
+        harness.send(Mode::Client, &[1; 1000])?;
+        harness.recv(Mode::Server, &mut received)?;
+        harness.send(Mode::Server, &[2; 1000])?;
+        harness.recv(Mode::Client, &mut received)?;
+        harness.shutdown()?;
+    }
+    Ok(())
+}
+
+/// Get how much RSS grew over dropping `CONNECTIONS` harnesses, after warming
+/// up. May be negative if memory was returned to the OS.
+fn drop_and_measure<T: TlsBenchHarness>() -> Result<isize, Box<dyn Error>> {
+    drop_connections::<T>(WARMUP_CONNECTIONS)?;
+    let baseline = resident_set_size()?;
+    drop_connections::<T>(CONNECTIONS)?;
+    Ok(resident_set_size()? as isize - baseline as isize)
+}
+
+#[test]
+fn test_dropped_connections_are_reclaimed() {
+    // run sequentially in one test so neither library skews the other's
+    // measurements
+    for (name, growth) in [
+        ("s2n-tls", drop_and_measure::<S2NHarness>().unwrap()),
+        ("rustls", drop_and_measure::<RustlsHarness>().unwrap()),
+    ] {
+        assert!(
+            growth < MAX_RSS_GROWTH as isize,
+            "{name}: RSS grew by {growth} bytes over {CONNECTIONS} connections"
+        );
+    }
+}
@@ -35,6 +35,12 @@ pub use client_hello::*;
 mod pkey;
 pub use pkey::*;
 
+mod session_cache;
+pub use session_cache::*;
+
+mod session_ticket;
+pub use session_ticket::*;
+
 /// Convert the connection pointer provided to a callback into a Connection
 /// and Context useable with the Rust bindings.
 ///
@@ -0,0 +1,25 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Support for application-implemented server-side session caches.
+
+use core::time::Duration;
+
+/// A trait for a server-side cache of session state, used to resume TLS1.2
+/// sessions by session ID rather than session ticket.
+///
+/// Use in conjunction with
+/// [config::Builder::set_session_cache](`crate::config::Builder::set_session_cache()`).
+pub trait SessionCache {
+    /// Store the serialized session state `value` under the session ID `key`.
+    ///
+    /// The entry should be kept for at most `ttl`.
+    fn store(&self, key: &[u8], value: &[u8], ttl: Duration);
+
+    /// Retrieve the serialized session state stored under the session ID `key`,
+    /// or `None` if the session isn't cached.
+    fn retrieve(&self, key: &[u8]) -> Option<Vec<u8>>;
+
+    /// Delete the session state stored under the session ID `key`.
+    fn delete(&self, key: &[u8]);
+}
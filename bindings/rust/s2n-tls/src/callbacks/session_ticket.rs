@@ -0,0 +1,66 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Support for application-implemented session ticket callbacks.
+
+use crate::{connection::Connection, error::Fallible};
+use core::time::Duration;
+use s2n_tls_sys::*;
+
+/// A session ticket received by a client connection.
+///
+/// The ticket can be stored and later passed to
+/// [Connection::set_session_ticket()](`crate::connection::Connection::set_session_ticket()`)
+/// to resume the session.
+pub struct SessionTicket(s2n_session_ticket);
+
+impl SessionTicket {
+    pub(crate) fn from_ptr(ticket: &s2n_session_ticket) -> &Self {
+        unsafe { &*(ticket as *const s2n_session_ticket as *const SessionTicket) }
+    }
+
+    // SAFETY: casting *const s2n_session_ticket -> *mut s2n_session_ticket: This is
+    // safe as long as the data is not actually mutated. None of the s2n_session_ticket
+    // accessors mutate the ticket.
+    fn deref_mut_ptr(&self) -> *mut s2n_session_ticket {
+        &self.0 as *const s2n_session_ticket as *mut s2n_session_ticket
+    }
+
+    /// The lifetime hint the server assigned to the ticket.
+    pub fn lifetime(&self) -> Result<Duration, crate::error::Error> {
+        let mut lifetime = 0;
+        unsafe {
+            s2n_session_ticket_get_lifetime(self.deref_mut_ptr(), &mut lifetime).into_result()
+        }?;
+        Ok(Duration::new(lifetime.into(), 0))
+    }
+
+    /// The length of the serialized session state.
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> Result<usize, crate::error::Error> {
+        let mut data_len = 0;
+        unsafe {
+            s2n_session_ticket_get_data_len(self.deref_mut_ptr(), &mut data_len).into_result()
+        }?;
+        Ok(data_len)
+    }
+
+    /// Copies the serialized session state into `output`.
+    ///
+    /// `output` must be at least [`Self::len()`] bytes long.
+    pub fn data(&self, output: &mut [u8]) -> Result<(), crate::error::Error> {
+        unsafe {
+            s2n_session_ticket_get_data(self.deref_mut_ptr(), output.len(), output.as_mut_ptr())
+                .into_result()
+        }?;
+        Ok(())
+    }
+}
+
+/// A trait for the callback executed when a client connection receives a session ticket.
+///
+/// Use in conjunction with
+/// [config::Builder::set_session_ticket_callback](`crate::config::Builder::set_session_ticket_callback()`).
+pub trait SessionTicketCallback {
+    fn on_session_ticket(&self, connection: &mut Connection, session_ticket: &SessionTicket);
+}
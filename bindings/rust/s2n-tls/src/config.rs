@@ -13,6 +13,7 @@ use std::{
     ffi::{c_void, CString},
     path::Path,
     sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
 #[derive(Debug, PartialEq)]
@@ -347,6 +348,29 @@ impl Builder {
         Ok(self)
     }
 
+    /// Clients will request that the server limits the size of the records it sends.
+    ///
+    /// Corresponds to the underlying C API
+    /// [s2n_config_send_max_fragment_length](https://aws.github.io/s2n-tls/doxygen/s2n_8h.html).
+    pub fn send_max_fragment_length(
+        &mut self,
+        length: MaxFragmentLength,
+    ) -> Result<&mut Self, Error> {
+        unsafe {
+            s2n_config_send_max_fragment_length(self.as_mut_ptr(), length.into()).into_result()
+        }?;
+        Ok(self)
+    }
+
+    /// Servers will limit the size of the records they send if requested by the client.
+    ///
+    /// Corresponds to the underlying C API
+    /// [s2n_config_accept_max_fragment_length](https://aws.github.io/s2n-tls/doxygen/s2n_8h.html).
+    pub fn accept_max_fragment_length(&mut self) -> Result<&mut Self, Error> {
+        unsafe { s2n_config_accept_max_fragment_length(self.as_mut_ptr()).into_result() }?;
+        Ok(self)
+    }
+
     /// Clients will request OCSP stapling from the server.
     pub fn enable_ocsp(&mut self) -> Result<&mut Self, Error> {
         unsafe {
@@ -356,6 +380,18 @@ impl Builder {
         Ok(self)
     }
 
+    /// Clients will request signed certificate timestamps from the server.
+    ///
+    /// Corresponds to the underlying C API
+    /// [s2n_config_set_ct_support_level](https://aws.github.io/s2n-tls/doxygen/s2n_8h.html).
+    pub fn enable_certificate_transparency(&mut self) -> Result<&mut Self, Error> {
+        unsafe {
+            s2n_config_set_ct_support_level(self.as_mut_ptr(), s2n_ct_support_level::REQUEST)
+                .into_result()
+        }?;
+        Ok(self)
+    }
+
     /// Sets the OCSP data for the default certificate chain associated with the Config.
     ///
     /// Servers will send the data in response to OCSP stapling requests from clients.
@@ -414,6 +450,204 @@ impl Builder {
         Ok(self)
     }
 
+    /// Enables or disables session resumption using session tickets.
+    ///
+    /// Servers also need at least one key added with [`Self::add_session_ticket_key()`]
+    /// to issue tickets.
+    pub fn enable_session_tickets(&mut self, enable: bool) -> Result<&mut Self, Error> {
+        unsafe {
+            s2n_config_set_session_tickets_onoff(self.as_mut_ptr(), enable.into()).into_result()
+        }?;
+        Ok(self)
+    }
+
+    /// Adds a key used by servers to encrypt and decrypt session tickets.
+    ///
+    /// The key becomes active for encryption at `intro_time`.
+    ///
+    /// Corresponds to the underlying C API
+    /// [s2n_config_add_ticket_crypto_key](https://aws.github.io/s2n-tls/doxygen/s2n_8h.html).
+    pub fn add_session_ticket_key(
+        &mut self,
+        key_name: &[u8],
+        key: &[u8],
+        intro_time: SystemTime,
+    ) -> Result<&mut Self, Error> {
+        let key_name_len: u32 = key_name
+            .len()
+            .try_into()
+            .map_err(|_| Error::INVALID_INPUT)?;
+        let key_len: u32 = key.len().try_into().map_err(|_| Error::INVALID_INPUT)?;
+        let intro_time = intro_time
+            .duration_since(UNIX_EPOCH)
+            .map_err(|_| Error::INVALID_INPUT)?;
+        // Adding a ticket key implies the application wants to issue tickets.
+        // s2n-tls ignores keys added while tickets are disabled, so enable them first.
+        self.enable_session_tickets(true)?;
+        unsafe {
+            s2n_config_add_ticket_crypto_key(
+                self.as_mut_ptr(),
+                key_name.as_ptr(),
+                key_name_len,
+                // s2n-tls does not mutate the key, but the C API takes a non-const pointer
+                key.as_ptr() as *mut u8,
+                key_len,
+                intro_time.as_secs(),
+            )
+            .into_result()
+        }?;
+        Ok(self)
+    }
+
+    /// Sets the number of session tickets a TLS1.3 server sends after the handshake.
+    ///
+    /// Corresponds to the underlying C API
+    /// [s2n_config_set_initial_ticket_count](https://aws.github.io/s2n-tls/doxygen/s2n_8h.html).
+    pub fn set_initial_ticket_count(&mut self, count: u8) -> Result<&mut Self, Error> {
+        unsafe { s2n_config_set_initial_ticket_count(self.as_mut_ptr(), count).into_result() }?;
+        Ok(self)
+    }
+
+    /// Sets the callback executed when a client connection receives a session ticket.
+    ///
+    /// Corresponds to the underlying C API
+    /// [s2n_config_set_session_ticket_cb](https://aws.github.io/s2n-tls/doxygen/s2n_8h.html).
+    pub fn set_session_ticket_callback<T: 'static + SessionTicketCallback>(
+        &mut self,
+        handler: T,
+    ) -> Result<&mut Self, Error> {
+        unsafe extern "C" fn session_ticket_cb(
+            conn_ptr: *mut s2n_connection,
+            _context: *mut ::libc::c_void,
+            session_ticket: *mut s2n_session_ticket,
+        ) -> libc::c_int {
+            let session_ticket = SessionTicket::from_ptr(&*session_ticket);
+            with_context(conn_ptr, |conn, context| {
+                let callback = context.session_ticket_callback.as_ref();
+                if let Some(callback) = callback {
+                    callback.on_session_ticket(conn, session_ticket);
+                }
+            });
+            CallbackResult::Success.into()
+        }
+
+        let handler = Box::new(handler);
+        let context = self.config.context_mut();
+        context.session_ticket_callback = Some(handler);
+
+        unsafe {
+            s2n_config_set_session_ticket_cb(
+                self.as_mut_ptr(),
+                Some(session_ticket_cb),
+                core::ptr::null_mut(),
+            )
+            .into_result()?;
+        }
+        // Receiving tickets requires tickets to be enabled
+        self.enable_session_tickets(true)
+    }
+
+    /// Sets the cache used by servers to store and retrieve TLS1.2 sessions by
+    /// session ID, and enables session caching.
+    ///
+    /// Cache entries are encrypted with the session ticket keys, so at least
+    /// one key should be added with [`Self::add_session_ticket_key()`].
+    ///
+    /// Corresponds to the underlying C APIs
+    /// [s2n_config_set_cache_store_callback](https://aws.github.io/s2n-tls/doxygen/s2n_8h.html),
+    /// [s2n_config_set_cache_retrieve_callback](https://aws.github.io/s2n-tls/doxygen/s2n_8h.html),
+    /// and [s2n_config_set_cache_delete_callback](https://aws.github.io/s2n-tls/doxygen/s2n_8h.html).
+    pub fn set_session_cache<T: 'static + SessionCache>(
+        &mut self,
+        cache: T,
+    ) -> Result<&mut Self, Error> {
+        unsafe extern "C" fn cache_store_cb(
+            conn_ptr: *mut s2n_connection,
+            _context: *mut ::libc::c_void,
+            ttl_in_seconds: u64,
+            key: *const ::libc::c_void,
+            key_size: u64,
+            value: *const ::libc::c_void,
+            value_size: u64,
+        ) -> libc::c_int {
+            let key = core::slice::from_raw_parts(key as *const u8, key_size as usize);
+            let value = core::slice::from_raw_parts(value as *const u8, value_size as usize);
+            with_context(conn_ptr, |_conn, context| {
+                if let Some(cache) = context.session_cache.as_ref() {
+                    cache.store(key, value, Duration::from_secs(ttl_in_seconds));
+                }
+            });
+            CallbackResult::Success.into()
+        }
+
+        unsafe extern "C" fn cache_retrieve_cb(
+            conn_ptr: *mut s2n_connection,
+            _context: *mut ::libc::c_void,
+            key: *const ::libc::c_void,
+            key_size: u64,
+            value: *mut ::libc::c_void,
+            value_size: *mut u64,
+        ) -> libc::c_int {
+            let key = core::slice::from_raw_parts(key as *const u8, key_size as usize);
+            let entry = with_context(conn_ptr, |_conn, context| {
+                context
+                    .session_cache
+                    .as_ref()
+                    .and_then(|cache| cache.retrieve(key))
+            });
+            match entry {
+                Some(entry) if entry.len() as u64 <= *value_size => {
+                    core::ptr::copy_nonoverlapping(entry.as_ptr(), value as *mut u8, entry.len());
+                    *value_size = entry.len() as u64;
+                    CallbackResult::Success.into()
+                }
+                _ => CallbackResult::Failure.into(),
+            }
+        }
+
+        unsafe extern "C" fn cache_delete_cb(
+            conn_ptr: *mut s2n_connection,
+            _context: *mut ::libc::c_void,
+            key: *const ::libc::c_void,
+            key_size: u64,
+        ) -> libc::c_int {
+            let key = core::slice::from_raw_parts(key as *const u8, key_size as usize);
+            with_context(conn_ptr, |_conn, context| {
+                if let Some(cache) = context.session_cache.as_ref() {
+                    cache.delete(key);
+                }
+            });
+            CallbackResult::Success.into()
+        }
+
+        let cache = Box::new(cache);
+        let context = self.config.context_mut();
+        context.session_cache = Some(cache);
+
+        unsafe {
+            s2n_config_set_cache_store_callback(
+                self.as_mut_ptr(),
+                Some(cache_store_cb),
+                core::ptr::null_mut(),
+            )
+            .into_result()?;
+            s2n_config_set_cache_retrieve_callback(
+                self.as_mut_ptr(),
+                Some(cache_retrieve_cb),
+                core::ptr::null_mut(),
+            )
+            .into_result()?;
+            s2n_config_set_cache_delete_callback(
+                self.as_mut_ptr(),
+                Some(cache_delete_cb),
+                core::ptr::null_mut(),
+            )
+            .into_result()?;
+            s2n_config_set_session_cache_onoff(self.as_mut_ptr(), 1).into_result()?;
+        }
+        Ok(self)
+    }
+
     /// # Safety
     /// THIS SHOULD BE USED FOR DEBUGGING PURPOSES ONLY!
     /// The `context` pointer must live at least as long as the config
@@ -606,6 +840,8 @@ pub(crate) struct Context {
     pub(crate) client_hello_callback: Option<Box<dyn ClientHelloCallback>>,
     pub(crate) private_key_callback: Option<Box<dyn PrivateKeyCallback>>,
     pub(crate) verify_host_callback: Option<Box<dyn VerifyHostNameCallback>>,
+    pub(crate) session_ticket_callback: Option<Box<dyn SessionTicketCallback>>,
+    pub(crate) session_cache: Option<Box<dyn SessionCache>>,
     pub(crate) wall_clock: Option<Box<dyn WallClock>>,
     pub(crate) monotonic_clock: Option<Box<dyn MonotonicClock>>,
 }
@@ -621,6 +857,8 @@ impl Default for Context {
             client_hello_callback: None,
             private_key_callback: None,
             verify_host_callback: None,
+            session_ticket_callback: None,
+            session_cache: None,
             wall_clock: None,
             monotonic_clock: None,
         }
@@ -22,7 +22,7 @@ use core::{
 };
 use libc::c_void;
 use s2n_tls_sys::*;
-use std::ffi::CStr;
+use std::{ffi::CStr, io::IoSlice};
 
 mod builder;
 pub use builder::*;
@@ -485,6 +485,19 @@ impl Connection {
         unsafe { s2n_send(self.connection.as_ptr(), buf_ptr, buf_len, &mut blocked).into_poll() }
     }
 
+    /// Encrypts and sends data from multiple buffers on a connection where
+    /// [negotiate](`Self::poll_negotiate`) has succeeded.
+    ///
+    /// Data from all the buffers is coalesced into as few records as possible.
+    /// Returns the number of bytes written, and may indicate a partial write.
+    pub fn poll_sendv(&mut self, bufs: &[IoSlice]) -> Poll<Result<usize, Error>> {
+        let mut blocked = s2n_blocked_status::NOT_BLOCKED;
+        let count: isize = bufs.len().try_into().map_err(|_| Error::INVALID_INPUT)?;
+        // IoSlice is guaranteed to be ABI compatible with iovec on unix
+        let bufs = bufs.as_ptr() as *const libc::iovec;
+        unsafe { s2n_sendv(self.connection.as_ptr(), bufs, count, &mut blocked).into_poll() }
+    }
+
     /// Reads and decrypts data from a connection where
     /// [negotiate](`Self::poll_negotiate`) has succeeded.
     ///
@@ -551,6 +564,44 @@ impl Connection {
         }
     }
 
+    /// Sets the serialized session state to resume, as received by
+    /// [SessionTicketCallback](`crate::callbacks::SessionTicketCallback`).
+    ///
+    /// Must be called on a client connection before the handshake.
+    pub fn set_session_ticket(&mut self, session: &[u8]) -> Result<&mut Self, Error> {
+        unsafe {
+            s2n_connection_set_session(self.connection.as_ptr(), session.as_ptr(), session.len())
+                .into_result()
+        }?;
+        Ok(self)
+    }
+
+    /// Returns the serialized session state, which can be passed to
+    /// [`Self::set_session_ticket()`] on a later connection to resume the session.
+    ///
+    /// For TLS1.2 sessions without a session ticket, the state includes the
+    /// session ID to resume with the server's session cache. In TLS1.3, use
+    /// [SessionTicketCallback](`crate::callbacks::SessionTicketCallback`) instead.
+    pub fn session(&mut self) -> Result<Vec<u8>, Error> {
+        let len = unsafe { s2n_connection_get_session_length(self.connection.as_ptr()) };
+        let len: usize = len
+            .into_result()?
+            .try_into()
+            .map_err(|_| Error::INVALID_INPUT)?;
+        let mut session = vec![0; len];
+        let copied = unsafe {
+            s2n_connection_get_session(self.connection.as_ptr(), session.as_mut_ptr(), len)
+                .into_result()
+        }?;
+        session.truncate(copied as usize);
+        Ok(session)
+    }
+
+    /// Returns true if the handshake resumed a previous session.
+    pub fn resumed(&self) -> bool {
+        unsafe { s2n_connection_is_session_resumed(self.connection.as_ptr()) == 1 }
+    }
+
     /// Returns the TLS alert code, if any
     pub fn alert(&self) -> Option<u8> {
         let alert =
@@ -578,6 +629,17 @@ impl Connection {
         }
     }
 
+    /// Returns the application protocol negotiated with ALPN, if any.
+    pub fn application_protocol(&self) -> Option<&[u8]> {
+        unsafe {
+            let protocol = s2n_get_application_protocol(self.connection.as_ptr());
+            match protocol.into_result() {
+                Ok(protocol) => Some(CStr::from_ptr(protocol).to_bytes()),
+                Err(_) => None,
+            }
+        }
+    }
+
     /// Sets a Waker on the connection context or clears it if `None` is passed.
     pub fn set_waker(&mut self, waker: Option<&Waker>) -> Result<&mut Self, Error> {
         let ctx = self.context_mut();
@@ -678,6 +740,79 @@ impl Connection {
         unsafe { Ok(Some(std::slice::from_raw_parts(chain, len as usize))) }
     }
 
+    /// Returns the number of certificates in the peer's validated certificate
+    /// chain, from its leaf certificate up to and including the trust anchor.
+    ///
+    /// The trust anchor is counted even if the peer didn't send it.
+    ///
+    /// Corresponds to [s2n_connection_get_peer_cert_chain](https://aws.github.io/s2n-tls/doxygen/s2n_8h.html)
+    /// and [s2n_cert_chain_get_length](https://aws.github.io/s2n-tls/doxygen/s2n_8h.html).
+    pub fn peer_cert_chain_len(&self) -> Result<usize, Error> {
+        let chain = unsafe { s2n_cert_chain_and_key_new().into_result()? }.as_ptr();
+        let mut len = 0;
+        // the chain is only used to read its length, so it's freed either way
+        let result = unsafe {
+            s2n_connection_get_peer_cert_chain(self.connection.as_ptr(), chain)
+                .into_result()
+                .and_then(|_| s2n_cert_chain_get_length(chain, &mut len).into_result())
+        };
+        unsafe { s2n_cert_chain_and_key_free(chain).into_result() }?;
+        result?;
+        Ok(len as usize)
+    }
+
+    /// Adds an external pre-shared key (PSK) to the connection, using SHA256
+    /// as its HMAC algorithm.
+    ///
+    /// PSKs are only used in TLS1.3. The client offers its PSKs in order, and
+    /// the server chooses the first offered PSK with an identity it also has.
+    ///
+    /// Corresponds to [s2n_connection_append_psk](https://aws.github.io/s2n-tls/doxygen/s2n_8h.html).
+    pub fn append_psk(&mut self, identity: &[u8], secret: &[u8]) -> Result<&mut Self, Error> {
+        let identity_len: u16 = identity
+            .len()
+            .try_into()
+            .map_err(|_| Error::INVALID_INPUT)?;
+        let secret_len: u16 = secret.len().try_into().map_err(|_| Error::INVALID_INPUT)?;
+
+        let mut psk = unsafe { s2n_external_psk_new().into_result()? }.as_ptr();
+        // the connection stores a copy of the PSK, so it's freed either way
+        let result = unsafe {
+            s2n_psk_set_identity(psk, identity.as_ptr(), identity_len)
+                .into_result()
+                .and_then(|_| s2n_psk_set_secret(psk, secret.as_ptr(), secret_len).into_result())
+                .and_then(|_| {
+                    s2n_connection_append_psk(self.connection.as_ptr(), psk).into_result()
+                })
+        };
+        unsafe { s2n_psk_free(&mut psk).into_result() }?;
+        result?;
+        Ok(self)
+    }
+
+    /// Returns the identity of the external PSK negotiated in the handshake, if any.
+    pub fn negotiated_psk_identity(&self) -> Result<Option<Vec<u8>>, Error> {
+        let mut len = 0;
+        unsafe {
+            s2n_connection_get_negotiated_psk_identity_length(self.connection.as_ptr(), &mut len)
+                .into_result()?;
+        }
+        if len == 0 {
+            return Ok(None);
+        }
+
+        let mut identity = vec![0; len as usize];
+        unsafe {
+            s2n_connection_get_negotiated_psk_identity(
+                self.connection.as_ptr(),
+                identity.as_mut_ptr(),
+                len,
+            )
+            .into_result()?;
+        }
+        Ok(Some(identity))
+    }
+
     // The memory backing the ClientHello is owned by the Connection, so we
     // tie the ClientHello to the lifetime of the Connection. This is validated
     // with a doc test that ensures the ClientHello is invalid once the
@@ -753,6 +888,14 @@ impl Connection {
         static_const_str!(curve)
     }
 
+    pub fn kem_group_name(&self) -> Result<&str, Error> {
+        let group =
+            unsafe { s2n_connection_get_kem_group_name(self.connection.as_ptr()).into_result()? };
+        // The strings returned by s2n_connection_get_kem_group_name are
+        // either "NONE" or const fields on static const structs
+        static_const_str!(group)
+    }
+
     pub fn selected_signature_algorithm(&self) -> Result<SignatureAlgorithm, Error> {
         let mut sig_alg = s2n_tls_signature_algorithm::ANONYMOUS;
         unsafe {
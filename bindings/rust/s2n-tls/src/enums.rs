@@ -108,6 +108,26 @@ impl From<ClientAuthType> for s2n_cert_auth_type::Type {
     }
 }
 
+#[non_exhaustive]
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum MaxFragmentLength {
+    Bytes512,
+    Bytes1024,
+    Bytes2048,
+    Bytes4096,
+}
+
+impl From<MaxFragmentLength> for s2n_max_frag_len::Type {
+    fn from(input: MaxFragmentLength) -> s2n_max_frag_len::Type {
+        match input {
+            MaxFragmentLength::Bytes512 => s2n_max_frag_len::LEN_512,
+            MaxFragmentLength::Bytes1024 => s2n_max_frag_len::LEN_1024,
+            MaxFragmentLength::Bytes2048 => s2n_max_frag_len::LEN_2048,
+            MaxFragmentLength::Bytes4096 => s2n_max_frag_len::LEN_4096,
+        }
+    }
+}
+
 #[non_exhaustive]
 #[derive(Debug, PartialEq, Copy, Clone)]
 pub enum AlertBehavior {
@@ -32,10 +32,18 @@ impl Harness {
     pub fn connection(&self) -> &Connection {
         &self.connection
     }
-}
 
-impl super::Connection for Harness {
-    fn poll<Ctx: Context>(&mut self, context: &mut Ctx) -> Poll<Result<()>> {
+    pub fn connection_mut(&mut self) -> &mut Connection {
+        &mut self.connection
+    }
+
+    /// Calls `f` with the connection while it sends to and receives from
+    /// `context`, e.g. to exchange application data after the handshake
+    pub fn with_io<Ctx: Context, T>(
+        &mut self,
+        context: &mut Ctx,
+        f: impl FnOnce(&mut Connection) -> T,
+    ) -> Result<T> {
         let mut callback: Callback<Ctx> = Callback {
             context,
             err: None,
@@ -48,10 +56,18 @@ impl super::Connection for Harness {
             callback.set(&mut self.connection);
         }
 
-        let result = self.connection.poll_negotiate().map_ok(|_| ());
+        let result = f(&mut self.connection);
 
         callback.unset(&mut self.connection)?;
 
+        Ok(result)
+    }
+}
+
+impl super::Connection for Harness {
+    fn poll<Ctx: Context>(&mut self, context: &mut Ctx) -> Poll<Result<()>> {
+        let result = self.with_io(context, |conn| conn.poll_negotiate().map_ok(|_| ()))?;
+
         match result {
             Poll::Ready(Ok(_)) => {
                 if !self.handshake_done {
@@ -209,7 +225,39 @@ mod tests {
     use alloc::sync::Arc;
     use core::sync::atomic::Ordering;
     use futures_test::task::new_count_waker;
-    use std::{fs, path::Path, pin::Pin, sync::atomic::AtomicUsize};
+    use std::{fs, io::IoSlice, path::Path, pin::Pin, sync::atomic::AtomicUsize};
+
+    /// Transfers the data sent by the client, then receives application data
+    /// on the server until `len` bytes arrive
+    fn server_recv(pair: &mut Pair<Harness, Harness>, len: usize) -> Result<Vec<u8>, Error> {
+        pair.client.1.transfer(&mut pair.server.1);
+        let (server, context) = &mut pair.server;
+        let mut received = vec![0; len];
+        let mut filled = 0;
+        while filled < len {
+            match server.with_io(context, |conn| conn.poll_recv(&mut received[filled..]))? {
+                Poll::Ready(Ok(0)) | Poll::Pending => {
+                    return Err("not all data was received".into())
+                }
+                Poll::Ready(result) => filled += result?,
+            }
+        }
+        Ok(received)
+    }
+
+    /// Returns the length of each record sent through `context` that hasn't
+    /// been transferred to the peer yet
+    fn sent_record_lens(context: &MemoryContext) -> Vec<usize> {
+        let sent: Vec<u8> = context.tx.iter().flatten().copied().collect();
+        let mut lens = Vec::new();
+        let mut pos = 0;
+        while pos + 5 <= sent.len() {
+            let len = u16::from_be_bytes([sent[pos + 3], sent[pos + 4]]) as usize;
+            lens.push(len);
+            pos += 5 + len;
+        }
+        lens
+    }
 
     #[test]
     fn handshake_default() {
@@ -654,6 +702,261 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn peer_cert_chain_len() -> Result<(), Error> {
+        let keypair = CertKeyPair::default();
+        let config = {
+            let mut config = Builder::new();
+            config
+                .load_pem(keypair.cert(), keypair.key())?
+                .set_security_policy(&security::DEFAULT_TLS13)?
+                .set_verify_host_callback(InsecureAcceptAllCertificatesHandler {})?
+                .trust_pem(keypair.cert())?;
+            config.build()?
+        };
+
+        // the test cert is self-signed, so it's both the leaf and the trust anchor
+        let pair = poll_tls_pair(tls_pair(config));
+        assert_eq!(pair.client.0.connection.peer_cert_chain_len()?, 1);
+
+        // without x509 verification, there's no validated chain
+        let pair = poll_tls_pair(tls_pair(build_config(&security::DEFAULT_TLS13)?));
+        assert!(pair.client.0.connection.peer_cert_chain_len().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn poll_sendv() -> Result<(), Error> {
+        let config = build_config(&security::DEFAULT_TLS13)?;
+        let mut pair = poll_tls_pair(tls_pair(config));
+
+        let bufs: [&[u8]; 4] = [b"hello", b"", b", vectored", b" world"];
+        let slices: Vec<IoSlice> = bufs.iter().map(|buf| IoSlice::new(buf)).collect();
+        let expected = bufs.concat();
+
+        let (client, context) = &mut pair.client;
+        match client.with_io(context, |conn| conn.poll_sendv(&slices))? {
+            Poll::Ready(len) => assert_eq!(len?, expected.len()),
+            Poll::Pending => panic!("send blocked"),
+        }
+        assert_eq!(server_recv(&mut pair, expected.len())?, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn external_psk() -> Result<(), Error> {
+        const SECRET: &[u8] = &[1; 32];
+
+        let config = build_config(&security::DEFAULT_TLS13)?;
+        let handshake = |client_identity: &[u8], server_identity: &[u8]| -> Result<_, Error> {
+            let mut pair = tls_pair(config.clone());
+            pair.client
+                .0
+                .connection_mut()
+                .append_psk(client_identity, SECRET)?;
+            pair.server
+                .0
+                .connection_mut()
+                .append_psk(server_identity, SECRET)?;
+            Ok(poll_tls_pair(pair))
+        };
+
+        let pair = handshake(b"identity", b"identity")?;
+        for harness in [&pair.client.0, &pair.server.0] {
+            let identity = harness.connection().negotiated_psk_identity()?;
+            assert_eq!(identity.as_deref(), Some(&b"identity"[..]));
+        }
+
+        // without a matching identity, the server falls back to a full handshake
+        let pair = handshake(b"client identity", b"server identity")?;
+        for harness in [&pair.client.0, &pair.server.0] {
+            assert_eq!(harness.connection().negotiated_psk_identity()?, None);
+        }
+
+        // identity lengths must fit in a u16
+        let mut connection = crate::connection::Connection::new_client();
+        let err = connection
+            .append_psk(&[0; u16::MAX as usize + 1], SECRET)
+            .unwrap_err();
+        assert_eq!(err.name(), crate::error::Error::INVALID_INPUT.name());
+
+        Ok(())
+    }
+
+    #[test]
+    fn max_fragment_length() -> Result<(), Error> {
+        use crate::enums::MaxFragmentLength;
+
+        // TLS1.3 records add a content type byte and a 16 byte AEAD tag
+        const RECORD_OVERHEAD: usize = 17;
+        let data = [0; 10_000];
+
+        let send_from_server = |config| -> Result<Vec<usize>, Error> {
+            let mut pair = poll_tls_pair(tls_pair(config));
+            let (server, context) = &mut pair.server;
+            match server.with_io(context, |conn| conn.poll_send(&data))? {
+                Poll::Ready(len) => assert_eq!(len?, data.len()),
+                Poll::Pending => panic!("send blocked"),
+            }
+            Ok(sent_record_lens(context))
+        };
+
+        for (length, limit) in [
+            (MaxFragmentLength::Bytes512, 512),
+            (MaxFragmentLength::Bytes1024, 1024),
+            (MaxFragmentLength::Bytes2048, 2048),
+            (MaxFragmentLength::Bytes4096, 4096),
+        ] {
+            let config = {
+                let mut config = config_builder(&security::DEFAULT_TLS13)?;
+                config
+                    .send_max_fragment_length(length)?
+                    .accept_max_fragment_length()?;
+                config.build()?
+            };
+            // the server limits its records to the length requested by the client
+            let record_lens = send_from_server(config)?;
+            assert_eq!(record_lens[0], limit + RECORD_OVERHEAD, "{:?}", length);
+            assert!(
+                record_lens
+                    .iter()
+                    .all(|len| *len <= limit + RECORD_OVERHEAD),
+                "{:?}",
+                length
+            );
+        }
+
+        // without the extension, records are larger than the largest limit
+        let record_lens = send_from_server(build_config(&security::DEFAULT_TLS13)?)?;
+        assert!(record_lens[0] > 4096 + RECORD_OVERHEAD);
+
+        Ok(())
+    }
+
+    #[test]
+    fn certificate_transparency() -> Result<(), Error> {
+        use crate::{client_hello::FingerprintType, config::Config};
+
+        const SIGNED_CERTIFICATE_TIMESTAMP: u16 = 18;
+
+        // the extension types in the ClientHello received by the server
+        let client_hello_extensions = |config: Config| -> Result<Vec<u16>, Error> {
+            let pair = poll_tls_pair(tls_pair(config));
+            let client_hello = pair.server.0.connection().client_hello()?;
+            let mut hash = Vec::new();
+            let string_size = client_hello.fingerprint_hash(FingerprintType::JA3, &mut hash)?;
+            let mut ja3 = String::with_capacity(string_size as usize);
+            client_hello.fingerprint_string(FingerprintType::JA3, &mut ja3)?;
+            // the third field of a JA3 string lists the extension types
+            let extensions = ja3.split(',').nth(2).unwrap_or_default();
+            Ok(extensions
+                .split('-')
+                .filter_map(|extension| extension.parse().ok())
+                .collect())
+        };
+
+        let extensions = client_hello_extensions(build_config(&security::DEFAULT_TLS13)?)?;
+        assert!(!extensions.contains(&SIGNED_CERTIFICATE_TIMESTAMP));
+
+        let config = {
+            let mut config = config_builder(&security::DEFAULT_TLS13)?;
+            config.enable_certificate_transparency()?;
+            config.build()?
+        };
+        // the server has no timestamps to send, so the handshake still succeeds
+        let extensions = client_hello_extensions(config)?;
+        assert!(extensions.contains(&SIGNED_CERTIFICATE_TIMESTAMP));
+
+        Ok(())
+    }
+
+    #[test]
+    fn session_ticket_resumption() -> Result<(), Error> {
+        use crate::callbacks::{SessionTicket, SessionTicketCallback};
+        use std::{sync::Mutex, time::SystemTime};
+
+        #[derive(Default, Clone)]
+        struct TicketStore(Arc<Mutex<Option<Vec<u8>>>>);
+        impl SessionTicketCallback for TicketStore {
+            fn on_session_ticket(
+                &self,
+                _connection: &mut crate::connection::Connection,
+                ticket: &SessionTicket,
+            ) {
+                let mut data = vec![0; ticket.len().unwrap()];
+                ticket.data(&mut data).unwrap();
+                *self.0.lock().unwrap() = Some(data);
+            }
+        }
+
+        let store = TicketStore::default();
+        let config = {
+            // TLS1.2 tickets are sent during the handshake, so no application
+            // data needs to be exchanged to receive one
+            let mut config = config_builder(&security::DEFAULT)?;
+            config.add_session_ticket_key(b"key name", &[0; 32], SystemTime::now())?;
+            config.set_session_ticket_callback(store.clone())?;
+            config.build()?
+        };
+
+        let pair = poll_tls_pair(tls_pair(config.clone()));
+        assert!(!pair.client.0.connection.resumed());
+        let ticket = store.0.lock().unwrap().take().expect("ticket not received");
+
+        let mut pair = tls_pair(config);
+        pair.client.0.connection.set_session_ticket(&ticket)?;
+        let pair = poll_tls_pair(pair);
+        assert!(pair.client.0.connection.resumed());
+        assert!(pair.server.0.connection.resumed());
+
+        Ok(())
+    }
+
+    #[test]
+    fn session_cache_resumption() -> Result<(), Error> {
+        use crate::callbacks::SessionCache;
+        use std::{collections::HashMap, sync::Mutex, time::SystemTime};
+
+        #[derive(Default, Clone)]
+        struct Cache(Arc<Mutex<HashMap<Vec<u8>, Vec<u8>>>>);
+        impl SessionCache for Cache {
+            fn store(&self, key: &[u8], value: &[u8], _ttl: core::time::Duration) {
+                self.0.lock().unwrap().insert(key.to_vec(), value.to_vec());
+            }
+            fn retrieve(&self, key: &[u8]) -> Option<Vec<u8>> {
+                self.0.lock().unwrap().get(key).cloned()
+            }
+            fn delete(&self, key: &[u8]) {
+                self.0.lock().unwrap().remove(key);
+            }
+        }
+
+        let cache = Cache::default();
+        let config = {
+            let mut config = config_builder(&security::DEFAULT)?;
+            config.add_session_ticket_key(b"key name", &[0; 32], SystemTime::now())?;
+            config.set_session_cache(cache.clone())?;
+            // resume with the session ID rather than a ticket
+            config.enable_session_tickets(false)?;
+            config.build()?
+        };
+
+        let mut pair = poll_tls_pair(tls_pair(config.clone()));
+        assert!(!pair.client.0.connection.resumed());
+        assert_eq!(cache.0.lock().unwrap().len(), 1);
+        let session = pair.client.0.connection.session()?;
+
+        let mut pair = tls_pair(config);
+        pair.client.0.connection.set_session_ticket(&session)?;
+        let pair = poll_tls_pair(pair);
+        assert!(pair.client.0.connection.resumed());
+        assert!(pair.server.0.connection.resumed());
+
+        Ok(())
+    }
+
     #[test]
     fn system_certs_loaded_by_default() {
         let keypair = CertKeyPair::default();